@@ -17,31 +17,51 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::fmt::Display;
 use std::io::{stdout, Stdout};
+use std::path::PathBuf;
+use std::pin::pin;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
 
-use clap::Parser;
-use crossterm::event::{KeyCode, KeyEventKind};
+use clap::{Parser, ValueEnum};
+use crossterm::event::{
+    DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEventKind, MouseButton, MouseEvent,
+    MouseEventKind,
+};
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
 use crossterm::{event, ExecutableCommand};
+use futures_util::StreamExt;
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
 use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Alignment, Constraint, Direction, Layout};
-use ratatui::style::Stylize;
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::Paragraph;
-use ratatui::Terminal;
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Sparkline, Tabs, Wrap};
+use ratatui::{Frame, Terminal};
+use serde::Deserialize;
 use tokio::select;
-use tokio::sync::mpsc::{channel, Receiver};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
 
-use arsnova_client::{Client, Feedback, FeedbackHandler, FeedbackValue};
+use arsnova_client::client::LoggedIn;
+use arsnova_client::models::{Answer, AnswerKind, AnswerOption, Comment, Content, ContentKind};
+use arsnova_client::{
+    Client, CommentEvent, ConnectionState, ContentEvent, Envelope, Feedback, FeedbackEvent,
+    FeedbackHandler, FeedbackHistory, FeedbackValue, RoomEvent, RoomInfo, ShortId,
+};
 
 #[derive(Parser)]
 #[command(author, version, about = "Terminal-based ARSnova live feedback client", long_about = None)]
 #[command(arg_required_else_help(true))]
 pub struct Cli {
-    #[arg(help = "Raum")]
-    room: String,
+    #[arg(
+        help = "Raum, mehrere Räume werden nebeneinander angezeigt",
+        required = true
+    )]
+    rooms: Vec<String>,
     #[arg(
         short = 'u',
         long = "url",
@@ -49,13 +69,419 @@ pub struct Cli {
         default_value = "https://ars.particify.de/api"
     )]
     url: String,
+    #[arg(
+        short = 't',
+        long = "theme",
+        help = "Farbschema",
+        value_enum,
+        default_value_t = ThemeName::Dark
+    )]
+    theme: ThemeName,
+    #[arg(long = "theme-file", help = "Datei mit eigenen Farben (JSON)")]
+    theme_file: Option<PathBuf>,
+    #[arg(
+        long = "join-url",
+        help = "URL zum Beitreten des Raums [Standard: <Server>/p/<Raum>]"
+    )]
+    join_url: Option<String>,
+    #[arg(
+        long = "history",
+        help = "Zeitraum des Stimmungsverlaufs in Minuten",
+        default_value_t = 10,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    history: u64,
+    #[arg(long = "ticker", help = "Neue Fragen als Laufschrift anzeigen")]
+    ticker: bool,
+    #[arg(long = "lang", help = "Sprache [Standard: Systemsprache]", value_enum)]
+    lang: Option<Lang>,
+}
+
+/// The language of the UI
+#[derive(Clone, Copy, ValueEnum)]
+enum Lang {
+    De,
+    En,
+}
+
+impl Lang {
+    /// Detects the language using the system locale, defaults to English
+    fn detect() -> Lang {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        match locale.starts_with("de") {
+            true => Lang::De,
+            false => Lang::En,
+        }
+    }
+
+    fn messages(self) -> &'static Messages {
+        match self {
+            Lang::De => &MESSAGES_DE,
+            Lang::En => &MESSAGES_EN,
+        }
+    }
+}
+
+/// The texts shown in the UI, `{}` is replaced by a value using `Messages::fill()`
+struct Messages {
+    participants: &'static str,
+    tab_feedback: &'static str,
+    tab_comments: &'static str,
+    tab_comments_new: &'static str,
+    tab_quiz: &'static str,
+    help: &'static str,
+    monitor_title: &'static str,
+    monitor_help: &'static str,
+    paused: &'static str,
+    ticker: &'static str,
+    connected: &'static str,
+    disconnected: &'static str,
+    reconnecting: &'static str,
+    feedback_values: [&'static str; 4],
+    feedback_stopped: &'static str,
+    votes: &'static str,
+    history: &'static str,
+    no_comments: &'static str,
+    no_content: &'static str,
+    answer_sent: &'static str,
+    cannot_answer: &'static str,
+    answer_with: &'static str,
+}
+
+const MESSAGES_DE: Messages = Messages {
+    participants: "{} Teilnehmer",
+    tab_feedback: "Feedback",
+    tab_comments: "Fragen",
+    tab_comments_new: "Fragen ({} neu)",
+    tab_quiz: "Quiz",
+    help: "Ansicht wechseln mit <Tab> - QR-Code mit <j> - Anhalten mit <p> - Beenden mit <Esc>",
+    monitor_title: "Live Feedback: {} Räume",
+    monitor_help: "Beenden mit <Esc>",
+    paused: "Angehalten - Fortsetzen mit <p>",
+    ticker: " Neue Frage ",
+    connected: "verbunden",
+    disconnected: "getrennt",
+    reconnecting: "verbinde neu…",
+    feedback_values: ["Super", "Gut", "Nicht so gut", "Schlecht"],
+    feedback_stopped: "Feedback gestoppt",
+    votes: "{} Antworten",
+    history: "Stimmung der letzten {} Minuten",
+    no_comments: "Keine Fragen vorhanden",
+    no_content: "Keine Frage freigegeben",
+    answer_sent: "Antwort gesendet",
+    cannot_answer: "Diese Frage kann hier nicht beantwortet werden",
+    answer_with: "Antworten mit <1> bis <{}>",
+};
+
+const MESSAGES_EN: Messages = Messages {
+    participants: "{} participants",
+    tab_feedback: "Feedback",
+    tab_comments: "Questions",
+    tab_comments_new: "Questions ({} new)",
+    tab_quiz: "Quiz",
+    help: "Switch view with <Tab> - QR code with <j> - Pause with <p> - Quit with <Esc>",
+    monitor_title: "Live Feedback: {} rooms",
+    monitor_help: "Quit with <Esc>",
+    paused: "Paused - resume with <p>",
+    ticker: " New question ",
+    connected: "connected",
+    disconnected: "disconnected",
+    reconnecting: "reconnecting…",
+    feedback_values: ["Great", "Good", "Not so good", "Bad"],
+    feedback_stopped: "Feedback stopped",
+    votes: "{} votes",
+    history: "Mood of the last {} minutes",
+    no_comments: "No questions available",
+    no_content: "No question published",
+    answer_sent: "Answer sent",
+    cannot_answer: "This question cannot be answered here",
+    answer_with: "Answer with <1> to <{}>",
+};
+
+impl Messages {
+    /// Replaces the placeholder of given text by given value
+    fn fill(text: &str, value: impl Display) -> String {
+        text.replacen("{}", &value.to_string(), 1)
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ThemeName {
+    Dark,
+    Light,
+    HighContrast,
+}
+
+/// The colors used to render the UI
+#[derive(Clone)]
+struct Theme {
+    background: Color,
+    text: Color,
+    header: Color,
+    header_text: Color,
+    accent: Color,
+    accent_text: Color,
+    bar: Color,
+    bar_background: Color,
+    highlight: Color,
+    success: Color,
+}
+
+impl Theme {
+    fn new(name: ThemeName) -> Theme {
+        match name {
+            ThemeName::Dark => Theme {
+                background: Color::Reset,
+                text: Color::White,
+                header: Color::Blue,
+                header_text: Color::White,
+                accent: Color::Magenta,
+                accent_text: Color::White,
+                bar: Color::Green,
+                bar_background: Color::Black,
+                highlight: Color::Yellow,
+                success: Color::Green,
+            },
+            ThemeName::Light => Theme {
+                background: Color::White,
+                text: Color::Black,
+                header: Color::Blue,
+                header_text: Color::White,
+                accent: Color::Magenta,
+                accent_text: Color::White,
+                bar: Color::Blue,
+                bar_background: Color::Gray,
+                highlight: Color::Red,
+                success: Color::Green,
+            },
+            ThemeName::HighContrast => Theme {
+                background: Color::Black,
+                text: Color::White,
+                header: Color::White,
+                header_text: Color::Black,
+                accent: Color::Yellow,
+                accent_text: Color::Black,
+                bar: Color::Yellow,
+                bar_background: Color::DarkGray,
+                highlight: Color::LightCyan,
+                success: Color::LightGreen,
+            },
+        }
+    }
+
+    /// Reads custom colors from given JSON file, e.g. `{"bar": "#00aa00", "text": "black"}`
+    ///
+    /// Colors not contained in the file are not changed.
+    fn with_file(mut self, path: &PathBuf) -> Result<Theme, String> {
+        let config = std::fs::read_to_string(path)
+            .map_err(|_| format!("Cannot read theme file {}!", path.display()))?;
+        let config = serde_json::from_str::<ThemeConfig>(&config)
+            .map_err(|err| format!("Cannot parse theme file: {}", err))?;
+
+        let colors = [
+            (config.background, &mut self.background),
+            (config.text, &mut self.text),
+            (config.header, &mut self.header),
+            (config.header_text, &mut self.header_text),
+            (config.accent, &mut self.accent),
+            (config.accent_text, &mut self.accent_text),
+            (config.bar, &mut self.bar),
+            (config.bar_background, &mut self.bar_background),
+            (config.highlight, &mut self.highlight),
+            (config.success, &mut self.success),
+        ];
+        for (value, color) in colors {
+            if let Some(value) = value {
+                *color =
+                    Color::from_str(&value).map_err(|_| format!("Invalid color: {}", value))?;
+            }
+        }
+        Ok(self)
+    }
+
+    fn text(&self) -> Style {
+        Style::default().fg(self.text)
+    }
+
+    fn header(&self) -> Style {
+        Style::default().fg(self.header_text).bg(self.header)
+    }
+
+    fn accent(&self) -> Style {
+        Style::default().fg(self.accent_text).bg(self.accent)
+    }
+}
+
+/// Custom colors of a theme file, using color names like `blue` or hex values like `#0000ff`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeConfig {
+    background: Option<String>,
+    text: Option<String>,
+    header: Option<String>,
+    header_text: Option<String>,
+    accent: Option<String>,
+    accent_text: Option<String>,
+    bar: Option<String>,
+    bar_background: Option<String>,
+    highlight: Option<String>,
+    success: Option<String>,
+}
+
+/// The interval to record unchanged feedback for the history
+const HISTORY_TICK: Duration = Duration::from_secs(5);
+
+/// The interval to scroll the ticker by one character
+const TICKER_TICK: Duration = Duration::from_millis(150);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tab {
+    Feedback,
+    Comments,
+    Quiz,
+}
+
+impl Tab {
+    fn next(self) -> Tab {
+        match self {
+            Tab::Feedback => Tab::Comments,
+            Tab::Comments => Tab::Quiz,
+            Tab::Quiz => Tab::Feedback,
+        }
+    }
+}
+
+/// An event changing the UI besides feedback and comments
+enum UiEvent {
+    SwitchTab(Tab),
+    ContentShown(Box<Content>),
+    ContentHidden,
+    Answered(usize),
+    ConnectionChanged(ConnectionState),
+    UserCountChanged(usize),
+    ToggleQrCode,
+    TogglePause,
+    /// The left mouse button was pressed or the screen was touched at given column and row
+    Click(u16, u16),
+}
+
+/// The channels connecting the UI with the client and the input handling
+struct UiChannels {
+    feedback: Receiver<Feedback>,
+    votes: Sender<FeedbackValue>,
+    comments: Receiver<Envelope<CommentEvent>>,
+    events: Receiver<UiEvent>,
+}
+
+/// Information about the room and view settings independent of the selected tab
+struct RoomView {
+    title: String,
+    disabled: bool,
+    join_url: String,
+    qr_code: String,
+    history: Duration,
+    ticker: bool,
+    messages: &'static Messages,
+}
+
+impl RoomView {
+    /// Creates the room view, the join URL is derived from the API URL if not given
+    fn new(room_info: &RoomInfo, cli: &Cli) -> RoomView {
+        let join_url = cli.join_url.clone().unwrap_or_else(|| {
+            format!(
+                "{}/p/{}",
+                cli.url.trim_end_matches('/').trim_end_matches("/api"),
+                room_info.short_id
+            )
+        });
+        let qr_code = QrCode::new(&join_url)
+            .map(|code| code.render::<Dense1x2>().quiet_zone(true).build())
+            .unwrap_or_default();
+        RoomView {
+            title: format!("Live Feedback: {} ({})", room_info.name, room_info.short_id),
+            disabled: room_info.is_closed() || room_info.is_feedback_locked(),
+            join_url,
+            qr_code,
+            history: Duration::from_secs(cli.history * 60),
+            ticker: cli.ticker,
+            messages: cli.lang.unwrap_or_else(Lang::detect).messages(),
+        }
+    }
+}
+
+/// The feedback of a room shown side by side with other rooms
+struct RoomPanel {
+    short_id: ShortId,
+    title: String,
+    feedback: Feedback,
+    disabled: bool,
+}
+
+impl RoomPanel {
+    fn apply(&mut self, event: FeedbackEvent) {
+        match event {
+            FeedbackEvent::Changed(feedback) => self.feedback = feedback,
+            FeedbackEvent::Started => self.disabled = false,
+            FeedbackEvent::Stopped => self.disabled = true,
+            _ => {}
+        }
+    }
+}
+
+/// A single line scrolling through new comments from right to left
+#[derive(Default)]
+struct Ticker {
+    item: usize,
+    offset: usize,
+}
+
+impl Ticker {
+    /// Returns the visible part of the current comment of given width
+    ///
+    /// Continues with the next comment once the current one has scrolled through.
+    fn line(&mut self, comments: &[&str], width: usize) -> String {
+        if comments.is_empty() {
+            *self = Ticker::default();
+            return String::new();
+        }
+        let text = |item: usize| format!("{}{}", " ".repeat(width), comments[item]);
+        if self.offset >= text(self.item % comments.len()).chars().count() {
+            self.item += 1;
+            self.offset = 0;
+        }
+        self.item %= comments.len();
+        text(self.item)
+            .chars()
+            .skip(self.offset)
+            .take(width)
+            .collect()
+    }
+}
+
+/// A comment shown in the comments tab, new comments are highlighted until the tab is left
+struct CommentItem {
+    comment: Comment,
+    new: bool,
 }
 
 #[tokio::main(worker_threads = 2)]
 async fn main() -> Result<(), String> {
     let cli = Cli::parse();
 
-    let client = match Client::new(&cli.url) {
+    let theme = match &cli.theme_file {
+        Some(path) => Theme::new(cli.theme).with_file(path)?,
+        None => Theme::new(cli.theme),
+    };
+
+    let client = match Client::builder(&cli.url)
+        .dedup_votes(Duration::from_secs(5))
+        .reconnect_delay(Duration::from_secs(2))
+        .build()
+    {
         Ok(client) => client,
         Err(_) => return Err("Cannot create client!".to_string()),
     };
@@ -65,67 +491,187 @@ async fn main() -> Result<(), String> {
         .await
         .map_err(|_| "Cannot login!".to_string())?;
 
+    if cli.rooms.len() > 1 {
+        return monitor(&client, &cli, &theme).await;
+    }
+    let room = ShortId::from(&cli.rooms[0]);
+
     let (in_tx, in_rx) = channel::<Feedback>(10);
     let (out_tx, out_rx) = channel::<FeedbackValue>(10);
+    let (comment_tx, comment_rx) = channel::<Envelope<CommentEvent>>(10);
+    let (content_tx, mut content_rx) = channel::<Envelope<ContentEvent>>(10);
+    let (answer_tx, mut answer_rx) = channel::<usize>(10);
+    let (ui_tx, ui_rx) = channel::<UiEvent>(10);
 
-    let _ = in_tx
-        .clone()
-        .send(client.get_feedback(&cli.room).await.unwrap())
-        .await;
+    let comments = client
+        .get_comments(&room)
+        .await
+        .map_err(|_| "Cannot request comments!".to_string())?;
 
     stdout()
         .execute(EnterAlternateScreen)
+        .and_then(|stdout| stdout.execute(EnableMouseCapture))
         .map_err(|_| String::new())?;
     enable_raw_mode().map_err(|_| String::new())?;
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout())).map_err(|_| String::new())?;
     terminal.clear().map_err(|_| String::new())?;
 
-    let l1 = client.on_feedback_changed(&cli.room, FeedbackHandler::SenderReceiver(in_tx, out_rx));
+    let l1 = client.on_feedback_changed(&room, FeedbackHandler::SenderReceiver(in_tx, out_rx));
+    let l4 = client.on_comments_changed(&room, comment_tx);
+    let l5 = client.on_content_changed(&room, content_tx);
 
-    let room_info = client.get_room_info(&cli.room).await.map_err(|_| {
-        let _ = stdout().execute(LeaveAlternateScreen).map_err(|_| ());
+    let quiz_tx = ui_tx.clone();
+    let l6 = async {
+        let mut current: Option<Content> = None;
+        loop {
+            select! {
+                Some(envelope) = content_rx.recv() => {
+                    let ContentEvent::StateChanged(id, state) = envelope.event;
+                    if state.visible {
+                        if let Ok(content) = client.get_content(&room, &id).await {
+                            current = Some(content.clone());
+                            let _ = quiz_tx.send(UiEvent::ContentShown(Box::new(content))).await;
+                            if let Ok(Some(Answer {
+                                kind: AnswerKind::Choice { selected_choice_indexes },
+                                ..
+                            })) = client.get_my_answer(&room, &id).await
+                            {
+                                if let Some(idx) = selected_choice_indexes.first() {
+                                    current = None;
+                                    let _ = quiz_tx.send(UiEvent::Answered(*idx)).await;
+                                }
+                            }
+                        }
+                    } else if current.as_ref().is_some_and(|content| content.id == id) {
+                        current = None;
+                        let _ = quiz_tx.send(UiEvent::ContentHidden).await;
+                    }
+                },
+                Some(idx) = answer_rx.recv() => {
+                    let Some(content) = current.take() else {
+                        continue;
+                    };
+                    if idx < choice_options(&content).len()
+                        && client.answer_choice(&room, &content, vec![idx]).await.is_ok()
+                    {
+                        let _ = quiz_tx.send(UiEvent::Answered(idx)).await;
+                    } else {
+                        current = Some(content);
+                    }
+                },
+                else => break,
+            }
+        }
+    };
+
+    let connection_tx = ui_tx.clone();
+    let mut connection_rx = client.connection_state();
+    let l7 = async move {
+        loop {
+            let state = *connection_rx.borrow_and_update();
+            let _ = connection_tx.send(UiEvent::ConnectionChanged(state)).await;
+            if connection_rx.changed().await.is_err() {
+                break;
+            }
+        }
+    };
+
+    let room_info = client.get_room_info(&room).await.map_err(|_| {
+        let _ = stdout()
+            .execute(DisableMouseCapture)
+            .and_then(|stdout| stdout.execute(LeaveAlternateScreen));
         let _ = disable_raw_mode().map_err(|_| ()).map_err(|_| ());
         "Cannot request room information!".to_string()
     })?;
-    let room_stats = client.get_room_stats(&cli.room).await.map_err(|err| {
-        let _ = stdout().execute(LeaveAlternateScreen).map_err(|_| ());
+    let room_stats = client.get_room_stats(&room).await.map_err(|err| {
+        let _ = stdout()
+            .execute(DisableMouseCapture)
+            .and_then(|stdout| stdout.execute(LeaveAlternateScreen));
         let _ = disable_raw_mode().map_err(|_| ()).map_err(|_| ());
         err.to_string()
     })?;
-    let title = format!(
-        "Live Feedback: {} ({}) - 👥: {}",
-        room_info.name, room_info.short_id, room_stats.room_user_count
-    );
+    let room_view = RoomView::new(&room_info, &cli);
+
+    let _ = ui_tx
+        .send(UiEvent::UserCountChanged(room_stats.room_user_count))
+        .await;
+    let user_count_tx = ui_tx.clone();
+    let l8 = async {
+        let (tx, mut rx) = channel::<Envelope<usize>>(10);
+        let forward = async move {
+            while let Some(envelope) = rx.recv().await {
+                let _ = user_count_tx
+                    .send(UiEvent::UserCountChanged(envelope.event))
+                    .await;
+            }
+        };
+        let _ = tokio::join!(
+            client.on_user_count_changed(&room, Duration::from_secs(5), tx),
+            forward
+        );
+        // Keep the last known count if polling ends
+        std::future::pending::<()>().await
+    };
 
     let l2 = create_ui(
         &mut terminal,
-        &title,
-        room_info.is_closed() || room_info.is_feedback_locked(),
-        in_rx,
+        &theme,
+        &room_view,
+        comments,
+        UiChannels {
+            feedback: in_rx,
+            votes: out_tx.clone(),
+            comments: comment_rx,
+            events: ui_rx,
+        },
     );
 
     let l3 = tokio::spawn(async move {
+        let mut tab = Tab::Feedback;
         loop {
-            if event::poll(std::time::Duration::from_millis(16))
+            if event::poll(Duration::from_millis(16))
                 .map_err(|_| ())
                 .is_ok()
             {
-                if let event::Event::Key(key) = event::read().map_err(|_| ()).unwrap() {
+                let next = event::read().map_err(|_| ()).unwrap();
+                if let event::Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::Down(MouseButton::Left),
+                    column,
+                    row,
+                    ..
+                }) = next
+                {
+                    let _ = ui_tx.send(UiEvent::Click(column, row)).await;
+                }
+                if let event::Event::Key(key) = next {
                     if key.kind == KeyEventKind::Press {
-                        match key.code {
-                            KeyCode::Esc => break,
-                            KeyCode::Char('a') | KeyCode::Char('1') => {
+                        match (tab, key.code) {
+                            (_, KeyCode::Esc) => break,
+                            (_, KeyCode::Tab) => {
+                                tab = tab.next();
+                                let _ = ui_tx.send(UiEvent::SwitchTab(tab)).await;
+                            }
+                            (_, KeyCode::Char('j')) => {
+                                let _ = ui_tx.send(UiEvent::ToggleQrCode).await;
+                            }
+                            (_, KeyCode::Char('p')) => {
+                                let _ = ui_tx.send(UiEvent::TogglePause).await;
+                            }
+                            (Tab::Feedback, KeyCode::Char('a') | KeyCode::Char('1')) => {
                                 let _ = out_tx.send(FeedbackValue::VeryGood).await;
                             }
-                            KeyCode::Char('b') | KeyCode::Char('2') => {
+                            (Tab::Feedback, KeyCode::Char('b') | KeyCode::Char('2')) => {
                                 let _ = out_tx.send(FeedbackValue::Good).await;
                             }
-                            KeyCode::Char('c') | KeyCode::Char('3') => {
+                            (Tab::Feedback, KeyCode::Char('c') | KeyCode::Char('3')) => {
                                 let _ = out_tx.send(FeedbackValue::Bad).await;
                             }
-                            KeyCode::Char('d') | KeyCode::Char('4') => {
+                            (Tab::Feedback, KeyCode::Char('d') | KeyCode::Char('4')) => {
                                 let _ = out_tx.send(FeedbackValue::VeryBad).await;
                             }
+                            (Tab::Quiz, KeyCode::Char(c @ '1'..='9')) => {
+                                let _ = answer_tx.send(c as usize - '1' as usize).await;
+                            }
                             _ => {}
                         };
                     }
@@ -137,10 +683,17 @@ async fn main() -> Result<(), String> {
     select! {
         _ = l1 => {},
         _ = l2 => {},
-        _ = l3 => {}
+        _ = l3 => {},
+        _ = l4 => {},
+        _ = l5 => {},
+        _ = l6 => {},
+        _ = l7 => {},
+        _ = l8 => {}
     }
 
-    let _ = stdout().execute(LeaveAlternateScreen).map_err(|_| ());
+    let _ = stdout()
+        .execute(DisableMouseCapture)
+        .and_then(|stdout| stdout.execute(LeaveAlternateScreen));
     let _ = disable_raw_mode().map_err(|_| ()).map_err(|_| ());
 
     Ok(())
@@ -148,11 +701,509 @@ async fn main() -> Result<(), String> {
 
 async fn create_ui(
     terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-    title: &str,
-    disabled: bool,
-    mut rx: Receiver<Feedback>,
+    theme: &Theme,
+    room: &RoomView,
+    comments: Vec<Comment>,
+    channels: UiChannels,
 ) -> Result<(), ()> {
-    const ICONS: [&str; 4] = ["Super", "Gut", "Nicht so gut", "Schlecht"];
+    let UiChannels {
+        feedback: mut rx,
+        votes,
+        comments: mut comment_rx,
+        events: mut ui_rx,
+    } = channels;
+    let mut tab = Tab::Feedback;
+    let mut content: Option<Content> = None;
+    let mut answered: Option<usize> = None;
+    let mut feedback = Feedback::from_values([0; 4]);
+    let mut connection = ConnectionState::Disconnected;
+    let mut user_count = 0;
+    let mut show_qr_code = false;
+    // The feedback shown while paused, changes are still recorded in the history
+    let mut paused: Option<Feedback> = None;
+    // The areas of the vote buttons as last drawn, empty if not shown
+    let mut buttons: Vec<Rect> = vec![];
+    let mut history = FeedbackHistory::new(10_000);
+    // Record unchanged feedback periodically to extend the history up to now
+    let mut history_tick = tokio::time::interval(HISTORY_TICK);
+    let mut ticker = Ticker::default();
+    let mut ticker_tick = tokio::time::interval(TICKER_TICK);
+    // Do not catch up with ticks missed while no new comments were shown
+    ticker_tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut comments = comments
+        .into_iter()
+        .map(|comment| CommentItem {
+            comment,
+            new: false,
+        })
+        .collect::<Vec<_>>();
+
+    loop {
+        select! {
+            Some(next) = rx.recv() => {
+                history.record(next.clone());
+                feedback = next;
+            },
+            _ = history_tick.tick() => {
+                if feedback.count_votes() > 0 {
+                    history.record(feedback.clone());
+                }
+            },
+            _ = ticker_tick.tick(), if room.ticker && comments.iter().any(|item| item.new) => {
+                ticker.offset += 1;
+            },
+            Some(envelope) = comment_rx.recv() => apply_comment_event(&mut comments, envelope.event),
+            Some(event) = ui_rx.recv() => match event {
+                UiEvent::SwitchTab(next) => {
+                    if tab == Tab::Comments {
+                        comments.iter_mut().for_each(|item| item.new = false);
+                    }
+                    tab = next;
+                }
+                UiEvent::ContentShown(next) => {
+                    content = Some(*next);
+                    answered = None;
+                }
+                UiEvent::ContentHidden => {
+                    content = None;
+                    answered = None;
+                }
+                UiEvent::Answered(idx) => answered = Some(idx),
+                UiEvent::ConnectionChanged(state) => connection = state,
+                UiEvent::UserCountChanged(count) => user_count = count,
+                UiEvent::ToggleQrCode => show_qr_code = !show_qr_code,
+                UiEvent::TogglePause => {
+                    paused = match paused {
+                        Some(_) => None,
+                        None => Some(feedback.clone()),
+                    }
+                }
+                UiEvent::Click(column, row) => {
+                    let clicked = buttons.iter().position(|area| {
+                        (area.left()..area.right()).contains(&column)
+                            && (area.top()..area.bottom()).contains(&row)
+                    });
+                    let value = match clicked {
+                        Some(0) => FeedbackValue::VeryGood,
+                        Some(1) => FeedbackValue::Good,
+                        Some(2) => FeedbackValue::Bad,
+                        Some(3) => FeedbackValue::VeryBad,
+                        _ => continue,
+                    };
+                    let _ = votes.send(value).await;
+                    continue;
+                }
+            },
+            else => return Ok(()),
+        }
+
+        let new_comments = comments
+            .iter()
+            .filter(|item| item.new)
+            .map(|item| item.comment.body.as_str())
+            .collect::<Vec<_>>();
+        let ticker_height = match room.ticker && !new_comments.is_empty() {
+            true => 1,
+            false => 0,
+        };
+
+        buttons.clear();
+        let _ = terminal.draw(|frame| {
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Max(1),
+                    Constraint::Max(1),
+                    Constraint::Min(1),
+                    Constraint::Length(ticker_height),
+                    Constraint::Max(1),
+                ])
+                .split(frame.size());
+
+            frame.render_widget(
+                Block::default().style(theme.text().bg(theme.background)),
+                frame.size(),
+            );
+
+            frame.render_widget(
+                Paragraph::new(format!(
+                    "{} - {}",
+                    room.title,
+                    Messages::fill(room.messages.participants, user_count)
+                ))
+                .style(theme.header())
+                .bold()
+                .alignment(Alignment::Center),
+                layout[0],
+            );
+
+            let comments_title = match new_comments.len() {
+                0 => room.messages.tab_comments.to_string(),
+                count => Messages::fill(room.messages.tab_comments_new, count),
+            };
+            frame.render_widget(
+                Tabs::new(vec![
+                    room.messages.tab_feedback.to_string(),
+                    comments_title,
+                    room.messages.tab_quiz.to_string(),
+                ])
+                .select(match tab {
+                    Tab::Feedback => 0,
+                    Tab::Comments => 1,
+                    Tab::Quiz => 2,
+                })
+                .style(theme.text())
+                .highlight_style(theme.accent().bold()),
+                layout[1],
+            );
+
+            let qr_code_width = match show_qr_code {
+                true => room.qr_code.lines().map(|line| line.chars().count()).max(),
+                false => None,
+            };
+            let main_layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Min(1),
+                    Constraint::Length(qr_code_width.map_or(0, |width| width as u16 + 2)),
+                ])
+                .split(layout[2]);
+
+            match tab {
+                Tab::Feedback => {
+                    let feedback_layout = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([
+                            Constraint::Max(8),
+                            Constraint::Max(1),
+                            Constraint::Min(0),
+                            Constraint::Max(6),
+                        ])
+                        .split(main_layout[0]);
+                    render_feedback(
+                        frame,
+                        feedback_layout[0],
+                        theme,
+                        room.messages,
+                        paused.as_ref().unwrap_or(&feedback),
+                        room.disabled,
+                        connection == ConnectionState::Connected,
+                    );
+                    buttons = render_buttons(frame, feedback_layout[1], theme, room.messages);
+                    render_history(frame, feedback_layout[3], theme, &history, room);
+                }
+                Tab::Comments => {
+                    render_comments(frame, main_layout[0], theme, room.messages, &comments)
+                }
+                Tab::Quiz => render_quiz(
+                    frame,
+                    main_layout[0],
+                    theme,
+                    room.messages,
+                    content.as_ref(),
+                    answered,
+                ),
+            }
+
+            if show_qr_code {
+                render_qr_code(frame, main_layout[1], theme, room);
+            }
+
+            if ticker_height > 0 {
+                let label = room.messages.ticker;
+                let width = layout[3].width.saturating_sub(label.chars().count() as u16);
+                let line = ticker
+                    .line(&new_comments, width as usize)
+                    .replace('\n', " ");
+                frame.render_widget(
+                    Paragraph::new(Line::from(vec![
+                        Span::styled(label, theme.accent().bold()),
+                        Span::styled(line, theme.text().fg(theme.highlight)),
+                    ])),
+                    layout[3],
+                );
+            }
+
+            let footer_layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(1), Constraint::Max(16)])
+                .split(layout[4]);
+
+            let help = match paused {
+                Some(_) => Paragraph::new(room.messages.paused).style(theme.accent().bold()),
+                None => Paragraph::new(room.messages.help).style(theme.header()),
+            };
+            frame.render_widget(help.alignment(Alignment::Left), footer_layout[0]);
+            frame.render_widget(
+                connection_status(theme, room.messages, connection),
+                footer_layout[1],
+            );
+        });
+    }
+}
+
+/// Shows the feedback of multiple rooms side by side using a single websocket connection
+///
+/// Votes cannot be sent in this view as the keys would be ambiguous.
+async fn monitor(client: &Client<LoggedIn>, cli: &Cli, theme: &Theme) -> Result<(), String> {
+    let messages = cli.lang.unwrap_or_else(Lang::detect).messages();
+    let short_ids = cli.rooms.iter().map(ShortId::from).collect::<Vec<_>>();
+
+    let mut panels = vec![];
+    for short_id in &short_ids {
+        let room_info = client
+            .get_room_info(short_id)
+            .await
+            .map_err(|_| format!("Cannot request room information of {}!", short_id))?;
+        let feedback = client
+            .get_feedback(short_id)
+            .await
+            .map_err(|_| format!("Cannot request feedback of {}!", short_id))?;
+        panels.push(RoomPanel {
+            short_id: short_id.clone(),
+            title: format!("{} ({})", room_info.name, room_info.short_id),
+            feedback,
+            disabled: room_info.is_closed() || room_info.is_feedback_locked(),
+        });
+    }
+
+    let events = client
+        .monitor_rooms(&short_ids)
+        .await
+        .map_err(|_| "Cannot monitor rooms!".to_string())?;
+    let mut events = pin!(events);
+    let mut connection_rx = client.connection_state();
+
+    stdout()
+        .execute(EnterAlternateScreen)
+        .map_err(|_| String::new())?;
+    enable_raw_mode().map_err(|_| String::new())?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout())).map_err(|_| String::new())?;
+    terminal.clear().map_err(|_| String::new())?;
+
+    let mut keys = tokio::spawn(async {
+        loop {
+            if matches!(event::poll(Duration::from_millis(16)), Ok(true)) {
+                if let Ok(event::Event::Key(key)) = event::read() {
+                    if key.kind == KeyEventKind::Press && key.code == KeyCode::Esc {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    loop {
+        let connection = *connection_rx.borrow_and_update();
+        let _ = terminal.draw(|frame| {
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Max(1), Constraint::Min(1), Constraint::Max(1)])
+                .split(frame.size());
+
+            frame.render_widget(
+                Block::default().style(theme.text().bg(theme.background)),
+                frame.size(),
+            );
+
+            frame.render_widget(
+                Paragraph::new(Messages::fill(messages.monitor_title, panels.len()))
+                    .style(theme.header())
+                    .bold()
+                    .alignment(Alignment::Center),
+                layout[0],
+            );
+
+            let panel_layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(
+                    panels
+                        .iter()
+                        .map(|_| Constraint::Ratio(1, panels.len() as u32))
+                        .collect::<Vec<_>>(),
+                )
+                .split(layout[1]);
+
+            for (panel, area) in panels.iter().zip(panel_layout.iter()) {
+                let block = Block::default()
+                    .borders(Borders::ALL)
+                    .title(panel.title.as_str())
+                    .title_style(theme.text().bold())
+                    .border_style(theme.text());
+                let inner = block.inner(*area);
+                frame.render_widget(block, *area);
+                render_feedback(
+                    frame,
+                    inner,
+                    theme,
+                    messages,
+                    &panel.feedback,
+                    panel.disabled,
+                    connection == ConnectionState::Connected,
+                );
+            }
+
+            let footer_layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(1), Constraint::Max(16)])
+                .split(layout[2]);
+
+            frame.render_widget(
+                Paragraph::new(messages.monitor_help)
+                    .style(theme.header())
+                    .alignment(Alignment::Left),
+                footer_layout[0],
+            );
+            frame.render_widget(
+                connection_status(theme, messages, connection),
+                footer_layout[1],
+            );
+        });
+
+        select! {
+            next = events.next() => match next {
+                Some((short_id, envelope)) => {
+                    let panel = panels.iter_mut().find(|panel| panel.short_id == short_id);
+                    if let (Some(panel), RoomEvent::Feedback(event)) = (panel, envelope.event) {
+                        panel.apply(event);
+                    }
+                }
+                None => break,
+            },
+            _ = connection_rx.changed() => {},
+            _ = &mut keys => break,
+        }
+    }
+
+    let _ = stdout().execute(LeaveAlternateScreen).map_err(|_| ());
+    let _ = disable_raw_mode().map_err(|_| ()).map_err(|_| ());
+
+    Ok(())
+}
+
+/// Returns the footer indicator of the websocket connection state
+fn connection_status(
+    theme: &Theme,
+    messages: &Messages,
+    state: ConnectionState,
+) -> Paragraph<'static> {
+    let status = match state {
+        ConnectionState::Connected => Span::raw(messages.connected).white().on_green(),
+        ConnectionState::Disconnected => Span::raw(messages.disconnected).white().on_red(),
+        ConnectionState::Reconnecting => Span::raw(messages.reconnecting).black().on_yellow(),
+    };
+    Paragraph::new(Line::from(status))
+        .style(theme.header())
+        .alignment(Alignment::Right)
+}
+
+/// Renders the average score of the feedback within given duration as a sparkline
+///
+/// Each column shows the average score of an equal part of the duration. Parts without
+/// changes show the score of the previous part.
+fn render_history(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    history: &FeedbackHistory,
+    room: &RoomView,
+) {
+    let duration = room.history;
+    let width = area.width.max(1) as u32;
+    let interval = (duration / width).max(Duration::from_secs(1));
+    let since = SystemTime::now() - duration;
+
+    let mut score: Option<f32> = None;
+    let mut data = vec![];
+    let mut next_start = None;
+    for bucket in history.aggregate(interval) {
+        // Fill intervals without snapshots within the shown duration with the previous score
+        if let Some(mut start) = next_start {
+            let skipped =
+                since.duration_since(start).unwrap_or_default().as_millis() / interval.as_millis();
+            start += interval * skipped as u32;
+            while start < bucket.start {
+                if start + interval > since {
+                    data.push(score.map_or(0, |score| (score * 100.0).round() as u64));
+                }
+                start += interval;
+            }
+        }
+        score = bucket.average_score.or(score);
+        if bucket.start + interval > since {
+            data.push(score.map_or(0, |score| (score * 100.0).round() as u64));
+        }
+        next_start = Some(bucket.start + interval);
+    }
+    let skip = data.len().saturating_sub(width as usize);
+
+    let minutes = duration.as_secs() / 60;
+    frame.render_widget(
+        Sparkline::default()
+            .block(
+                Block::default()
+                    .title(Messages::fill(room.messages.history, minutes))
+                    .title_style(theme.text()),
+            )
+            .data(&data[skip..])
+            .max(100)
+            .style(Style::default().fg(theme.bar).bg(theme.bar_background)),
+        area,
+    );
+}
+
+/// Renders the QR code to join the room followed by the join URL
+///
+/// The QR code is always rendered dark on light to be scannable using any theme.
+fn render_qr_code(frame: &mut Frame, area: Rect, theme: &Theme, room: &RoomView) {
+    let height = room.qr_code.lines().count() as u16;
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(height), Constraint::Min(1)])
+        .margin(1)
+        .split(area);
+
+    frame.render_widget(
+        Paragraph::new(room.qr_code.as_str()).black().on_white(),
+        layout[0],
+    );
+    frame.render_widget(
+        Paragraph::new(room.join_url.as_str())
+            .style(theme.text())
+            .wrap(Wrap { trim: true })
+            .alignment(Alignment::Center),
+        layout[1],
+    );
+}
+
+/// Applies a comment event to the comment list, created comments are marked as new
+fn apply_comment_event(comments: &mut Vec<CommentItem>, event: CommentEvent) {
+    match event {
+        CommentEvent::Created(comment) if comment.ack => {
+            comments.push(CommentItem { comment, new: true })
+        }
+        CommentEvent::Created(_) => {}
+        CommentEvent::Patched(id, changes) => {
+            if let Some(item) = comments.iter_mut().find(|item| item.comment.id == id) {
+                item.comment.apply(&changes);
+            }
+        }
+        CommentEvent::Highlighted(_, _) => {}
+        CommentEvent::Deleted(id) => comments.retain(|item| item.comment.id != id),
+    }
+}
+
+/// Renders the feedback bars, which are dimmed if feedback changes are not received
+fn render_feedback(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    messages: &Messages,
+    feedback: &Feedback,
+    disabled: bool,
+    connected: bool,
+) {
+    let labels = messages.feedback_values;
 
     let feedback_paragraph =
         |feedback: &Feedback, idx: usize, width: usize| -> Paragraph<'static> {
@@ -164,7 +1215,7 @@ async fn create_ui(
                 _ => 0,
             };
 
-            let icons = ICONS
+            let icons = labels
                 .iter()
                 .map(|icon| format!("{: <12}", icon))
                 .collect::<Vec<_>>();
@@ -177,7 +1228,7 @@ async fn create_ui(
                 _ => "            ",
             };
 
-            let width = width - 24;
+            let width = width.saturating_sub(24);
 
             let l = ((value as f32 / feedback.count_votes() as f32) * width as f32) as usize;
 
@@ -186,108 +1237,204 @@ async fn create_ui(
                     Span::raw(format!("{} : ", icon)),
                     Span::raw(format!("[{: >5}] ", value)).dim(),
                     Span::raw("■".to_string().repeat(l).to_string())
-                        .green()
-                        .on_black(),
-                    Span::raw(" ".to_string().repeat(width - l).to_string()).on_black(),
+                        .fg(theme.bar)
+                        .bg(theme.bar_background),
+                    Span::raw(" ".to_string().repeat(width - l).to_string())
+                        .bg(theme.bar_background),
                 ])),
                 _ => Paragraph::default(),
             }
         };
 
-    loop {
-        let feedback = match rx.recv().await {
-            Some(feedback) => feedback,
-            _ => continue,
-        };
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Max(6), Constraint::Max(2), Constraint::Min(0)])
+        .split(area);
 
-        let _ = terminal.draw(|frame| {
-            let layout = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Max(1),
-                    Constraint::Max(6),
-                    Constraint::Max(2),
-                    Constraint::Max(1),
-                    Constraint::Min(1),
-                    Constraint::Max(1),
-                ])
-                .split(frame.size());
+    if disabled {
+        frame.render_widget(
+            Paragraph::new(messages.feedback_stopped)
+                .style(theme.text())
+                .alignment(Alignment::Center),
+            layout[1],
+        )
+    } else {
+        frame.render_widget(
+            Paragraph::new(Messages::fill(messages.votes, feedback.count_votes()))
+                .style(theme.text())
+                .bold()
+                .alignment(Alignment::Center),
+            layout[1],
+        );
+    }
 
-            frame.render_widget(
-                Paragraph::new(title)
-                    .white()
-                    .on_blue()
-                    .bold()
-                    .alignment(Alignment::Center),
-                layout[0],
-            );
+    let feedback_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Max(1),
+            Constraint::Max(1),
+            Constraint::Max(1),
+            Constraint::Max(1),
+        ])
+        .margin(1)
+        .split(layout[0]);
 
-            if disabled {
-                frame.render_widget(
-                    Paragraph::new("Feedback gestoppt")
-                        .white()
-                        .alignment(Alignment::Center),
-                    layout[2],
-                )
-            } else {
-                frame.render_widget(
-                    Paragraph::new(format!("{} Antworten", feedback.count_votes()))
-                        .white()
-                        .bold()
-                        .alignment(Alignment::Center),
-                    layout[2],
-                );
-            }
+    [0usize, 1, 2, 3].iter().for_each(|&idx| {
+        let paragraph = feedback_paragraph(feedback, idx, feedback_layout[idx].width as usize);
+        frame.render_widget(
+            match connected {
+                true => paragraph,
+                false => paragraph.dim(),
+            },
+            feedback_layout[idx],
+        )
+    });
+}
 
+/// Renders the keys to send feedback and returns the areas of the buttons
+fn render_buttons(frame: &mut Frame, area: Rect, theme: &Theme, messages: &Messages) -> Vec<Rect> {
+    let button_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    messages
+        .feedback_values
+        .iter()
+        .enumerate()
+        .for_each(|(idx, label)| {
             frame.render_widget(
-                Paragraph::new("Beenden mit <Esc>")
-                    .on_blue()
-                    .alignment(Alignment::Left),
-                layout[5],
-            );
+                Paragraph::new(Line::from(vec![
+                    Span::styled(format!(" {} ", idx + 1), theme.accent()).bold(),
+                    Span::raw(format!("{: ^14}", label))
+                        .fg(theme.text)
+                        .bg(theme.bar_background),
+                ]))
+                .alignment(Alignment::Center),
+                button_layout[idx],
+            )
+        });
 
-            let feedback_layout = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Max(1),
-                    Constraint::Max(1),
-                    Constraint::Max(1),
-                    Constraint::Max(1),
-                ])
-                .margin(1)
-                .split(layout[1]);
+    button_layout[..4].to_vec()
+}
 
-            [0usize, 1, 2, 3].iter().for_each(|&idx| {
-                frame.render_widget(
-                    feedback_paragraph(&feedback, idx, feedback_layout[idx].width as usize),
-                    feedback_layout[idx],
-                )
-            });
+fn render_comments(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    messages: &Messages,
+    comments: &[CommentItem],
+) {
+    if comments.is_empty() {
+        frame.render_widget(
+            Paragraph::new(messages.no_comments)
+                .style(theme.text())
+                .alignment(Alignment::Center),
+            area,
+        );
+        return;
+    }
 
-            let button_layout = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([
-                    Constraint::Percentage(25),
-                    Constraint::Percentage(25),
-                    Constraint::Percentage(25),
-                    Constraint::Percentage(25),
-                    Constraint::Min(0),
-                ])
-                .split(layout[3]);
+    let mut sorted = comments.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|item| std::cmp::Reverse(item.comment.score));
 
-            ICONS.iter().enumerate().for_each(|(idx, label)| {
-                frame.render_widget(
-                    Paragraph::new(Line::from(vec![
-                        Span::raw(format!(" {} ", idx + 1))
-                            .white()
-                            .on_magenta()
-                            .bold(),
-                        Span::raw(format!("{: ^14}", label)).white().on_black(),
-                    ]))
-                    .alignment(Alignment::Center),
-                    button_layout[idx],
-                )
-            });
-        });
+    let items = sorted
+        .into_iter()
+        .map(|item| {
+            let line = Line::from(vec![
+                Span::raw(format!(" [{: >4}] ", item.comment.score)).dim(),
+                Span::raw(item.comment.body.replace('\n', " ")),
+            ]);
+            match item.new {
+                true => ListItem::new(line).fg(theme.highlight).bold(),
+                false => ListItem::new(line).style(theme.text()),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    frame.render_widget(List::new(items), area);
+}
+
+/// Returns the answer options of a content that can be answered by choosing a single option
+fn choice_options(content: &Content) -> &[AnswerOption] {
+    match &content.kind {
+        ContentKind::Choice { options, .. } | ContentKind::Binary { options, .. } => options,
+        _ => &[],
     }
 }
+
+fn render_quiz(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    messages: &Messages,
+    content: Option<&Content>,
+    answered: Option<usize>,
+) {
+    let Some(content) = content else {
+        frame.render_widget(
+            Paragraph::new(messages.no_content)
+                .style(theme.text())
+                .alignment(Alignment::Center),
+            area,
+        );
+        return;
+    };
+
+    let options = choice_options(content);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Max(2),
+            Constraint::Max(3),
+            Constraint::Min(1),
+            Constraint::Max(1),
+        ])
+        .split(area);
+
+    frame.render_widget(
+        Paragraph::new(content.subject.clone())
+            .style(theme.text())
+            .bold(),
+        layout[0],
+    );
+    frame.render_widget(
+        Paragraph::new(content.body.clone()).style(theme.text()),
+        layout[1],
+    );
+
+    let items = options
+        .iter()
+        .enumerate()
+        .map(|(idx, option)| {
+            let line = Line::from(vec![
+                Span::styled(format!(" {} ", idx + 1), theme.accent()).bold(),
+                Span::raw(format!(" {}", option.label)),
+            ]);
+            match answered {
+                Some(answered) if answered == idx => ListItem::new(line).fg(theme.success).bold(),
+                _ => ListItem::new(line).style(theme.text()),
+            }
+        })
+        .collect::<Vec<_>>();
+    frame.render_widget(List::new(items), layout[2]);
+
+    let status = match (answered, options.len()) {
+        (Some(_), _) => messages.answer_sent.to_string(),
+        (None, 0) => messages.cannot_answer.to_string(),
+        (None, count) => Messages::fill(messages.answer_with, count.min(9)),
+    };
+    frame.render_widget(
+        Paragraph::new(status)
+            .style(theme.text())
+            .alignment(Alignment::Center),
+        layout[3],
+    );
+}
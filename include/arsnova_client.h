@@ -0,0 +1,171 @@
+#ifndef ARSNOVA_CLIENT_H
+#define ARSNOVA_CLIENT_H
+
+/* This file is generated by cbindgen using `cargo build --features ffi`. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The result of FFI functions
+ */
+typedef enum ArsnovaResult {
+  ARSNOVA_RESULT_OK = 0,
+  ARSNOVA_RESULT_CONNECTION_ERROR = 1,
+  ARSNOVA_RESULT_LOGIN_ERROR = 2,
+  ARSNOVA_RESULT_ROOM_NOT_FOUND_ERROR = 3,
+  ARSNOVA_RESULT_PARSER_ERROR = 4,
+  ARSNOVA_RESULT_URL_ERROR = 5,
+  ARSNOVA_RESULT_FEEDBACK_VALUE_ERROR = 6,
+  ARSNOVA_RESULT_CERTIFICATE_ERROR = 7,
+  ARSNOVA_RESULT_INVALID_ARGUMENT = 8,
+  ARSNOVA_RESULT_NOT_LOGGED_IN = 9,
+  ARSNOVA_RESULT_INVALID_HEADER_ERROR = 10,
+  ARSNOVA_RESULT_CONTENT_GROUP_NOT_FOUND_ERROR = 11,
+  ARSNOVA_RESULT_VOTE_SUPPRESSED_ERROR = 12,
+} ArsnovaResult;
+
+/**
+ * An ARSnova client created by `arsnova_client_new()`
+ */
+typedef struct ArsnovaClient ArsnovaClient;
+
+#if defined(ARSNOVA_CLIENT_WEBSOCKET)
+/**
+ * A handle to cancel a feedback subscription, created by `arsnova_subscription_new()`
+ */
+typedef struct ArsnovaSubscription ArsnovaSubscription;
+#endif
+
+/**
+ * A snapshot of the feedback of a room
+ */
+typedef struct ArsnovaFeedback {
+  uint16_t very_good;
+  uint16_t good;
+  uint16_t bad;
+  uint16_t very_bad;
+} ArsnovaFeedback;
+
+/**
+ * A callback receiving feedback changes and the user data given on subscription
+ */
+typedef void (*ArsnovaFeedbackCallback)(const struct ArsnovaFeedback *feedback, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a new client for given API URL
+ *
+ * Returns a null pointer if the URL cannot be parsed. The client must be freed using
+ * `arsnova_client_free()`.
+ *
+ * # Safety
+ *
+ * `api_url` must be a null pointer or point to a null-terminated string.
+ */
+struct ArsnovaClient *arsnova_client_new(const char *api_url);
+
+/**
+ * Frees a client created by `arsnova_client_new()`
+ *
+ * # Safety
+ *
+ * `client` must be a null pointer or a client returned by `arsnova_client_new()`
+ * which has not been freed yet.
+ */
+void arsnova_client_free(struct ArsnovaClient *client);
+
+/**
+ * Requests a guest login
+ *
+ * Logging in an already logged in client has no effect.
+ *
+ * # Safety
+ *
+ * `client` must be a null pointer or a valid client returned by `arsnova_client_new()`.
+ */
+enum ArsnovaResult arsnova_client_guest_login(struct ArsnovaClient *client);
+
+/**
+ * Requests the current feedback of the room with given 8-digit room ID
+ *
+ * On success, the feedback is written to `feedback`.
+ *
+ * # Safety
+ *
+ * `client` must be a null pointer or a valid client returned by `arsnova_client_new()`,
+ * `short_id` must be a null pointer or point to a null-terminated string and `feedback`
+ * must be a null pointer or point to a writable `ArsnovaFeedback`.
+ */
+enum ArsnovaResult arsnova_client_get_feedback(const struct ArsnovaClient *client,
+                                               const char *short_id,
+                                               struct ArsnovaFeedback *feedback);
+
+#if defined(ARSNOVA_CLIENT_WEBSOCKET)
+/**
+ * Creates a handle to cancel a feedback subscription
+ *
+ * The handle must be freed using `arsnova_subscription_free()` after the subscription ended.
+ */
+struct ArsnovaSubscription *arsnova_subscription_new(void);
+#endif
+
+#if defined(ARSNOVA_CLIENT_WEBSOCKET)
+/**
+ * Cancels the subscription using given handle, ending `arsnova_client_subscribe_feedback()`
+ *
+ * This can be called from any thread, including the callback. A cancelled handle cannot
+ * be used for another subscription.
+ *
+ * # Safety
+ *
+ * `subscription` must be a null pointer or a handle returned by `arsnova_subscription_new()`
+ * which has not been freed yet.
+ */
+void arsnova_subscription_cancel(const struct ArsnovaSubscription *subscription);
+#endif
+
+#if defined(ARSNOVA_CLIENT_WEBSOCKET)
+/**
+ * Frees a handle created by `arsnova_subscription_new()`
+ *
+ * # Safety
+ *
+ * `subscription` must be a null pointer or a handle returned by `arsnova_subscription_new()`
+ * which has not been freed yet and is not used by a running subscription.
+ */
+void arsnova_subscription_free(struct ArsnovaSubscription *subscription);
+#endif
+
+#if defined(ARSNOVA_CLIENT_WEBSOCKET)
+/**
+ * Subscribes to feedback changes of the room with given 8-digit room ID
+ *
+ * The callback is called with each feedback change and given user data. This function blocks
+ * the calling thread until the subscription ends or is cancelled using given handle and
+ * `arsnova_subscription_cancel()`. Without a handle, the subscription cannot be cancelled.
+ *
+ * # Safety
+ *
+ * `client` must be a null pointer or a valid client returned by `arsnova_client_new()`,
+ * `short_id` must be a null pointer or point to a null-terminated string and `subscription`
+ * must be a null pointer or a valid handle returned by `arsnova_subscription_new()`.
+ * The feedback passed to the callback is only valid during the callback.
+ */
+enum ArsnovaResult arsnova_client_subscribe_feedback(const struct ArsnovaClient *client,
+                                                     const char *short_id,
+                                                     ArsnovaFeedbackCallback callback,
+                                                     void *user_data,
+                                                     const struct ArsnovaSubscription *subscription);
+#endif
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ARSNOVA_CLIENT_H */
@@ -0,0 +1,176 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Backoff policies governing websocket reconnects and HTTP retries
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Decides how long to wait before retrying a failed connection or request
+///
+/// Implement this trait to apply custom network policies, e.g. to limit the number of attempts.
+pub trait BackoffPolicy: Send + Sync {
+    /// Returns the delay before given attempt, starting with 1, or `None` to give up
+    fn delay(&self, attempt: u32) -> Option<Duration>;
+}
+
+/// A `BackoffPolicy` using the same delay for every attempt
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixedBackoff {
+    delay: Duration,
+    max_attempts: Option<u32>,
+}
+
+impl FixedBackoff {
+    /// Creates a policy using given delay, retrying without limit
+    pub fn new(delay: Duration) -> FixedBackoff {
+        FixedBackoff {
+            delay,
+            max_attempts: None,
+        }
+    }
+
+    /// Gives up after given number of attempts
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> FixedBackoff {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+}
+
+impl BackoffPolicy for FixedBackoff {
+    fn delay(&self, attempt: u32) -> Option<Duration> {
+        match self.max_attempts {
+            Some(max_attempts) if attempt > max_attempts => None,
+            _ => Some(self.delay),
+        }
+    }
+}
+
+/// A `BackoffPolicy` doubling the delay with every attempt up to a maximum delay
+///
+/// Delays are randomly shortened by up to half of their length by default, so clients
+/// losing their connection at the same time do not reconnect at the same time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExponentialBackoff {
+    initial_delay: Duration,
+    max_delay: Duration,
+    jitter: f64,
+    max_attempts: Option<u32>,
+}
+
+impl ExponentialBackoff {
+    /// Creates a policy starting with given delay up to given maximum delay, retrying without limit
+    pub fn new(initial_delay: Duration, max_delay: Duration) -> ExponentialBackoff {
+        ExponentialBackoff {
+            initial_delay,
+            max_delay: max_delay.max(initial_delay),
+            jitter: 0.5,
+            max_attempts: None,
+        }
+    }
+
+    /// Sets the ratio of each delay it is randomly shortened by, between 0 (no jitter) and 1
+    pub fn with_jitter(mut self, jitter: f64) -> ExponentialBackoff {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Gives up after given number of attempts
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> ExponentialBackoff {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+}
+
+impl BackoffPolicy for ExponentialBackoff {
+    fn delay(&self, attempt: u32) -> Option<Duration> {
+        if self
+            .max_attempts
+            .is_some_and(|max_attempts| attempt > max_attempts)
+        {
+            return None;
+        }
+        let delay = self
+            .initial_delay
+            .checked_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay));
+        Some(delay.mul_f64(1.0 - self.jitter * random_fraction()))
+    }
+}
+
+/// Returns a random number in `[0, 1)`, using the random keys of the standard library hasher
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_use_fixed_delay_until_max_attempts() {
+        let backoff = FixedBackoff::new(Duration::from_secs(2)).with_max_attempts(2);
+
+        assert_eq!(backoff.delay(1), Some(Duration::from_secs(2)));
+        assert_eq!(backoff.delay(2), Some(Duration::from_secs(2)));
+        assert_eq!(backoff.delay(3), None);
+    }
+
+    #[test]
+    fn should_double_delay_up_to_max_delay() {
+        let backoff = ExponentialBackoff::new(Duration::from_secs(1), Duration::from_secs(10))
+            .with_jitter(0.0);
+
+        assert_eq!(backoff.delay(1), Some(Duration::from_secs(1)));
+        assert_eq!(backoff.delay(2), Some(Duration::from_secs(2)));
+        assert_eq!(backoff.delay(4), Some(Duration::from_secs(8)));
+        assert_eq!(backoff.delay(5), Some(Duration::from_secs(10)));
+        assert_eq!(backoff.delay(u32::MAX), Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn should_shorten_delay_by_jitter() {
+        let backoff = ExponentialBackoff::new(Duration::from_secs(8), Duration::from_secs(8))
+            .with_jitter(0.5);
+
+        for attempt in 1..100 {
+            let delay = backoff.delay(attempt).unwrap();
+            assert!(delay >= Duration::from_secs(4) && delay <= Duration::from_secs(8));
+        }
+    }
+
+    #[test]
+    fn should_give_up_after_max_attempts() {
+        let backoff = ExponentialBackoff::new(Duration::from_secs(1), Duration::from_secs(10))
+            .with_max_attempts(3);
+
+        assert!(backoff.delay(3).is_some());
+        assert_eq!(backoff.delay(4), None);
+    }
+
+    #[test]
+    fn should_return_random_fraction() {
+        for _ in 0..100 {
+            let fraction = random_fraction();
+            assert!((0.0..1.0).contains(&fraction));
+        }
+    }
+}
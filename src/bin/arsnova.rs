@@ -0,0 +1,770 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::fs;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use futures_util::StreamExt;
+use keyring::Entry;
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::select;
+use tokio::sync::mpsc::channel;
+
+use arsnova_client::client::ClientError;
+use arsnova_client::client::LoggedIn;
+use arsnova_client::{
+    Client, Envelope, EventKinds, Feedback, FeedbackEvent, FeedbackHandler, FeedbackValue,
+    RoomEvent, Session, ShortId,
+};
+
+#[derive(Parser)]
+#[command(author, version, about = "ARSnova live feedback command line client", long_about = None)]
+#[command(arg_required_else_help(true))]
+struct Cli {
+    #[arg(
+        short = 'u',
+        long = "url",
+        help = "API-URL",
+        default_value = "https://ars.particify.de/api",
+        global = true
+    )]
+    url: String,
+    #[arg(
+        short = 's',
+        long = "session",
+        help = "Sitzungsdatei [Standard: ~/.arsnova-session.json]",
+        global = true
+    )]
+    session: Option<PathBuf>,
+    #[arg(
+        long = "lang",
+        help = "Sprache [Standard: Systemsprache]",
+        value_enum,
+        global = true
+    )]
+    lang: Option<Lang>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// The language of the output
+#[derive(Clone, Copy, ValueEnum)]
+enum Lang {
+    De,
+    En,
+}
+
+impl Lang {
+    /// Detects the language using the system locale, defaults to English
+    fn detect() -> Lang {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        match locale.starts_with("de") {
+            true => Lang::De,
+            false => Lang::En,
+        }
+    }
+
+    fn messages(self) -> &'static Messages {
+        match self {
+            Lang::De => &MESSAGES_DE,
+            Lang::En => &MESSAGES_EN,
+        }
+    }
+}
+
+/// The texts of the output
+struct Messages {
+    feedback_values: [&'static str; 4],
+    votes: &'static str,
+    participants: &'static str,
+    contents: &'static str,
+    comments: &'static str,
+    password: &'static str,
+    logged_in: &'static str,
+    logged_out: &'static str,
+}
+
+const MESSAGES_DE: Messages = Messages {
+    feedback_values: ["Super", "Gut", "Nicht so gut", "Schlecht"],
+    votes: "Antworten",
+    participants: "Teilnehmende",
+    contents: "Inhalte",
+    comments: "Kommentare",
+    password: "Passwort: ",
+    logged_in: "Angemeldet als",
+    logged_out: "Abgemeldet",
+};
+
+const MESSAGES_EN: Messages = Messages {
+    feedback_values: ["Great", "Good", "Not so good", "Bad"],
+    votes: "votes",
+    participants: "Participants",
+    contents: "Contents",
+    comments: "Comments",
+    password: "Password: ",
+    logged_in: "Logged in as",
+    logged_out: "Logged out",
+};
+
+#[derive(Subcommand)]
+enum Command {
+    /// Live-Feedback
+    #[command(subcommand)]
+    Feedback(FeedbackCommand),
+    /// Raumstatistik anzeigen
+    Stats {
+        #[arg(help = "Raum")]
+        room: String,
+        #[arg(
+            long = "csv",
+            help = "CSV-Datei, an die bis zum Abbruch regelmäßig Statistik und Feedback angehängt werden"
+        )]
+        csv: Option<PathBuf>,
+        #[arg(
+            long = "interval",
+            help = "Abfrageintervall für --csv, z.B. 30s, 5m oder 1h",
+            default_value = "30s",
+            value_parser = parse_interval
+        )]
+        interval: Duration,
+    },
+    /// Kommentare
+    #[command(subcommand)]
+    Comments(CommentsCommand),
+    /// Beigetretene Räume anzeigen
+    Rooms,
+    /// Daten für andere Anwendungen bereitstellen
+    #[command(subcommand)]
+    Export(ExportCommand),
+    /// Mit Benutzerkonto anmelden und Token im Schlüsselbund speichern
+    Login {
+        #[arg(help = "Benutzername")]
+        username: String,
+        #[arg(
+            long = "password-stdin",
+            help = "Passwort von der Standardeingabe lesen"
+        )]
+        password_stdin: bool,
+    },
+    /// Gespeicherten Token aus dem Schlüsselbund entfernen
+    Logout,
+}
+
+#[derive(Subcommand)]
+enum FeedbackCommand {
+    /// Feedback-Änderungen anzeigen
+    Watch {
+        #[arg(help = "Raum")]
+        room: String,
+        #[arg(
+            long = "format",
+            help = "Ausgabeformat, jsonl enthält alle Ereignisse des Raums",
+            value_enum,
+            default_value_t = Format::Text
+        )]
+        format: Format,
+    },
+    /// Feedback senden
+    Send {
+        #[arg(help = "Raum")]
+        room: String,
+        #[arg(
+            help = "Feedback (0 = sehr gut bis 3 = sehr schlecht, a-d oder very_good, good, bad, very_bad)"
+        )]
+        value: FeedbackValue,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Feedback as text
+    Text,
+    /// All events as one JSON object per line
+    Jsonl,
+}
+
+/// A room event printed as JSON line
+#[derive(Serialize)]
+struct JsonEvent<'a> {
+    /// Milliseconds since UNIX epoch the event was received at
+    timestamp: u64,
+    room: &'a ShortId,
+    #[serde(flatten)]
+    event: &'a RoomEvent,
+}
+
+#[derive(Subcommand)]
+enum CommentsCommand {
+    /// Kommentare auflisten
+    List {
+        #[arg(help = "Raum")]
+        room: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportCommand {
+    /// Feedback und Statistik als Prometheus-Metriken bereitstellen
+    Prometheus {
+        #[arg(help = "Raum")]
+        room: String,
+        #[arg(
+            long = "listen",
+            help = "Adresse des HTTP-Servers",
+            default_value = "127.0.0.1:9090"
+        )]
+        listen: SocketAddr,
+    },
+}
+
+/// An error ending a command with a specific exit code to be handled by scripts
+struct CliError {
+    message: String,
+    code: u8,
+}
+
+impl CliError {
+    /// Used for errors without a specific exit code
+    const FAILURE: u8 = 1;
+    const LOGIN: u8 = 3;
+    const CONNECTION: u8 = 4;
+    const ROOM_NOT_FOUND: u8 = 5;
+    const FEEDBACK_STOPPED: u8 = 6;
+
+    fn new(message: &str, code: u8) -> CliError {
+        CliError {
+            message: message.to_string(),
+            code,
+        }
+    }
+}
+
+impl From<ClientError> for CliError {
+    fn from(err: ClientError) -> Self {
+        let code = match err {
+            ClientError::LoginError => CliError::LOGIN,
+            ClientError::ConnectionError => CliError::CONNECTION,
+            ClientError::RoomNotFoundError(_) => CliError::ROOM_NOT_FOUND,
+            _ => CliError::FAILURE,
+        };
+        CliError {
+            message: err.to_string(),
+            code,
+        }
+    }
+}
+
+#[tokio::main(worker_threads = 2)]
+async fn main() -> ExitCode {
+    match execute().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err.message);
+            ExitCode::from(err.code)
+        }
+    }
+}
+
+async fn execute() -> Result<(), CliError> {
+    let cli = Cli::parse();
+    let session_path = cli.session.clone().or_else(|| {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".arsnova-session.json"))
+    });
+
+    let messages = cli.lang.unwrap_or_else(Lang::detect).messages();
+    match &cli.command {
+        Command::Login {
+            username,
+            password_stdin,
+        } => return store_login(&cli.url, messages, username, *password_stdin).await,
+        Command::Logout => return remove_login(&cli.url, messages),
+        _ => {}
+    }
+
+    let stored_token = Entry::new(KEYRING_SERVICE, &cli.url)
+        .and_then(|entry| entry.get_password())
+        .ok();
+    // The token of a user login is kept in the keyring only
+    let with_token = stored_token.is_none();
+    let client = login(&cli.url, session_path.as_ref(), stored_token).await?;
+    if let Some(path) = &session_path {
+        save_session(path, &client.export_session(), with_token);
+    }
+
+    let result = run(&client, messages, cli.command).await;
+
+    // Save room memberships joined while running the command
+    if let Some(path) = &session_path {
+        save_session(path, &client.export_session(), with_token);
+    }
+    result
+}
+
+/// The service name of tokens stored in the OS keyring, the API URL is used as user name
+const KEYRING_SERVICE: &str = "arsnova-client";
+
+/// Logs in using the credentials of a registered user and stores the token in the OS keyring
+async fn store_login(
+    url: &str,
+    messages: &Messages,
+    username: &str,
+    password_stdin: bool,
+) -> Result<(), CliError> {
+    let password = match password_stdin {
+        true => {
+            let mut password = String::new();
+            std::io::stdin()
+                .read_line(&mut password)
+                .map(|_| password.trim_end_matches(['\r', '\n']).to_string())
+        }
+        false => rpassword::prompt_password(messages.password),
+    }
+    .map_err(|_| CliError::new("Cannot read password", CliError::FAILURE))?;
+
+    let client = Client::new(url)?.login(username, &password).await?;
+    Entry::new(KEYRING_SERVICE, url)
+        .and_then(|entry| entry.set_password(&client.export_session().token))
+        .map_err(|err| {
+            CliError::new(
+                &format!("Cannot store token in keyring: {}", err),
+                CliError::FAILURE,
+            )
+        })?;
+    println!("{} {}", messages.logged_in, username);
+    Ok(())
+}
+
+/// Removes the token stored in the OS keyring, following commands use a guest login
+fn remove_login(url: &str, messages: &Messages) -> Result<(), CliError> {
+    match Entry::new(KEYRING_SERVICE, url).and_then(|entry| entry.delete_credential()) {
+        Ok(()) | Err(keyring::Error::NoEntry) => {
+            println!("{}", messages.logged_out);
+            Ok(())
+        }
+        Err(err) => Err(CliError::new(
+            &format!("Cannot remove token from keyring: {}", err),
+            CliError::FAILURE,
+        )),
+    }
+}
+
+/// Resumes the stored session if available, otherwise requests a new guest login
+///
+/// If a token is stored in the keyring, it is used with the rooms of the stored session.
+async fn login(
+    url: &str,
+    session_path: Option<&PathBuf>,
+    stored_token: Option<String>,
+) -> Result<Client<LoggedIn>, CliError> {
+    let session = session_path
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<Session>(&content).ok());
+
+    if let Some(token) = stored_token {
+        // Keep the rooms of the session if it was stored for the same API URL
+        if let Some(session) = session {
+            let session = Session {
+                token: token.clone(),
+                ..session
+            };
+            if let Ok(client) = Client::new(url)?.restore_session(session) {
+                return Ok(client);
+            }
+        }
+        let session = Session {
+            api_url: url.to_string(),
+            token,
+            rooms: vec![],
+        };
+        return Client::new(url)?.restore_session(session).map_err(|_| {
+            CliError::new(
+                "Stored login is invalid, please run `arsnova login` again",
+                CliError::LOGIN,
+            )
+        });
+    }
+
+    if let Some(session) = session {
+        let client = Client::new(url)?;
+        if let Ok(client) = client.restore_session(session) {
+            return Ok(client);
+        }
+    }
+
+    Client::new(url)?
+        .guest_login()
+        .await
+        .map_err(CliError::from)
+}
+
+/// Saves given session, the token is omitted if not `with_token`
+fn save_session(path: &PathBuf, session: &Session, with_token: bool) {
+    let session = Session {
+        token: match with_token {
+            true => session.token.clone(),
+            false => String::new(),
+        },
+        ..session.clone()
+    };
+    let saved = serde_json::to_string(&session)
+        .ok()
+        .and_then(|content| fs::write(path, content).ok());
+    if saved.is_none() {
+        eprintln!("Cannot save session to {}", path.display());
+    }
+}
+
+async fn run(
+    client: &Client<LoggedIn>,
+    messages: &Messages,
+    command: Command,
+) -> Result<(), CliError> {
+    match command {
+        Command::Feedback(FeedbackCommand::Watch {
+            room,
+            format: Format::Jsonl,
+        }) => {
+            let room = ShortId::from(room);
+            let feedback = client.get_feedback(&room).await?;
+            print_json_event(
+                &room,
+                Envelope::new(RoomEvent::Feedback(FeedbackEvent::Changed(feedback)), None),
+            );
+
+            let mut events = pin!(
+                client
+                    .subscribe_room_events(&room, EventKinds::all())
+                    .await?
+            );
+            while let Some(event) = events.next().await {
+                print_json_event(&room, event);
+            }
+            Err(ClientError::ConnectionError.into())
+        }
+        Command::Feedback(FeedbackCommand::Watch {
+            room,
+            format: Format::Text,
+        }) => {
+            let room = ShortId::from(room);
+            let feedback = client.get_feedback(&room).await?;
+            print_feedback(messages, &feedback);
+
+            let (tx, mut rx) = channel::<Feedback>(10);
+            select! {
+                result = client.on_feedback_changed(&room, FeedbackHandler::Sender(tx)) => {
+                    result.map_err(CliError::from)
+                }
+                _ = async {
+                    while let Some(feedback) = rx.recv().await {
+                        print_feedback(messages, &feedback);
+                    }
+                } => Ok(())
+            }
+        }
+        Command::Feedback(FeedbackCommand::Send { room, value }) => {
+            let room = ShortId::from(room);
+            let room_info = client.get_room_info(&room).await?;
+            if room_info.is_closed() || room_info.is_feedback_locked() {
+                return Err(CliError::new(
+                    "Feedback is stopped",
+                    CliError::FEEDBACK_STOPPED,
+                ));
+            }
+            client
+                .send_feedback(&room, value)
+                .await
+                .map_err(CliError::from)
+        }
+        Command::Stats {
+            room,
+            csv,
+            interval,
+        } => {
+            let room = ShortId::from(room);
+            let room_info = client.get_room_info(&room).await?;
+            let stats = client.get_room_stats(&room).await?;
+            println!("{} ({})", room_info.name, room_info.short_id);
+            let rows = [
+                (messages.participants, stats.room_user_count),
+                (messages.contents, stats.content_count),
+                (messages.comments, stats.ack_comment_count),
+            ];
+            for (label, value) in rows {
+                println!("{: <14}{}", format!("{}:", label), value);
+            }
+            match csv {
+                Some(path) => sample_stats(client, &room, &path, interval).await,
+                None => Ok(()),
+            }
+        }
+        Command::Comments(CommentsCommand::List { room }) => {
+            let comments = client.get_comments(&ShortId::from(room)).await?;
+            for comment in comments {
+                println!("[{: >3}] {}", comment.score, comment.body);
+            }
+            Ok(())
+        }
+        Command::Rooms => {
+            let rooms = client.export_session().rooms;
+            let room_ids = rooms.iter().map(|room| room.id.clone()).collect::<Vec<_>>();
+            let stats = client.get_rooms_stats(&room_ids).await?;
+            for room in rooms {
+                let users = stats
+                    .get(&room.id)
+                    .map(|stats| stats.room_user_count)
+                    .unwrap_or_default();
+                println!("{}  {} - 👥: {}", room.short_id, room.name, users);
+            }
+            Ok(())
+        }
+        Command::Export(ExportCommand::Prometheus { room, listen }) => {
+            let room = ShortId::from(room);
+            client.get_room_info(&room).await?;
+            serve_metrics(client, &room, listen).await
+        }
+        // Handled before logging in
+        Command::Login { .. } | Command::Logout => Ok(()),
+    }
+}
+
+/// Parses an interval like `30s`, `5m` or `1h`, a number without unit is used as seconds
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    let (number, unit) = value
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or((value, ""), |idx| value.split_at(idx));
+    let number = number
+        .parse::<u64>()
+        .ok()
+        .filter(|number| *number > 0)
+        .ok_or_else(|| format!("Invalid interval '{}'", value))?;
+    match unit {
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => Ok(Duration::from_secs(number * 60)),
+        "h" => Ok(Duration::from_secs(number * 60 * 60)),
+        _ => Err(format!("Invalid interval unit '{}'", unit)),
+    }
+}
+
+/// Appends room statistics and feedback to given CSV file using given interval until interrupted
+///
+/// The header is written if the file is empty. Failed requests are reported and skipped.
+async fn sample_stats(
+    client: &Client<LoggedIn>,
+    room: &ShortId,
+    path: &Path,
+    interval: Duration,
+) -> Result<(), CliError> {
+    let cannot_write = |_| CliError::new(&format!("Cannot write to {}", path.display()), 1);
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(cannot_write)?;
+    if file.metadata().map_err(cannot_write)?.len() == 0 {
+        writeln!(
+            file,
+            "timestamp,room_user_count,content_count,ack_comment_count,very_good,good,bad,very_bad"
+        )
+        .map_err(cannot_write)?;
+    }
+
+    loop {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        match (
+            client.get_room_stats(room).await,
+            client.get_feedback(room).await,
+        ) {
+            (Ok(stats), Ok(feedback)) => {
+                writeln!(
+                    file,
+                    "{},{},{},{},{},{},{},{}",
+                    timestamp,
+                    stats.room_user_count,
+                    stats.content_count,
+                    stats.ack_comment_count,
+                    feedback.very_good,
+                    feedback.good,
+                    feedback.bad,
+                    feedback.very_bad
+                )
+                .map_err(cannot_write)?;
+            }
+            (Err(err), _) | (_, Err(err)) => eprintln!("Skipping sample: {}", err),
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Serves the metrics of given room at `/metrics` until interrupted
+///
+/// Feedback and statistics are requested on every scrape, so connections are handled one by one.
+async fn serve_metrics(
+    client: &Client<LoggedIn>,
+    room: &ShortId,
+    listen: SocketAddr,
+) -> Result<(), CliError> {
+    let listener = TcpListener::bind(listen)
+        .await
+        .map_err(|_| CliError::new(&format!("Cannot listen on {}", listen), CliError::FAILURE))?;
+    eprintln!("Serving metrics on http://{}/metrics", listen);
+
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+        // Do not let a stalled connection block following scrapes
+        let _ = tokio::time::timeout(Duration::from_secs(10), handle_scrape(client, room, stream))
+            .await;
+    }
+}
+
+/// Answers a single HTTP request, only `GET /metrics` is supported
+async fn handle_scrape(
+    client: &Client<LoggedIn>,
+    room: &ShortId,
+    mut stream: TcpStream,
+) -> std::io::Result<()> {
+    let mut request = vec![];
+    let mut buf = [0; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < 8192 {
+        match stream.read(&mut buf).await? {
+            0 => break,
+            len => request.extend_from_slice(&buf[..len]),
+        }
+    }
+    let request = String::from_utf8_lossy(&request);
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+
+    let (status, body) = match (request.starts_with("GET "), path.split('?').next()) {
+        (true, Some("/metrics")) => ("200 OK", metrics(client, room).await),
+        _ => ("404 Not Found", String::new()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Returns the current feedback and statistics of given room in the Prometheus text format
+///
+/// If a request fails, only `arsnova_up` is returned with value `0`.
+async fn metrics(client: &Client<LoggedIn>, room: &ShortId) -> String {
+    let up = |value: u8| {
+        format!(
+            "# HELP arsnova_up Whether the server could be requested\n# TYPE arsnova_up gauge\narsnova_up{{room=\"{}\"}} {}\n",
+            room, value
+        )
+    };
+    let (Ok(feedback), Ok(stats)) = (
+        client.get_feedback(room).await,
+        client.get_room_stats(room).await,
+    ) else {
+        return up(0);
+    };
+
+    let mut metrics = up(1);
+    metrics.push_str("# HELP arsnova_feedback_votes Feedback votes by value\n");
+    metrics.push_str("# TYPE arsnova_feedback_votes gauge\n");
+    let votes = [
+        ("very_good", feedback.very_good),
+        ("good", feedback.good),
+        ("bad", feedback.bad),
+        ("very_bad", feedback.very_bad),
+    ];
+    for (value, count) in votes {
+        metrics.push_str(&format!(
+            "arsnova_feedback_votes{{room=\"{}\",value=\"{}\"}} {}\n",
+            room, value, count
+        ));
+    }
+    let gauges = [
+        (
+            "arsnova_participants",
+            "Number of participants in the room",
+            stats.room_user_count,
+        ),
+        (
+            "arsnova_contents",
+            "Number of published contents",
+            stats.content_count,
+        ),
+        (
+            "arsnova_comments",
+            "Number of acknowledged comments",
+            stats.ack_comment_count,
+        ),
+    ];
+    for (name, help, value) in gauges {
+        metrics.push_str(&format!(
+            "# HELP {name} {help}\n# TYPE {name} gauge\n{name}{{room=\"{room}\"}} {value}\n"
+        ));
+    }
+    metrics
+}
+
+fn print_json_event(room: &ShortId, envelope: Envelope<RoomEvent>) {
+    let event = JsonEvent {
+        timestamp: envelope
+            .received_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+        room,
+        event: &envelope.event,
+    };
+    if let Ok(line) = serde_json::to_string(&event) {
+        println!("{}", line);
+    }
+}
+
+fn print_feedback(messages: &Messages, feedback: &Feedback) {
+    let [very_good, good, bad, very_bad] = messages.feedback_values;
+    println!(
+        "{}: {: >4}  {}: {: >4}  {}: {: >4}  {}: {: >4}  ({} {})",
+        very_good,
+        feedback.very_good,
+        good,
+        feedback.good,
+        bad,
+        feedback.bad,
+        very_bad,
+        feedback.very_bad,
+        feedback.count_votes(),
+        messages.votes
+    );
+}
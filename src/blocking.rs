@@ -0,0 +1,185 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A blocking ARSnova client for applications without an async runtime
+//!
+//! The blocking client wraps the async `arsnova_client::Client` and executes each request on an
+//! internal single-threaded runtime. It must not be used within an async context.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use reqwest::IntoUrl;
+use tokio::runtime::Runtime;
+
+use crate::client::ClientError::ConnectionError;
+#[cfg(feature = "websocket")]
+use crate::client::FeedbackHandler;
+use crate::client::{ClientError, Feedback, Latency, LoggedIn, LoggedOut, RoomInfo, RoomStats};
+use crate::ids::{RoomId, ShortId, UserId};
+
+/// A blocking ARSnova client mirroring the API of the async `arsnova_client::Client`
+pub struct Client<State = LoggedOut> {
+    pub(crate) inner: crate::Client<State>,
+    pub(crate) runtime: Arc<Runtime>,
+}
+
+impl Client {
+    /// Constructs a new blocking ARSnova client
+    ///
+    /// This method fails whenever the supplied Url cannot be parsed.
+    ///
+    /// If successful the result will be of type `Client<LoggedOut>`
+    pub fn new<U: IntoUrl>(api_url: U) -> Result<Client, ClientError> {
+        Client::from_async(crate::Client::new(api_url)?)
+    }
+
+    /// Wraps given async client, e.g. configured using a `ClientBuilder`
+    ///
+    /// This method fails if the internal runtime cannot be created.
+    pub fn from_async(client: crate::Client) -> Result<Client, ClientError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|_| ConnectionError)?;
+        Ok(Client {
+            inner: client,
+            runtime: Arc::new(runtime),
+        })
+    }
+}
+
+impl<State> Client<State> {
+    /// Measures the round-trip latency of an HTTP request and, if logged in, of a STOMP frame
+    ///
+    /// This method fails on connection errors.
+    pub fn ping(&self) -> Result<Latency, ClientError> {
+        self.runtime.block_on(self.inner.ping())
+    }
+
+    /// Returns the number of subscription events dropped since the client was created
+    #[cfg(feature = "websocket")]
+    pub fn dropped_events(&self) -> u64 {
+        self.inner.dropped_events()
+    }
+}
+
+impl Client<LoggedOut> {
+    /// Tries to login and request a new token if client is not logged in yet
+    ///
+    /// This method fails if a connection error occurs or the response cannot
+    /// be handled.
+    ///
+    /// If successful the result will be of type `Client<LoggedIn>`
+    pub fn guest_login(self) -> Result<Client<LoggedIn>, ClientError> {
+        let runtime = self.runtime.clone();
+        let inner = runtime.block_on(self.inner.guest_login())?;
+        Ok(Client { inner, runtime })
+    }
+}
+
+impl Client<LoggedIn> {
+    /// Get user ID extracted from client token
+    ///
+    /// This method fails if the token cannot be parsed
+    pub fn get_user_id(&self) -> Result<UserId, ClientError> {
+        self.inner.get_user_id()
+    }
+
+    /// Logout the client and discard existing token if not logged in
+    ///
+    /// If successful the result will be of type `Client<LoggedOut>`
+    pub fn logout(self) -> Client<LoggedOut> {
+        let runtime = self.runtime.clone();
+        Client {
+            inner: self.inner.logout(),
+            runtime,
+        }
+    }
+
+    /// Removes cached `RoomInfo` for given 8-digit room ID
+    pub fn invalidate_room_info(&self, short_id: &ShortId) {
+        self.inner.invalidate_room_info(short_id)
+    }
+
+    /// Removes all cached `RoomInfo`
+    pub fn clear_room_cache(&self) {
+        self.inner.clear_room_cache()
+    }
+
+    /// Requests `RoomInfo` for given 8-digit room ID
+    ///
+    /// This method fails on connection or response errors, and if
+    /// no room is available with given room ID.
+    pub fn get_room_info(&self, short_id: &ShortId) -> Result<RoomInfo, ClientError> {
+        self.runtime.block_on(self.inner.get_room_info(short_id))
+    }
+
+    /// Requests current `Feedback` for given 8-digit room ID
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    pub fn get_feedback(&self, short_id: &ShortId) -> Result<Feedback, ClientError> {
+        self.runtime.block_on(self.inner.get_feedback(short_id))
+    }
+
+    /// Requests current `Feedback` for given room ID without requesting room membership
+    pub fn get_feedback_by_room_id(&self, room_id: &RoomId) -> Result<Feedback, ClientError> {
+        self.runtime
+            .block_on(self.inner.get_feedback_by_room_id(room_id))
+    }
+
+    /// Requests `RoomStats` for given 8-digit room ID
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    pub fn get_room_stats(&self, short_id: &ShortId) -> Result<RoomStats, ClientError> {
+        self.runtime.block_on(self.inner.get_room_stats(short_id))
+    }
+
+    /// Requests `RoomStats` for given room ID without requesting room membership
+    pub fn get_room_stats_by_room_id(&self, room_id: &RoomId) -> Result<RoomStats, ClientError> {
+        self.runtime
+            .block_on(self.inner.get_room_stats_by_room_id(room_id))
+    }
+
+    /// Requests `RoomStats` of multiple rooms using a single request
+    ///
+    /// This method fails on connection or response errors.
+    pub fn get_rooms_stats(
+        &self,
+        room_ids: &[RoomId],
+    ) -> Result<HashMap<RoomId, RoomStats>, ClientError> {
+        self.runtime.block_on(self.inner.get_rooms_stats(room_ids))
+    }
+
+    /// Registers a handler to get notified on feedback changes
+    ///
+    /// This method blocks the current thread until the subscription ends.
+    /// It fails on connection or response errors and if no room is available with given room ID.
+    #[cfg(feature = "websocket")]
+    pub fn on_feedback_changed(
+        &self,
+        short_id: &ShortId,
+        handler: FeedbackHandler,
+    ) -> Result<(), ClientError> {
+        self.runtime
+            .block_on(self.inner.on_feedback_changed(short_id, handler))
+    }
+}
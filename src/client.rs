@@ -17,322 +17,774 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::collections::HashMap;
 use std::error;
 use std::fmt::{Debug, Display, Formatter};
 use std::marker::PhantomData;
-use std::time::Duration;
-
-use base64::engine::general_purpose::STANDARD_NO_PAD;
-use base64::Engine;
-use futures_util::{SinkExt, StreamExt};
-use reqwest::{IntoUrl, StatusCode};
-use serde::Deserialize;
-use serde_json::json;
-use tokio::select;
-use tokio::sync::mpsc::{Receiver, Sender};
-use tokio_tungstenite::connect_async;
-use tokio_tungstenite::tungstenite::Message;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use reqwest::header::{HeaderMap, HeaderValue, IntoHeaderName, ACCEPT_LANGUAGE, USER_AGENT};
+use reqwest::{Certificate, Identity, IntoUrl, Request, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
 use url::Url;
 
+use crate::backoff::BackoffPolicy;
+#[cfg(feature = "websocket")]
+use crate::backoff::FixedBackoff;
 use crate::client::ClientError::{
-    ConnectionError, LoginError, ParserError, RoomNotFoundError, UrlError,
+    CertificateError, ConnectionError, ContentGroupNotFoundError, FeedbackValueError,
+    InvalidHeaderError, LoginError, ParserError, RoomNotFoundError, UrlError, VoteSuppressedError,
 };
-
-#[derive(Deserialize, Debug)]
-struct LoginResponse {
-    #[serde(rename = "token")]
-    token: String,
+use crate::ids::{CommentId, ContentId, UserId};
+use crate::metrics::{Counter, Metrics, MetricsSink, NoopMetricsSink, Timing};
+#[cfg(feature = "test-util")]
+use crate::mock::MockServer;
+use crate::models::{
+    Announcement, Capabilities, Comment, CommentChanges, ContentState, ServerVersion,
+};
+pub use crate::models::{FeedbackMode, RoomInfo, RoomInfoSettings, RoomStats, SummaryResponse};
+use crate::recorder::Recorder;
+use crate::replay::Replay;
+#[cfg(feature = "websocket")]
+use crate::runtime::{Runtime, TokioRuntime};
+use crate::transport::{HttpTransport, ReqwestTransport};
+#[cfg(feature = "websocket")]
+use crate::transport::{TungsteniteTransport, WsTransport};
+
+mod attachments;
+mod auth;
+mod cookies;
+mod etag;
+mod feedback;
+#[cfg(feature = "legacy")]
+mod legacy;
+mod rooms;
+mod tokens;
+#[cfg(feature = "websocket")]
+mod ws;
+
+pub use attachments::AttachmentDownload;
+pub(crate) use auth::decode_user_id;
+pub use auth::Session;
+use cookies::CookieStore;
+use etag::EtagCache;
+pub use feedback::{Feedback, FeedbackClient, FeedbackDelta, FeedbackEvent, FeedbackValue};
+#[cfg(feature = "legacy")]
+pub use legacy::ApiVersion;
+use rooms::RoomInfoCache;
+pub use rooms::RoomsClient;
+pub use tokens::{FileTokenStore, TokenStore};
+
+#[cfg(all(feature = "websocket", feature = "test-util"))]
+pub(crate) use ws::StompFrame;
+#[cfg(feature = "websocket")]
+pub use ws::{ConnectionState, FeedbackHandler};
+
+/// Measured round-trip latencies of the connection to the ARSnova server
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Latency {
+    /// The round-trip time of an HTTP request
+    pub http: Duration,
+    /// The time until the server confirmed a STOMP frame, if measured
+    pub stomp: Option<Duration>,
 }
 
-#[derive(Deserialize, Debug)]
-struct TokenClaim {
-    sub: String,
+/// An event and the time it was received at
+///
+/// The server time is available if the server provides a timestamp with the event.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Envelope<T> {
+    /// The time the event was received by the client
+    pub received_at: SystemTime,
+    /// The time the event was sent by the server, if available
+    pub server_time: Option<SystemTime>,
+    pub event: T,
 }
 
-struct WsConnectMessage {
-    token: String,
-}
+impl<T> Envelope<T> {
+    /// Wraps given event received now
+    pub fn new(event: T, server_time: Option<SystemTime>) -> Envelope<T> {
+        Envelope {
+            received_at: SystemTime::now(),
+            server_time,
+            event,
+        }
+    }
 
-impl WsConnectMessage {
-    fn new(token: &str) -> WsConnectMessage {
-        WsConnectMessage {
-            token: token.to_string(),
+    /// Maps the wrapped event, keeping the timestamps
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Envelope<U> {
+        Envelope {
+            received_at: self.received_at,
+            server_time: self.server_time,
+            event: f(self.event),
         }
     }
 }
 
-impl Display for WsConnectMessage {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let str = format!(
-            "CONNECT\ntoken:{}\naccept-version:1.2,1.1,1.0\nheart-beat:20000,0\n\n\0",
-            self.token
-        );
-        write!(f, "{}", str)
+/// An event of a monitored room
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum RoomEvent {
+    /// The feedback of the room has changed
+    Feedback(FeedbackEvent),
+    /// The comments of the room have changed
+    Comment(CommentEvent),
+    /// The state of a content of the room has changed
+    Content(ContentEvent),
+    /// An announcement was created in the room
+    Announcement(AnnouncementEvent),
+    /// The statistics of the room have changed
+    Stats(RoomStats),
+}
+
+bitflags::bitflags! {
+    /// The kinds of `RoomEvent`s to subscribe to using `subscribe_room_events()`
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct EventKinds: u8 {
+        /// Feedback changes and status changes like started or stopped feedback
+        const FEEDBACK = 1;
+        /// Created, changed, highlighted and deleted comments
+        const COMMENTS = 1 << 1;
+        /// Content state changes, e.g. published contents
+        const CONTENT = 1 << 2;
+        /// Created announcements
+        const ANNOUNCEMENTS = 1 << 3;
+        /// Room statistics changes, these are polled as the server does not push them
+        const STATS = 1 << 4;
     }
 }
 
-struct WsSubscribeMessage {
-    room_id: String,
+/// A change of the comments of a room
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum CommentEvent {
+    /// A comment was created
+    Created(Comment),
+    /// Properties of a comment, e.g. its score, have changed
+    Patched(CommentId, CommentChanges),
+    /// A comment was highlighted by the moderator or its highlighting was removed
+    Highlighted(CommentId, bool),
+    /// A comment was deleted
+    Deleted(CommentId),
 }
 
-impl WsSubscribeMessage {
-    fn new(room_id: &str) -> WsSubscribeMessage {
-        WsSubscribeMessage {
-            room_id: room_id.to_string(),
-        }
-    }
+/// A change of the contents of a room
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ContentEvent {
+    /// The state of a content has changed, e.g. it was published or answering was stopped
+    StateChanged(ContentId, ContentState),
 }
 
-impl Display for WsSubscribeMessage {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let str = format!(
-            "SUBSCRIBE\nid:sub-6\ndestination:/topic/{}.stream\n\n\0",
-            self.room_id
-        );
-        write!(f, "{}", str)
-    }
+/// A change of the announcements of a room
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum AnnouncementEvent {
+    /// An announcement was created by the room owner
+    Created(Announcement),
 }
 
-struct WsSubscribeFeedbackMessage {
-    room_id: String,
+/// A change of what the presenter currently shows in a room
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum FocusEvent {
+    /// The presenter shows the content with given ID
+    Content(ContentId),
+    /// The presenter shows the comments
+    Comments,
+    /// The presenter shows the live feedback
+    Feedback,
 }
 
-impl WsSubscribeFeedbackMessage {
-    fn new(room_id: &str) -> WsSubscribeFeedbackMessage {
-        WsSubscribeFeedbackMessage {
-            room_id: room_id.to_string(),
-        }
-    }
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error), uniffi(flat_error))]
+pub enum ClientError {
+    ConnectionError,
+    LoginError,
+    RoomNotFoundError(String),
+    ParserError(String),
+    UrlError,
+    FeedbackValueError(String),
+    CertificateError,
+    InvalidHeaderError(String),
+    ContentGroupNotFoundError(String),
+    VoteSuppressedError,
 }
 
-impl Display for WsSubscribeFeedbackMessage {
+impl Display for ClientError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let str = format!(
-            "SUBSCRIBE\nid:sub-6\ndestination:/topic/{}.feedback.stream\n\n\0",
-            self.room_id
-        );
-        write!(f, "{}", str)
+        match self {
+            ConnectionError => write!(f, "Cannot connect"),
+            LoginError => write!(f, "Cannot login"),
+            RoomNotFoundError(short_id) => write!(f, "Requested room '{}' not found", short_id),
+            ParserError(msg) => write!(f, "Cannot parse response: {}", msg),
+            UrlError => write!(f, "Cannot parse given URL"),
+            FeedbackValueError(value) => write!(f, "Invalid feedback value '{}'", value),
+            CertificateError => write!(f, "Cannot parse given certificate"),
+            InvalidHeaderError(name) => write!(f, "Invalid value of header '{}'", name),
+            ContentGroupNotFoundError(group) => {
+                write!(f, "Requested content group '{}' not found", group)
+            }
+            VoteSuppressedError => write!(f, "Vote suppressed by the vote policy"),
+        }
     }
 }
 
-#[derive(Debug)]
-struct WsFeedbackMessage {
-    body: WsFeedbackBody,
+impl error::Error for ClientError {}
+
+pub struct LoggedIn;
+pub struct LoggedOut;
+
+/// Normalizes the API URL to always end with `/api/`
+///
+/// This allows endpoints to be joined using `Url::join()` no matter if the
+/// URL was given with or without `/api` suffix or trailing slash.
+fn normalize_api_url(mut url: Url) -> Url {
+    let path = url.path().trim_end_matches('/').to_string();
+    let path = if path.ends_with("/api") {
+        format!("{}/", path)
+    } else {
+        format!("{}/api/", path)
+    };
+    url.set_path(&path);
+    url.set_query(None);
+    url.set_fragment(None);
+    url
 }
 
-impl WsFeedbackMessage {
-    fn parse(raw: &str) -> Result<WsFeedbackMessage, ()> {
-        let parts = raw.split("\n\n");
-        match serde_json::from_str::<WsFeedbackBody>(parts.last().unwrap().replace('\0', "").trim())
-        {
-            Ok(body) => Ok(WsFeedbackMessage { body }),
-            Err(_) => Err(()),
+/// Normalizes the base URL of a service to end with a trailing slash
+fn normalize_service_url(mut url: Url) -> Url {
+    let path = format!("{}/", url.path().trim_end_matches('/'));
+    url.set_path(&path);
+    url.set_query(None);
+    url.set_fragment(None);
+    url
+}
+
+/// A service of a deployment which may be routed using its own gateway path
+///
+/// All other requests are sent to the API URL of the client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Service {
+    /// The core API, defaults to the API URL
+    ///
+    /// Unlike the API URL, no `/api` suffix is appended, e.g. for gateway prefixes like `/backend`.
+    /// The defaults of other services are relative to this URL.
+    Core,
+    /// The comment service, defaults to the API URL
+    Comments,
+    /// The websocket gateway used for live updates, defaults to `ws/` below the API URL
+    Websocket,
+}
+
+impl Service {
+    /// Returns the path of this service relative to the API URL if no base URL is configured
+    fn default_path(&self) -> &'static str {
+        match self {
+            Service::Core | Service::Comments => "",
+            Service::Websocket => "ws/",
         }
     }
 }
 
-#[derive(Deserialize, Debug)]
-struct WsFeedbackBody {
-    #[serde(rename = "type")]
-    body_type: String,
-    payload: WsFeedbackPayload,
+/// A middleware to modify the headers of outgoing requests
+///
+/// Middlewares are applied to every HTTP request and the websocket handshake.
+pub type Middleware = Arc<dyn Fn(&mut HeaderMap) + Send + Sync>;
+
+/// TLS options applied to HTTP and websocket connections
+#[derive(Default)]
+pub(crate) struct TlsOptions {
+    pub(crate) root_certificates: Vec<Vec<u8>>,
+    pub(crate) identity: Option<Vec<u8>>,
+    pub(crate) accept_invalid_certs: bool,
 }
 
-#[derive(Deserialize, Debug)]
-struct WsFeedbackPayload {
-    values: [u16; 4],
+/// A builder to configure and construct a `Client`
+pub struct ClientBuilder {
+    api_url: Result<Url, ClientError>,
+    middlewares: Vec<Middleware>,
+    room_cache_ttl: Duration,
+    room_cache_capacity: usize,
+    metrics_sink: Arc<dyn MetricsSink>,
+    recorder: Option<Arc<Recorder>>,
+    http_client: Option<reqwest::Client>,
+    service_urls: Vec<(Service, Result<Url, ClientError>)>,
+    cookie_store: bool,
+    token_store: Option<Arc<dyn TokenStore>>,
+    #[cfg(feature = "legacy")]
+    api_version: ApiVersion,
+    http_transport: Option<Arc<dyn HttpTransport>>,
+    retry_backoff: Option<Arc<dyn BackoffPolicy>>,
+    #[cfg(feature = "websocket")]
+    ws_transport: Option<Arc<dyn WsTransport>>,
+    #[cfg(feature = "websocket")]
+    runtime: Arc<dyn Runtime>,
+    user_agent: String,
+    locale: Option<String>,
+    headers: HeaderMap,
+    tls: TlsOptions,
+    #[cfg(feature = "websocket")]
+    subscription: ws::SubscriptionConfig,
 }
 
-impl WsFeedbackPayload {
-    fn get_feedback(self) -> Feedback {
-        Feedback::from_values(self.values)
+impl ClientBuilder {
+    /// Registers a middleware to modify the headers of all outgoing requests,
+    /// e.g. to add headers required by an authenticating reverse proxy.
+    ///
+    /// Middlewares are applied in order of registration.
+    pub fn middleware<F>(mut self, middleware: F) -> ClientBuilder
+    where
+        F: Fn(&mut HeaderMap) + Send + Sync + 'static,
+    {
+        self.middlewares.push(Arc::new(middleware));
+        self
     }
-}
 
-#[derive(Debug)]
-struct WsCreateFeedbackMessage {
-    room_id: String,
-    user_id: String,
-    value: u8,
-}
+    /// Sets the user agent used to identify the application
+    ///
+    /// Defaults to `arsnova-cli-client/<version>`.
+    pub fn user_agent<V: Into<String>>(mut self, user_agent: V) -> ClientBuilder {
+        self.user_agent = user_agent.into();
+        self
+    }
 
-impl WsCreateFeedbackMessage {
-    fn new(room_id: &str, user_id: &str, value: FeedbackValue) -> WsCreateFeedbackMessage {
-        WsCreateFeedbackMessage {
-            room_id: room_id.into(),
-            user_id: user_id.into(),
-            value: value.into_u8(),
-        }
+    /// Sets the preferred locale sent as `Accept-Language`, e.g. `de-DE` or `en;q=0.8`
+    ///
+    /// This allows server-provided strings like error messages to be returned in the
+    /// desired language.
+    pub fn locale<V: Into<String>>(mut self, locale: V) -> ClientBuilder {
+        self.locale = Some(locale.into());
+        self
     }
-}
 
-impl Display for WsCreateFeedbackMessage {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let payload = json!({
-            "type": "CreateFeedback",
-            "payload": {
-                "roomId": self.room_id,
-                "userId": self.user_id,
-                "value": self.value
-            }
-        })
-        .to_string();
+    /// Adds a header sent with all outgoing requests and the websocket handshake,
+    /// e.g. to identify the application or its installation to hosted instances.
+    pub fn header<K: IntoHeaderName>(mut self, name: K, value: HeaderValue) -> ClientBuilder {
+        self.headers.insert(name, value);
+        self
+    }
 
-        write!(f,
-                "SEND\ndestination:/queue/feedback.command\ncontent-type:application/json\ncontent-length:{}\n\n{}\0",
-                payload.chars().count(),
-                payload,
-            )
+    /// Adds PEM encoded root certificates to be trusted in addition to the built-in root certificates,
+    /// e.g. certificates of an internal CA used by a self-hosted instance.
+    pub fn add_root_certificate(mut self, pem: &[u8]) -> ClientBuilder {
+        self.tls.root_certificates.push(pem.to_vec());
+        self
     }
-}
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-pub struct RoomInfo {
-    pub id: String,
-    pub short_id: String,
-    pub name: String,
-    pub description: String,
-    pub closed: bool,
-    pub settings: RoomInfoSettings,
-}
+    /// Sets the client certificate used to authenticate the client
+    ///
+    /// The identity must be PEM encoded and contain the certificate chain and the private key.
+    pub fn identity(mut self, pem: &[u8]) -> ClientBuilder {
+        self.tls.identity = Some(pem.to_vec());
+        self
+    }
 
-impl RoomInfo {
-    /// Indicates if room is closed
-    pub fn is_closed(&self) -> bool {
-        self.closed
+    /// Controls the use of certificate validation
+    ///
+    /// Defaults to `false`.
+    ///
+    /// # Warning
+    ///
+    /// If enabled, any certificate is trusted, including expired certificates and
+    /// certificates of other hosts. Use this for testing purposes only.
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> ClientBuilder {
+        self.tls.accept_invalid_certs = accept_invalid_certs;
+        self
     }
 
-    /// Indicates if room has locked feedback
-    pub fn is_feedback_locked(&self) -> bool {
-        self.settings.feedback_locked
+    /// Sets the time to live of cached room information
+    ///
+    /// Room information is cached to prevent requesting room membership on every request.
+    /// Defaults to 60 seconds, a duration of zero disables caching.
+    pub fn room_cache_ttl(mut self, ttl: Duration) -> ClientBuilder {
+        self.room_cache_ttl = ttl;
+        self
     }
-}
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-pub struct RoomInfoSettings {
-    pub feedback_locked: bool,
-}
+    /// Sets the maximum number of cached room information entries
+    ///
+    /// If the capacity is reached, the oldest entry is removed.
+    /// Defaults to 100 entries, a capacity of zero disables caching.
+    pub fn room_cache_capacity(mut self, capacity: usize) -> ClientBuilder {
+        self.room_cache_capacity = capacity;
+        self
+    }
 
-#[derive(Deserialize, Clone, Debug)]
-pub struct SummaryResponse {
-    pub stats: RoomStats,
-}
+    /// Sets the `MetricsSink` to receive client metrics
+    ///
+    /// Defaults to `NoopMetricsSink` discarding all metrics.
+    pub fn metrics_sink<M: MetricsSink + 'static>(mut self, sink: M) -> ClientBuilder {
+        self.metrics_sink = Arc::new(sink);
+        self
+    }
 
-impl Default for SummaryResponse {
-    fn default() -> Self {
-        SummaryResponse {
-            stats: RoomStats {
-                ack_comment_count: 0,
-                content_count: 0,
-                room_user_count: 0,
-            },
+    /// Sets a `Recorder` to record all HTTP requests, responses and STOMP frames
+    ///
+    /// Use this to create reproducible traces, e.g. to be attached to bug reports.
+    pub fn recorder(mut self, recorder: Recorder) -> ClientBuilder {
+        self.recorder = Some(Arc::new(recorder));
+        self
+    }
+
+    /// Sets a `Replay` to answer all requests using a recorded session instead of the ARSnova server
+    ///
+    /// Use this for UI development and regression tests without a live ARSnova server.
+    pub fn replay(mut self, replay: Replay) -> ClientBuilder {
+        let replay = Arc::new(replay);
+        #[cfg(feature = "websocket")]
+        {
+            self.ws_transport = Some(replay.clone());
         }
+        self.http_transport = Some(replay);
+        self
     }
-}
 
-#[derive(Deserialize, Clone, Debug)]
-pub struct RoomStats {
-    #[serde(rename = "contentCount")]
-    pub content_count: usize,
-    #[serde(rename = "ackCommentCount")]
-    pub ack_comment_count: usize,
-    #[serde(rename = "roomUserCount")]
-    pub room_user_count: usize,
-}
+    /// Connects the client to given in-process `MockServer` instead of an ARSnova server
+    #[cfg(feature = "test-util")]
+    pub fn mock_server(mut self, server: &MockServer) -> ClientBuilder {
+        #[cfg(feature = "websocket")]
+        {
+            self.ws_transport = Some(Arc::new(server.clone()));
+        }
+        self.http_transport = Some(Arc::new(server.clone()));
+        self
+    }
 
-#[derive(Debug, Clone)]
-pub struct Feedback {
-    pub very_good: u16,
-    pub good: u16,
-    pub bad: u16,
-    pub very_bad: u16,
-}
+    /// Sets a preconfigured `reqwest::Client` to be used for HTTP requests,
+    /// e.g. to share a connection pool, proxy settings or instrumentation
+    ///
+    /// Configured TLS options are not applied to the given client, but to websocket connections only.
+    /// The user agent, locale and headers are added to each request.
+    pub fn http_client(mut self, client: reqwest::Client) -> ClientBuilder {
+        self.http_client = Some(client);
+        self
+    }
 
-impl Feedback {
-    pub fn from_values(values: [u16; 4]) -> Feedback {
-        Feedback {
-            very_good: values[0],
-            good: values[1],
-            bad: values[2],
-            very_bad: values[3],
-        }
+    /// Sets the base URL of given service, e.g. for self-hosted deployments splitting the API
+    /// across multiple gateway paths
+    ///
+    /// Requests of this service are sent relative to given URL instead of the API URL.
+    pub fn service_url<U: IntoUrl>(mut self, service: Service, url: U) -> ClientBuilder {
+        let url = url
+            .into_url()
+            .map(normalize_service_url)
+            .map_err(|_| UrlError);
+        self.service_urls.push((service, url));
+        self
     }
 
-    pub fn count_votes(&self) -> u16 {
-        self.very_good + self.good + self.bad + self.very_bad
+    /// Enables a cookie store to send cookies set by the server with all following requests
+    /// and the websocket handshake, e.g. sticky-session cookies of a reverse proxy
+    ///
+    /// Defaults to `false`.
+    pub fn cookie_store(mut self, enabled: bool) -> ClientBuilder {
+        self.cookie_store = enabled;
+        self
     }
-}
 
-#[allow(dead_code)]
-pub enum FeedbackHandler {
-    /// Handle incoming `Feedback` using a fn
-    Fn(fn(&Feedback)),
-    /// Handle incoming `Feedback` by sending it to a `Sender<Feedback>`
-    Sender(Sender<Feedback>),
-    /// Bidirectional handler for incoming `Feedback` and outgoing `FeedbackValue`
-    SenderReceiver(Sender<Feedback>, Receiver<FeedbackValue>),
-}
+    /// Sets a `TokenStore` to reuse the guest identity of a previous run
+    ///
+    /// On guest login, a stored and unexpired token is used instead of creating a new guest
+    /// account. Tokens of new guest accounts are stored.
+    pub fn token_store<T: TokenStore + 'static>(mut self, store: T) -> ClientBuilder {
+        self.token_store = Some(Arc::new(store));
+        self
+    }
 
-/// A possible feedback value
-#[derive(Clone)]
-pub enum FeedbackValue {
-    VeryGood,
-    A,
-    Good,
-    B,
-    Bad,
-    C,
-    VeryBad,
-    D,
-}
+    /// Sets the REST API of the server, e.g. `ApiVersion::Legacy` for ARSnova 2.x servers
+    ///
+    /// Using `ApiVersion::Detect`, the API is detected on login using the server version.
+    /// The legacy API requires a session cookie, so the cookie store is enabled if the legacy
+    /// API may be used.
+    ///
+    /// Defaults to `ApiVersion::Current`.
+    #[cfg(feature = "legacy")]
+    pub fn api_version(mut self, api_version: ApiVersion) -> ClientBuilder {
+        self.api_version = api_version;
+        self
+    }
 
-impl FeedbackValue {
-    /// Returns internal u8 representation
-    fn into_u8(self) -> u8 {
-        match self {
-            FeedbackValue::VeryGood | FeedbackValue::A => 0,
-            FeedbackValue::Good | FeedbackValue::B => 1,
-            FeedbackValue::Bad | FeedbackValue::C => 2,
-            FeedbackValue::VeryBad | FeedbackValue::D => 3,
-        }
+    /// Sets the `HttpTransport` used to execute HTTP requests
+    ///
+    /// Defaults to a `ReqwestTransport` using the configured TLS options.
+    pub fn http_transport<T: HttpTransport + 'static>(mut self, transport: T) -> ClientBuilder {
+        self.http_transport = Some(Arc::new(transport));
+        self
     }
-}
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub enum ClientError {
-    ConnectionError,
-    LoginError,
-    RoomNotFoundError(String),
-    ParserError(String),
-    UrlError,
-}
+    /// Sets the `WsTransport` used to connect websockets
+    ///
+    /// Defaults to a `TungsteniteTransport` using the configured TLS options.
+    #[cfg(feature = "websocket")]
+    pub fn ws_transport<T: WsTransport + 'static>(mut self, transport: T) -> ClientBuilder {
+        self.ws_transport = Some(Arc::new(transport));
+        self
+    }
 
-impl Display for ClientError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ConnectionError => write!(f, "Cannot connect"),
-            LoginError => write!(f, "Cannot login"),
-            RoomNotFoundError(short_id) => write!(f, "Requested room '{}' not found", short_id),
-            ParserError(msg) => write!(f, "Cannot parse response: {}", msg),
-            UrlError => write!(f, "Cannot parse given URL"),
+    /// Sets the `Runtime` used to spawn tasks and create timers of websocket subscriptions
+    ///
+    /// Defaults to `TokioRuntime`.
+    #[cfg(feature = "websocket")]
+    pub fn runtime<R: Runtime + 'static>(mut self, runtime: R) -> ClientBuilder {
+        self.runtime = Arc::new(runtime);
+        self
+    }
+
+    /// Enables coalescing of rapid feedback changes on feedback subscriptions
+    ///
+    /// Feedback will be handled at most once per given interval, keeping only the latest
+    /// feedback received within this interval.
+    #[cfg(feature = "websocket")]
+    pub fn coalesce_feedback(mut self, interval: Duration) -> ClientBuilder {
+        self.subscription.coalesce_interval = Some(interval);
+        self
+    }
+
+    /// Sets the maximum number of events queued by a monitoring stream
+    ///
+    /// If the consumer does not keep up and the capacity is reached, the oldest
+    /// event is dropped. Defaults to 64 events.
+    #[cfg(feature = "websocket")]
+    pub fn event_queue_capacity(mut self, capacity: usize) -> ClientBuilder {
+        self.subscription.event_queue_capacity = capacity;
+        self
+    }
+
+    /// Sets the maximum number of outgoing websocket messages queued for sending
+    ///
+    /// Outgoing messages are written by a dedicated task to not stall incoming message
+    /// processing. If the queue is full, new messages are discarded and counted as dropped events.
+    /// Defaults to 32 messages.
+    #[cfg(feature = "websocket")]
+    pub fn outgoing_queue_capacity(mut self, capacity: usize) -> ClientBuilder {
+        self.subscription.outgoing_queue_capacity = capacity;
+        self
+    }
+
+    /// Sets the interval of heart-beats sent to keep websocket connections alive
+    ///
+    /// Defaults to 15 seconds. Intervals below one second are raised to one second.
+    #[cfg(feature = "websocket")]
+    pub fn heartbeat_interval(mut self, interval: Duration) -> ClientBuilder {
+        self.subscription.heartbeat_interval = interval.max(Duration::from_secs(1));
+        self
+    }
+
+    /// Requests the server to send heart-beats at given interval
+    ///
+    /// The interval is negotiated with the server, using the larger of this and the interval
+    /// offered by the server. If no message is received within twice the negotiated interval,
+    /// the websocket connection is considered dead and closed, triggering a reconnect if enabled.
+    /// Disabled by default and if the server does not offer heart-beats.
+    #[cfg(feature = "websocket")]
+    pub fn server_heartbeat(mut self, interval: Duration) -> ClientBuilder {
+        self.subscription.server_heartbeat = Some(interval);
+        self
+    }
+
+    /// Sets the maximum size of a received STOMP frame in bytes
+    ///
+    /// Incomplete frames are buffered until they are received completely. If a frame exceeds
+    /// this size, the websocket connection is closed, triggering a reconnect if enabled.
+    /// Defaults to 1 MiB.
+    #[cfg(feature = "websocket")]
+    pub fn max_frame_size(mut self, size: usize) -> ClientBuilder {
+        self.subscription.max_frame_size = size;
+        self
+    }
+
+    /// Enables reconnecting lost websocket connections after given delay
+    ///
+    /// Reconnects are retried until the subscription is re-established. Without reconnects,
+    /// subscriptions end if the websocket connection is lost. Disabled by default.
+    #[cfg(feature = "websocket")]
+    pub fn reconnect_delay(self, delay: Duration) -> ClientBuilder {
+        self.reconnect_backoff(FixedBackoff::new(delay))
+    }
+
+    /// Enables reconnecting lost websocket connections using given `BackoffPolicy`
+    ///
+    /// Subscriptions end if the policy gives up. Disabled by default.
+    #[cfg(feature = "websocket")]
+    pub fn reconnect_backoff<B: BackoffPolicy + 'static>(mut self, backoff: B) -> ClientBuilder {
+        self.subscription.reconnect_backoff = Some(Arc::new(backoff));
+        self
+    }
+
+    /// Suppresses sending the same feedback value again within given interval
+    ///
+    /// This prevents accidentally resending a vote, e.g. if users press a key repeatedly.
+    /// Disabled by default.
+    #[cfg(feature = "websocket")]
+    pub fn dedup_votes(mut self, interval: Duration) -> ClientBuilder {
+        self.subscription.vote_dedup_interval = Some(interval);
+        self
+    }
+
+    /// Limits sending feedback values to one per given interval, votes sent earlier are suppressed
+    ///
+    /// Disabled by default.
+    #[cfg(feature = "websocket")]
+    pub fn min_vote_interval(mut self, interval: Duration) -> ClientBuilder {
+        self.subscription.min_vote_interval = Some(interval);
+        self
+    }
+
+    /// Enables retrying HTTP requests failed by connection errors or temporary server errors
+    ///
+    /// Only idempotent requests (GET, HEAD, PUT, DELETE and OPTIONS) are retried, if the request
+    /// failed or the server responded with status 429, 502, 503 or 504, until given `BackoffPolicy`
+    /// gives up. Disabled by default.
+    pub fn retry_backoff<B: BackoffPolicy + 'static>(mut self, backoff: B) -> ClientBuilder {
+        self.retry_backoff = Some(Arc::new(backoff));
+        self
+    }
+
+    /// Indicates if cookies set by the server need to be stored
+    fn uses_cookies(&self) -> bool {
+        #[cfg(feature = "legacy")]
+        if self.api_version != ApiVersion::Current {
+            return true;
         }
+        self.cookie_store
     }
-}
 
-impl error::Error for ClientError {}
+    /// Constructs the configured ARSnova client
+    ///
+    /// This method fails whenever the supplied Url, certificates, user agent or locale cannot be parsed.
+    ///
+    /// If successful the result will be of type `Client<LoggedOut>`
+    pub fn build(self) -> Result<Client, ClientError> {
+        let uses_cookies = self.uses_cookies();
+        let mut default_headers = self.headers;
+        default_headers.insert(
+            USER_AGENT,
+            HeaderValue::from_str(&self.user_agent)
+                .map_err(|_| InvalidHeaderError(USER_AGENT.to_string()))?,
+        );
+        if let Some(locale) = &self.locale {
+            default_headers.insert(
+                ACCEPT_LANGUAGE,
+                HeaderValue::from_str(locale)
+                    .map_err(|_| InvalidHeaderError(ACCEPT_LANGUAGE.to_string()))?,
+            );
+        }
 
-pub struct LoggedIn;
-pub struct LoggedOut;
+        let mut middlewares = self.middlewares;
+        let client = match self.http_client {
+            Some(client) => {
+                // The given client does not know about the default headers, so add them to each request
+                let headers = default_headers.clone();
+                middlewares.insert(
+                    0,
+                    Arc::new(move |request_headers: &mut HeaderMap| {
+                        for (name, value) in &headers {
+                            request_headers.entry(name).or_insert_with(|| value.clone());
+                        }
+                    }),
+                );
+                client
+            }
+            None => {
+                let mut client = reqwest::Client::builder()
+                    .default_headers(default_headers.clone())
+                    .danger_accept_invalid_certs(self.tls.accept_invalid_certs);
+                for pem in &self.tls.root_certificates {
+                    for certificate in
+                        Certificate::from_pem_bundle(pem).map_err(|_| CertificateError)?
+                    {
+                        client = client.add_root_certificate(certificate);
+                    }
+                }
+                if let Some(pem) = &self.tls.identity {
+                    client =
+                        client.identity(Identity::from_pem(pem).map_err(|_| CertificateError)?);
+                }
+                client.build().map_err(|_| ConnectionError)?
+            }
+        };
+
+        #[cfg(feature = "websocket")]
+        let ws_transport: Arc<dyn WsTransport> = match self.ws_transport {
+            Some(transport) => transport,
+            None => Arc::new(TungsteniteTransport::with_tls_options(&self.tls)?),
+        };
+
+        let service_urls = self
+            .service_urls
+            .into_iter()
+            .map(|(service, url)| url.map(|url| (service, url)))
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        Ok(Client {
+            api_url: self.api_url?,
+            service_urls,
+            http_transport: self
+                .http_transport
+                .unwrap_or_else(|| Arc::new(ReqwestTransport::new(client.clone()))),
+            retry_backoff: self.retry_backoff,
+            #[cfg(feature = "websocket")]
+            ws_transport,
+            #[cfg(feature = "websocket")]
+            runtime: self.runtime,
+            http_client: client,
+            #[cfg(feature = "websocket")]
+            default_headers,
+            middlewares,
+            cookies: uses_cookies.then(|| Arc::new(CookieStore::default())),
+            token_store: self.token_store,
+            #[cfg(feature = "legacy")]
+            api_version: self.api_version,
+            etags: EtagCache::default(),
+            room_cache: RoomInfoCache::new(self.room_cache_ttl, self.room_cache_capacity),
+            server_version: Mutex::new(None),
+            capabilities: Mutex::new(None),
+            #[cfg(feature = "websocket")]
+            subscription: self.subscription,
+            #[cfg(feature = "websocket")]
+            own_votes: ws::OwnVotes::default(),
+            #[cfg(feature = "websocket")]
+            shutdown: Arc::new(ws::Shutdown::default()),
+            metrics: Arc::new(Metrics::new(self.metrics_sink)),
+            recorder: self.recorder,
+            token: None,
+            user_id: None,
+            state: PhantomData::<LoggedOut>,
+        })
+    }
+}
 
 /// An asynchronous `Client` to make Requests with.
 ///
 /// The client can be created with an URL to an ARSnova API endpoint.
 pub struct Client<State = LoggedOut> {
-    api_url: String,
+    api_url: Url,
+    service_urls: HashMap<Service, Url>,
     http_client: reqwest::Client,
+    http_transport: Arc<dyn HttpTransport>,
+    retry_backoff: Option<Arc<dyn BackoffPolicy>>,
+    #[cfg(feature = "websocket")]
+    ws_transport: Arc<dyn WsTransport>,
+    #[cfg(feature = "websocket")]
+    runtime: Arc<dyn Runtime>,
+    #[cfg(feature = "websocket")]
+    default_headers: HeaderMap,
+    middlewares: Vec<Middleware>,
+    cookies: Option<Arc<CookieStore>>,
+    token_store: Option<Arc<dyn TokenStore>>,
+    #[cfg(feature = "legacy")]
+    api_version: legacy::ApiVersion,
+    etags: EtagCache,
+    room_cache: RoomInfoCache,
+    server_version: Mutex<Option<ServerVersion>>,
+    capabilities: Mutex<Option<Capabilities>>,
+    #[cfg(feature = "websocket")]
+    subscription: ws::SubscriptionConfig,
+    #[cfg(feature = "websocket")]
+    own_votes: ws::OwnVotes,
+    #[cfg(feature = "websocket")]
+    shutdown: Arc<ws::Shutdown>,
+    metrics: Arc<Metrics>,
+    recorder: Option<Arc<Recorder>>,
     token: Option<String>,
+    user_id: Option<UserId>,
     state: PhantomData<State>,
 }
 
@@ -343,355 +795,368 @@ impl Client {
     ///
     /// If successful the result will be of type `Client<LoggedOut>`
     pub fn new<U: IntoUrl>(api_url: U) -> Result<Client, ClientError> {
-        let client = reqwest::Client::builder()
-            .user_agent(format!("arsnova-cli-client/{}", env!("CARGO_PKG_VERSION")))
-            .build()
-            .map_err(|_| ConnectionError)?;
-
-        Ok(Client {
-            api_url: api_url.into_url().map_err(|_| UrlError)?.to_string(),
-            http_client: client,
-            token: None,
-            state: PhantomData::<LoggedOut>,
-        })
+        Client::builder(api_url).build()
     }
-}
 
-impl Client<LoggedOut> {
-    /// Tries to login and request a new token if client is not logged in yet
-    ///
-    /// This method fails if a connection error occurs or the response cannot
-    /// be handled.
-    ///
-    /// If successful the result will be of type `Client<LoggedIn>`
-    pub async fn guest_login(self) -> Result<Client<LoggedIn>, ClientError> {
-        match self
-            .http_client
-            .post(format!("{}/auth/login/guest", self.api_url))
-            .send()
-            .await
-        {
-            Ok(res) => match res.json::<LoginResponse>().await {
-                Ok(res) => Ok(Client {
-                    api_url: self.api_url,
-                    http_client: self.http_client,
-                    token: Some(res.token),
-                    state: PhantomData::<LoggedIn>,
-                }),
-                Err(_) => Err(LoginError),
-            },
-            Err(_) => Err(ConnectionError),
+    /// Returns a `ClientBuilder` to configure a new ARSnova client
+    pub fn builder<U: IntoUrl>(api_url: U) -> ClientBuilder {
+        ClientBuilder {
+            api_url: api_url
+                .into_url()
+                .map(normalize_api_url)
+                .map_err(|_| UrlError),
+            middlewares: vec![],
+            room_cache_ttl: Duration::from_secs(60),
+            room_cache_capacity: 100,
+            metrics_sink: Arc::new(NoopMetricsSink),
+            recorder: None,
+            http_client: None,
+            service_urls: vec![],
+            cookie_store: false,
+            token_store: None,
+            #[cfg(feature = "legacy")]
+            api_version: ApiVersion::default(),
+            http_transport: None,
+            retry_backoff: None,
+            #[cfg(feature = "websocket")]
+            ws_transport: None,
+            #[cfg(feature = "websocket")]
+            runtime: Arc::new(TokioRuntime),
+            user_agent: format!("arsnova-cli-client/{}", env!("CARGO_PKG_VERSION")),
+            locale: None,
+            headers: HeaderMap::new(),
+            tls: TlsOptions::default(),
+            #[cfg(feature = "websocket")]
+            subscription: ws::SubscriptionConfig::default(),
         }
     }
 }
 
-impl Client<LoggedIn> {
-    /// Get user ID extracted from client token
-    ///
-    /// This method fails if the token cannot be parsed
-    pub fn get_user_id(&self) -> Result<String, ClientError> {
-        let token = self.token.clone().unwrap_or_default();
-        let mut token_parts = token.split('.');
-
-        match token_parts.nth(1) {
-            None => Err(ParserError("Unparsable token".into())),
-            Some(part) => match STANDARD_NO_PAD.decode(part) {
-                Ok(d) => match serde_json::from_str::<TokenClaim>(
-                    &String::from_utf8(d).unwrap_or_default(),
-                ) {
-                    Ok(claim) => Ok(claim.sub),
-                    Err(err) => Err(ParserError(format!("Unparsable token claim: {}", err))),
-                },
-                Err(err) => Err(ParserError(format!("Unparsable token: {}", err))),
-            },
-        }
-    }
-
-    /// Logout the client and discard existing token if not logged in
-    ///
-    /// If successful the result will be of type `Client<LoggedOut>`
-    pub fn logout(self) -> Client<LoggedOut> {
+impl<State> Client<State> {
+    fn into_state<S>(self, token: Option<String>) -> Client<S> {
+        self.room_cache.clear();
         Client {
+            user_id: token
+                .as_deref()
+                .and_then(|token| decode_user_id(token).ok()),
             api_url: self.api_url,
+            service_urls: self.service_urls,
             http_client: self.http_client,
-            token: None,
-            state: PhantomData::<LoggedOut>,
+            http_transport: self.http_transport,
+            retry_backoff: self.retry_backoff,
+            #[cfg(feature = "websocket")]
+            ws_transport: self.ws_transport,
+            #[cfg(feature = "websocket")]
+            runtime: self.runtime,
+            #[cfg(feature = "websocket")]
+            default_headers: self.default_headers,
+            middlewares: self.middlewares,
+            cookies: self.cookies,
+            token_store: self.token_store,
+            #[cfg(feature = "legacy")]
+            api_version: self.api_version,
+            etags: self.etags,
+            room_cache: self.room_cache,
+            server_version: self.server_version,
+            capabilities: self.capabilities,
+            #[cfg(feature = "websocket")]
+            subscription: self.subscription,
+            #[cfg(feature = "websocket")]
+            own_votes: ws::OwnVotes::default(),
+            #[cfg(feature = "websocket")]
+            shutdown: Arc::new(ws::Shutdown::default()),
+            metrics: self.metrics,
+            recorder: self.recorder,
+            token,
+            state: PhantomData::<S>,
         }
     }
 
-    /// Requests `RoomInfo` for given 8-digit room ID
-    ///
-    /// This method fails on connection or response errors, and if
-    /// no room is available with given room ID.
-    pub async fn get_room_info(&self, short_id: &str) -> Result<RoomInfo, ClientError> {
-        let token = self.token.as_ref().unwrap();
-
-        let room_info_response = match self
-            .http_client
-            .post(format!(
-                "{}/room/~{}/request-membership",
-                self.api_url, short_id
-            ))
-            .bearer_auth(token.to_string())
-            .header("ars-room-role", "PARTICIPANT")
-            .header("content-type", "application/json")
-            .body("{}")
-            .send()
-            .await
-        {
-            Ok(res) => match res.status() {
-                StatusCode::OK => {
-                    match self
-                        .http_client
-                        .get(format!("{}/room/~{}", self.api_url, short_id))
-                        .bearer_auth(token.to_string())
-                        .send()
-                        .await
-                    {
-                        Ok(res) => match res.status() {
-                            StatusCode::OK => res
-                                .json::<RoomInfo>()
-                                .await
-                                .map_err(|err| ParserError(err.to_string()))?,
-                            StatusCode::NOT_FOUND => {
-                                return Err(RoomNotFoundError(short_id.into()))
-                            }
-                            _ => return Err(ConnectionError),
-                        },
-                        _ => return Err(ConnectionError),
-                    }
-                }
-                StatusCode::NOT_FOUND => return Err(RoomNotFoundError(short_id.into())),
-                _ => return Err(ConnectionError),
-            },
-            Err(_) => {
-                return Err(ConnectionError);
-            }
-        };
+    fn endpoint(&self, path: &str) -> Result<Url, ClientError> {
+        self.service_urls
+            .get(&Service::Core)
+            .unwrap_or(&self.api_url)
+            .join(path.trim_start_matches('/'))
+            .map_err(|_| UrlError)
+    }
 
-        Ok(room_info_response)
+    /// Returns the URL of given path of given service, using the API URL if no base URL is configured
+    fn service_endpoint(&self, service: Service, path: &str) -> Result<Url, ClientError> {
+        match self.service_urls.get(&service) {
+            Some(url) => url.join(path.trim_start_matches('/')).map_err(|_| UrlError),
+            None => self.endpoint(&format!(
+                "{}{}",
+                service.default_path(),
+                path.trim_start_matches('/')
+            )),
+        }
     }
 
-    /// Requests `Feedback` for given 8-digit room ID
+    /// Measures the round-trip latency of an HTTP request and, if logged in, of a STOMP frame
     ///
-    /// This method fails on connection or response errors and if
-    /// no room is available with given room ID.
-    pub async fn get_feedback(&self, short_id: &str) -> Result<Feedback, ClientError> {
-        let room_info = self.get_room_info(short_id).await?;
-
-        match self
-            .http_client
-            .get(format!("{}/room/{}/survey", self.api_url, room_info.id))
-            .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string())
-            .send()
+    /// Measured latencies are also passed to the configured `MetricsSink`.
+    /// This method fails on connection errors.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn ping(&self) -> Result<Latency, ClientError> {
+        let start = Instant::now();
+        self.send(self.http_client.get(self.endpoint("")?))
             .await
-        {
-            Ok(res) => match res.status() {
-                StatusCode::OK => Ok(Feedback::from_values(
-                    res.json::<[u16; 4]>()
-                        .await
-                        .map_err(|err| ParserError(err.to_string()))?,
-                )),
-                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.into())),
-                _ => Err(ConnectionError),
-            },
-            Err(_) => Err(ConnectionError),
-        }
+            .map_err(|_| ConnectionError)?;
+        let http = start.elapsed();
+        self.metrics.timing(Timing::HttpRoundTrip, http);
+
+        #[cfg(feature = "websocket")]
+        let stomp = match &self.token {
+            Some(token) => Some(self.stomp_latency(token).await?),
+            None => None,
+        };
+        #[cfg(not(feature = "websocket"))]
+        let stomp = None;
+
+        Ok(Latency { http, stomp })
     }
 
-    /// Requests `RoomStats` for given 8-digit room ID
+    /// Detects product name and version of the server
     ///
-    /// This method fails on connection or response errors and if
-    /// no room is available with given room ID.
-    pub async fn get_room_stats(&self, short_id: &str) -> Result<RoomStats, ClientError> {
-        let room_info = self.get_room_info(short_id).await?;
+    /// The detected version is cached. If the server does not provide version information,
+    /// an unknown `ServerVersion` is returned.
+    /// This method fails on connection errors.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn server_version(&self) -> Result<ServerVersion, ClientError> {
+        if let Some(server_version) = self
+            .server_version
+            .lock()
+            .ok()
+            .and_then(|cached| cached.clone())
+        {
+            return Ok(server_version);
+        }
 
-        match self
-            .http_client
-            .get(format!(
-                "{}/_view/room/summary?ids={}",
-                self.api_url, room_info.id
-            ))
-            .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string())
-            .send()
+        let res = self
+            .send(self.http_client.get(self.endpoint("")?))
             .await
-        {
-            Ok(res) => match res.status() {
-                StatusCode::OK => Ok(res
-                    .json::<Vec<SummaryResponse>>()
-                    .await
-                    .map_err(|err| ParserError(err.to_string()))
-                    .map(|summary_response| {
-                        summary_response
-                            .first()
-                            .unwrap_or(&SummaryResponse::default())
-                            .stats
-                            .clone()
-                    }))?,
-                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.into())),
-                _ => Err(ConnectionError),
-            },
-            Err(_) => Err(ConnectionError),
+            .map_err(|_| ConnectionError)?;
+        let server_version = match res.json::<serde_json::Value>().await {
+            Ok(value) => ServerVersion::from_value(&value),
+            Err(_) => ServerVersion::default(),
+        };
+        debug!(?server_version, "detected server version");
+        if let Ok(mut cached) = self.server_version.lock() {
+            *cached = Some(server_version.clone());
         }
+        Ok(server_version)
     }
 
-    /// Register feedback channel receiver and send incoming feedback to service
+    /// Detects the features offered by the server, e.g. to hide comments if they are disabled
     ///
-    /// This method fails on connection or response errors and if
-    /// no room is available with given room ID.
-    pub async fn register_feedback_receiver(
-        &self,
-        short_id: &str,
-        mut receiver: Receiver<FeedbackValue>,
-    ) -> Result<(), ClientError> {
-        let room_info = self.get_room_info(short_id).await?;
+    /// The detected capabilities are cached. If the server does not provide a feature
+    /// configuration, all features are assumed to be available.
+    /// This method fails on connection errors.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn capabilities(&self) -> Result<Capabilities, ClientError> {
+        if let Some(capabilities) = self
+            .capabilities
+            .lock()
+            .ok()
+            .and_then(|cached| cached.clone())
+        {
+            return Ok(capabilities);
+        }
 
-        let ws_url = self.api_url.replace("http", "ws");
-        let (socket, _) = connect_async(Url::parse(&format!("{}/ws/websocket", ws_url)).unwrap())
+        let res = self
+            .send(self.http_client.get(self.endpoint("configuration/")?))
             .await
             .map_err(|_| ConnectionError)?;
+        let capabilities = match res.status() {
+            StatusCode::OK => match res.json::<serde_json::Value>().await {
+                Ok(value) => Capabilities::from_value(&value),
+                Err(_) => Capabilities::default(),
+            },
+            StatusCode::NOT_FOUND => Capabilities::default(),
+            _ => return Err(ConnectionError),
+        };
+        debug!(?capabilities, "detected server capabilities");
+        if let Ok(mut cached) = self.capabilities.lock() {
+            *cached = Some(capabilities.clone());
+        }
+        Ok(capabilities)
+    }
 
-        let (mut write, _) = socket.split();
+    async fn send(&self, request: RequestBuilder) -> Result<Response, ClientError> {
+        let mut request = request.build().map_err(|_| ConnectionError)?;
+        if let Some(cookies) = &self.cookies {
+            cookies.apply(request.headers_mut());
+        }
+        self.middlewares
+            .iter()
+            .for_each(|middleware| middleware(request.headers_mut()));
 
-        let user_id = self.get_user_id().unwrap_or_default();
+        let backoff = match &self.retry_backoff {
+            Some(backoff) if request.method().is_idempotent() => backoff,
+            _ => return self.execute(request).await,
+        };
+        let mut attempt = 0;
+        loop {
+            let retry = request.try_clone();
+            let response = self.execute(request).await;
+            let retryable = match &response {
+                Ok(response) => matches!(
+                    response.status(),
+                    StatusCode::TOO_MANY_REQUESTS
+                        | StatusCode::BAD_GATEWAY
+                        | StatusCode::SERVICE_UNAVAILABLE
+                        | StatusCode::GATEWAY_TIMEOUT
+                ),
+                Err(_) => true,
+            };
+            let (Some(retry), true) = (retry, retryable) else {
+                return response;
+            };
+            attempt += 1;
+            let Some(delay) = backoff.delay(attempt) else {
+                return response;
+            };
+            warn!(attempt, ?delay, "retrying HTTP request");
+            self.sleep(delay).await;
+            request = retry;
+        }
+    }
 
-        if write
-            .send(Message::Text(
-                WsConnectMessage::new(self.token.as_ref().unwrap()).to_string(),
-            ))
-            .await
-            .is_ok()
-        {
-            return match write
-                .send(Message::Text(
-                    WsSubscribeFeedbackMessage::new(&room_info.id).to_string(),
-                ))
+    /// Sends given request once, storing cookies and recording the response
+    async fn execute(&self, request: Request) -> Result<Response, ClientError> {
+        debug!(method = %request.method(), url = %request.url(), "sending HTTP request");
+        self.metrics.increment(Counter::HttpRequests);
+        if let Some(recorder) = &self.recorder {
+            recorder.http_request(
+                request.method().as_str(),
+                request.url().as_str(),
+                request.headers(),
+                request.body().and_then(|body| body.as_bytes()),
+            );
+        }
+        let response = self.http_transport.execute(request).await;
+        if let (Some(cookies), Ok(response)) = (&self.cookies, &response) {
+            cookies.store(response.headers());
+        }
+        #[cfg(feature = "tracing")]
+        match &response {
+            Ok(response) => {
+                tracing::debug!(status = %response.status(), url = %response.url(), "received HTTP response")
+            }
+            Err(err) => tracing::warn!(error = %err, "HTTP request failed"),
+        }
+        match &self.recorder {
+            Some(recorder) => recorder
+                .http_response(response?)
                 .await
-            {
-                Ok(_) => loop {
-                    select!(
-                        Some(value) = receiver.recv() =>
-                        {
-                            let msg = WsCreateFeedbackMessage::new(&room_info.id, &user_id, value.to_owned()).to_string();
-                            let _ = write.send(Message::Text(msg)).await;
-                        },
-                        _ = tokio::time::sleep(Duration::from_secs(15)) => {
-                            let _ = write.send(Message::Text("\n".to_string())).await;
-                        }
-                    )
-                },
-                Err(_) => Err(ConnectionError),
-            };
+                .map_err(|_| ConnectionError),
+            None => response,
         }
+    }
 
-        Err(ConnectionError)
+    /// Waits for given duration, using the configured runtime if available
+    async fn sleep(&self, duration: Duration) {
+        #[cfg(feature = "websocket")]
+        self.runtime.sleep(duration).await;
+        #[cfg(not(feature = "websocket"))]
+        tokio::time::sleep(duration).await;
     }
 
-    /// Registers a handler to get notifications on feedback change.
+    /// Sends a GET request of a polled endpoint using the `ETag` of a previous response
     ///
-    /// This is done by using websocket connections to ARSnova.
+    /// Unchanged resources are answered with `304 Not Modified` by the server and the
+    /// cached body is returned instead.
+    async fn send_cached(&self, request: RequestBuilder) -> Result<Response, ClientError> {
+        let (http_client, request) = request.build_split();
+        let mut request = request.map_err(|_| ConnectionError)?;
+        self.etags.apply(&mut request);
+        let url = request.url().to_string();
+        let response = self
+            .send(RequestBuilder::from_parts(http_client, request))
+            .await?;
+        Ok(self.etags.response(&url, response).await)
+    }
+}
+
+impl Client<LoggedIn> {
+    /// Sends an authenticated GET request to given API path and returns the raw response
     ///
-    /// This method fails on connection or response errors and if
-    /// no room is available with given room ID.
-    pub async fn on_feedback_changed(
-        &self,
-        short_id: &str,
-        handler: FeedbackHandler,
-    ) -> Result<(), ClientError> {
-        let room_info = self.get_room_info(short_id).await?;
+    /// This can be used to call API endpoints not (yet) covered by this client.
+    /// The path is relative to the API URL, e.g. `/room/~12345678`.
+    ///
+    /// This method fails on connection errors only, the response status is not checked.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn raw_get(&self, path: &str) -> Result<reqwest::Response, ClientError> {
+        self.send(
+            self.http_client
+                .get(self.endpoint(path)?)
+                .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+        )
+        .await
+        .map_err(|_| ConnectionError)
+    }
 
-        let ws_url = self.api_url.replace("http", "ws");
-        let (socket, _) = connect_async(Url::parse(&format!("{}/ws/websocket", ws_url)).unwrap())
-            .await
-            .map_err(|_| ConnectionError)?;
+    /// Sends an authenticated POST request with given JSON body to given API path
+    /// and returns the raw response
+    ///
+    /// This can be used to call API endpoints not (yet) covered by this client.
+    /// The path is relative to the API URL, e.g. `/room/~12345678/request-membership`.
+    ///
+    /// This method fails on connection errors only, the response status is not checked.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, body))
+    )]
+    pub async fn raw_post<B: Into<reqwest::Body>>(
+        &self,
+        path: &str,
+        body: B,
+    ) -> Result<reqwest::Response, ClientError> {
+        self.send(
+            self.http_client
+                .post(self.endpoint(path)?)
+                .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string())
+                .header("content-type", "application/json")
+                .body(body),
+        )
+        .await
+        .map_err(|_| ConnectionError)
+    }
+}
 
-        let (mut write, mut read) = socket.split();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if write
-            .send(Message::Text(
-                WsConnectMessage::new(self.token.as_ref().unwrap()).to_string(),
-            ))
-            .await
-            .is_ok()
-        {
-            match write
-                .send(Message::Text(
-                    WsSubscribeFeedbackMessage::new(&room_info.id).to_string(),
-                ))
-                .await
-            {
-                Ok(_) => match handler {
-                    FeedbackHandler::Fn(f) => loop {
-                        select! {
-                            Some(next) = read.next() => {
-                                match &next {
-                                    Ok(msg) => self.handle_incoming_feedback_with_fn(msg, &f).await,
-                                    Err(_) => break
-                                }
-                            }
-                            _ = tokio::time::sleep(Duration::from_secs(15)) => {
-                                let _ = write.send(Message::Text("\n".to_string())).await;
-                            }
-                        }
-                    },
-                    FeedbackHandler::Sender(tx) => loop {
-                        select! {
-                            Some(next) = read.next() => {
-                                match &next {
-                                    Ok(msg) => self.handle_incoming_feedback_with_sender(msg, &tx).await,
-                                    Err(_) => break
-                                }
-                            }
-                            _ = tokio::time::sleep(Duration::from_secs(15)) => {
-                                let _ = write.send(Message::Text("\n".to_string())).await;
-                            }
-                        }
-                    },
-                    FeedbackHandler::SenderReceiver(tx, mut rx) => loop {
-                        select! {
-                            Some(next) = read.next() => {
-                                match &next {
-                                    Ok(msg) => self.handle_incoming_feedback_with_sender(msg, &tx).await,
-                                    Err(_) => break
-                                }
-                            }
-                            Some(value) = rx.recv() => {
-                                let user_id = self.get_user_id().unwrap_or_default();
-                                let msg = WsCreateFeedbackMessage::new(&room_info.id, &user_id, value.to_owned()).to_string();
-                                let _ = write.send(Message::Text(msg)).await;
-                            }
-                            _ = tokio::time::sleep(Duration::from_secs(15)) => {
-                                let _ = write.send(Message::Text("\n".to_string())).await;
-                            }
-                        }
-                    },
-                },
-                Err(_) => return Err(ConnectionError),
-            }
-        }
+    #[test]
+    fn should_reject_invalid_user_agent() {
+        let result = Client::builder("http://localhost/api")
+            .user_agent("arsnova\nclient")
+            .build();
 
-        Err(ConnectionError)
+        assert_eq!(
+            result.err(),
+            Some(InvalidHeaderError("user-agent".to_string()))
+        );
     }
 
-    async fn handle_incoming_feedback_with_fn(&self, msg: &Message, f: &fn(&Feedback)) {
-        if msg.is_text() && msg.clone().into_text().unwrap().starts_with("MESSAGE") {
-            if let Ok(msg) = WsFeedbackMessage::parse(msg.to_text().unwrap()) {
-                if msg.body.body_type == "FeedbackChanged" {
-                    let feedback = msg.body.payload.get_feedback();
-                    f(&feedback);
-                }
-            }
-        }
+    #[test]
+    fn should_reject_invalid_locale() {
+        let result = Client::builder("http://localhost/api")
+            .locale("de\r\n")
+            .build();
+
+        assert_eq!(
+            result.err(),
+            Some(InvalidHeaderError("accept-language".to_string()))
+        );
     }
 
-    async fn handle_incoming_feedback_with_sender(&self, msg: &Message, tx: &Sender<Feedback>) {
-        if msg.is_text() && msg.clone().into_text().unwrap().starts_with("MESSAGE") {
-            if let Ok(msg) = WsFeedbackMessage::parse(msg.to_text().unwrap()) {
-                if msg.body.body_type == "FeedbackChanged" {
-                    let feedback = msg.body.payload.get_feedback();
-                    let _ = tx.send(feedback).await;
-                }
-            }
-        }
+    #[test]
+    fn should_name_invalid_header() {
+        assert_eq!(
+            InvalidHeaderError("user-agent".to_string()).to_string(),
+            "Invalid value of header 'user-agent'"
+        );
     }
 }
@@ -0,0 +1,205 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Files attached to contents, e.g. images illustrating a question
+//!
+//! Attachment requests are available on `Client<LoggedIn>`.
+
+use reqwest::header::{HeaderValue, CONTENT_TYPE};
+use reqwest::{Response, StatusCode};
+
+use crate::client::ClientError::{ConnectionError, ParserError, RoomNotFoundError};
+use crate::client::{Client, ClientError, LoggedIn};
+use crate::ids::{AttachmentId, ContentId, ShortId};
+use crate::models::Attachment;
+
+/// A running download of an attachment
+///
+/// The file is received chunk by chunk, so large files do not have to be kept in memory.
+pub struct AttachmentDownload {
+    response: Response,
+}
+
+impl AttachmentDownload {
+    /// Returns the size of the file in bytes, if announced by the server
+    pub fn size(&self) -> Option<u64> {
+        self.response.content_length()
+    }
+
+    /// Returns the media type of the file, if announced by the server
+    pub fn media_type(&self) -> Option<&str> {
+        self.response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+    }
+
+    /// Receives the next chunk of the file or `None` if the file was completely received
+    ///
+    /// This method fails if the connection was interrupted.
+    pub async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, ClientError> {
+        self.response
+            .chunk()
+            .await
+            .map(|chunk| chunk.map(|chunk| chunk.to_vec()))
+            .map_err(|_| ConnectionError)
+    }
+}
+
+impl Client<LoggedIn> {
+    /// Requests the `Attachment`s of the content with given ID
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room or content is available with given IDs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_attachments(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+    ) -> Result<Vec<Attachment>, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!(
+                        "room/{}/content/{}/attachment/",
+                        room_info.id, content_id
+                    ))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => res
+                    .json::<Vec<Attachment>>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Downloads the file of the attachment with given ID
+    ///
+    /// Use `stream_attachment()` to receive large files chunk by chunk.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room, content or attachment is available with given IDs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn download_attachment(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+        attachment_id: &AttachmentId,
+    ) -> Result<Vec<u8>, ClientError> {
+        let mut download = self
+            .stream_attachment(short_id, content_id, attachment_id)
+            .await?;
+
+        let mut data = Vec::with_capacity(download.size().unwrap_or_default() as usize);
+        while let Some(chunk) = download.next_chunk().await? {
+            data.extend(chunk);
+        }
+        Ok(data)
+    }
+
+    /// Starts the download of the file of the attachment with given ID
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room, content or attachment is available with given IDs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn stream_attachment(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+        attachment_id: &AttachmentId,
+    ) -> Result<AttachmentDownload, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!(
+                        "room/{}/content/{}/attachment/{}",
+                        room_info.id, content_id, attachment_id
+                    ))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(AttachmentDownload { response: res }),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Uploads a file with given name and media type and attaches it to the content with given ID
+    ///
+    /// Requires the owner role in the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room or content is available with given IDs.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, data))
+    )]
+    pub async fn upload_attachment(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+        name: &str,
+        media_type: &str,
+        data: Vec<u8>,
+    ) -> Result<Attachment, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+        let media_type = HeaderValue::from_str(media_type)
+            .map_err(|_| ParserError(format!("Invalid media type: {}", media_type)))?;
+
+        match self
+            .send(
+                self.http_client
+                    .post(self.endpoint(&format!(
+                        "room/{}/content/{}/attachment/",
+                        room_info.id, content_id
+                    ))?)
+                    .query(&[("name", name)])
+                    .header(CONTENT_TYPE, media_type)
+                    .body(data)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK | StatusCode::CREATED => res
+                    .json::<Attachment>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+}
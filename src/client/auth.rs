@@ -0,0 +1,335 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Authentication and sessions of the client
+
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::client::ClientError::{ConnectionError, LoginError, ParserError, UrlError};
+use crate::client::{normalize_api_url, Client, ClientError, LoggedIn, LoggedOut, RoomInfo};
+use crate::ids::UserId;
+use crate::models::{LoginResponse, Person, UserProfile};
+
+#[derive(Deserialize, Debug)]
+struct TokenClaim {
+    sub: String,
+    exp: Option<u64>,
+}
+
+/// The state of a logged in client, which can be persisted to resume a session later
+///
+/// Contains the API URL, the token and the cached `RoomInfo` of joined rooms.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub api_url: String,
+    pub token: String,
+    pub rooms: Vec<RoomInfo>,
+}
+
+/// Extracts the user ID from the claims of given token
+pub(crate) fn decode_user_id(token: &str) -> Result<UserId, ClientError> {
+    decode_claim(token).map(|claim| UserId::new(claim.sub))
+}
+
+/// Indicates if given token can be parsed and has not expired
+fn is_valid_token(token: &str) -> bool {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    decode_claim(token).is_ok_and(|claim| claim.exp.is_none_or(|exp| exp > now))
+}
+
+fn decode_claim(token: &str) -> Result<TokenClaim, ClientError> {
+    let mut token_parts = token.split('.');
+
+    match token_parts.nth(1) {
+        None => Err(ParserError("Unparsable token".into())),
+        Some(part) => match STANDARD_NO_PAD.decode(part) {
+            Ok(d) => {
+                match serde_json::from_str::<TokenClaim>(&String::from_utf8(d).unwrap_or_default())
+                {
+                    Ok(claim) => Ok(claim),
+                    Err(err) => Err(ParserError(format!("Unparsable token claim: {}", err))),
+                }
+            }
+            Err(err) => Err(ParserError(format!("Unparsable token: {}", err))),
+        },
+    }
+}
+
+impl Client<LoggedOut> {
+    /// Tries to login and request a new token if client is not logged in yet
+    ///
+    /// This method fails with `ClientError::LoginError` if the server does not respond
+    /// with a token, and with `ClientError::ConnectionError` if a connection error occurs.
+    ///
+    /// If a `TokenStore` is configured, a stored and unexpired token is used instead of
+    /// requesting a new one and new tokens are stored.
+    ///
+    /// If successful the result will be of type `Client<LoggedIn>`
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn guest_login(self) -> Result<Client<LoggedIn>, ClientError> {
+        #[cfg(feature = "legacy")]
+        if self.uses_legacy_api().await {
+            return self.legacy_guest_login().await;
+        }
+
+        let stored_token = self
+            .token_store
+            .as_ref()
+            .and_then(|store| store.load(self.api_url.as_str()))
+            .filter(|token| is_valid_token(token));
+        if let Some(token) = stored_token {
+            debug!("reusing stored guest token");
+            return Ok(self.into_state::<LoggedIn>(Some(token)));
+        }
+
+        match self
+            .send(self.http_client.post(self.endpoint("auth/login/guest")?))
+            .await
+        {
+            Ok(res) if res.status().is_success() => match res.json::<LoginResponse>().await {
+                Ok(res) if !res.token.is_empty() => {
+                    if let Some(store) = &self.token_store {
+                        store.store(self.api_url.as_str(), &res.token);
+                    }
+                    Ok(self.into_state::<LoggedIn>(Some(res.token)))
+                }
+                _ => Err(LoginError),
+            },
+            Ok(_) => Err(LoginError),
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Tries to login using the credentials of a registered user
+    ///
+    /// This method fails with `ClientError::LoginError` if the credentials are not accepted
+    /// or the legacy API is used, and with `ClientError::ConnectionError` if a connection
+    /// error occurs. The token is not stored in a configured `TokenStore`, use
+    /// `Client::export_session()` to keep it.
+    ///
+    /// If successful the result will be of type `Client<LoggedIn>`
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, password))
+    )]
+    pub async fn login(
+        self,
+        login_id: &str,
+        password: &str,
+    ) -> Result<Client<LoggedIn>, ClientError> {
+        #[cfg(feature = "legacy")]
+        if self.uses_legacy_api().await {
+            return Err(LoginError);
+        }
+
+        let credentials = serde_json::json!({
+            "loginId": login_id,
+            "password": password
+        });
+
+        match self
+            .send(
+                self.http_client
+                    .post(self.endpoint("auth/login/registered")?)
+                    .json(&credentials),
+            )
+            .await
+        {
+            Ok(res) if res.status().is_success() => match res.json::<LoginResponse>().await {
+                Ok(res) if !res.token.is_empty() => {
+                    Ok(self.into_state::<LoggedIn>(Some(res.token)))
+                }
+                _ => Err(LoginError),
+            },
+            Ok(_) => Err(LoginError),
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Resumes given session exported by `Client::export_session()`
+    ///
+    /// The token is not validated, requests will fail if it has expired. Restored `RoomInfo`
+    /// is cached again for the configured TTL, so no room memberships need to be requested.
+    ///
+    /// This method fails if the session was exported for another API URL or its token
+    /// cannot be parsed.
+    ///
+    /// If successful the result will be of type `Client<LoggedIn>`
+    pub fn restore_session(self, session: Session) -> Result<Client<LoggedIn>, ClientError> {
+        let api_url = Url::parse(&session.api_url)
+            .map(normalize_api_url)
+            .map_err(|_| UrlError)?;
+        if api_url != self.api_url {
+            return Err(LoginError);
+        }
+        decode_user_id(&session.token)?;
+
+        let client = self.into_state::<LoggedIn>(Some(session.token));
+        session
+            .rooms
+            .iter()
+            .for_each(|room_info| client.room_cache.insert(&room_info.short_id, room_info));
+        Ok(client)
+    }
+}
+
+impl Client<LoggedIn> {
+    /// Exports the session of this client to be restored using `Client::restore_session()`
+    ///
+    /// The session contains the token, so it should be stored like a password.
+    pub fn export_session(&self) -> Session {
+        Session {
+            api_url: self.api_url.to_string(),
+            token: self.token.clone().unwrap_or_default(),
+            rooms: self.room_cache.room_infos(),
+        }
+    }
+
+    /// Get user ID extracted from client token
+    ///
+    /// The user ID is decoded once on login.
+    ///
+    /// This method fails if the token cannot be parsed
+    pub fn get_user_id(&self) -> Result<UserId, ClientError> {
+        match &self.user_id {
+            Some(user_id) => Ok(user_id.clone()),
+            None => decode_user_id(self.token.as_deref().unwrap_or_default()),
+        }
+    }
+
+    /// Requests the `UserProfile` of the current user
+    ///
+    /// This method fails on connection or response errors.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_user_profile(&self) -> Result<UserProfile, ClientError> {
+        let user_id = self.get_user_id()?;
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!("user/{}", user_id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) if res.status().is_success() => res
+                .json::<UserProfile>()
+                .await
+                .map_err(|err| ParserError(err.to_string())),
+            _ => Err(ConnectionError),
+        }
+    }
+
+    /// Updates personal information like the display name of the current user
+    /// and returns the updated `UserProfile`
+    ///
+    /// This method fails on connection or response errors.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn update_user_profile(&self, person: &Person) -> Result<UserProfile, ClientError> {
+        let user_id = self.get_user_id()?;
+
+        match self
+            .send(
+                self.http_client
+                    .patch(self.endpoint(&format!("user/{}", user_id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string())
+                    .json(&serde_json::json!({ "person": person })),
+            )
+            .await
+        {
+            Ok(res) if res.status().is_success() => res
+                .json::<UserProfile>()
+                .await
+                .map_err(|err| ParserError(err.to_string())),
+            _ => Err(ConnectionError),
+        }
+    }
+
+    /// Requests an export of all data stored for the current user
+    ///
+    /// The response body is returned as is, e.g. to be saved as a file to answer a subject
+    /// access request.
+    ///
+    /// This method fails on connection or response errors.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn export_my_data(&self) -> Result<Vec<u8>, ClientError> {
+        let user_id = self.get_user_id()?;
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!("user/{}/export", user_id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) if res.status().is_success() => res
+                .bytes()
+                .await
+                .map(|body| body.to_vec())
+                .map_err(|_| ConnectionError),
+            _ => Err(ConnectionError),
+        }
+    }
+
+    /// Deletes the account of the current user including all owned rooms
+    ///
+    /// The deletion has to be confirmed using the confirmation token provided by the server,
+    /// e.g. sent by mail. The confirmation token is sent in the request body to not show up
+    /// in URLs. The token of this client is invalid afterwards, use `logout()`
+    /// to continue using this client.
+    ///
+    /// This method fails on connection or response errors and if the confirmation token
+    /// is not accepted.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, confirmation_token))
+    )]
+    pub async fn delete_account(&self, confirmation_token: &str) -> Result<(), ClientError> {
+        let user_id = self.get_user_id()?;
+
+        match self
+            .send(
+                self.http_client
+                    .delete(self.endpoint(&format!("user/{}", user_id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string())
+                    .json(&serde_json::json!({ "confirmationToken": confirmation_token })),
+            )
+            .await
+        {
+            Ok(res) if res.status().is_success() => Ok(()),
+            _ => Err(ConnectionError),
+        }
+    }
+
+    /// Logout the client and discard existing token if not logged in
+    ///
+    /// If successful the result will be of type `Client<LoggedOut>`
+    pub fn logout(self) -> Client<LoggedOut> {
+        self.into_state::<LoggedOut>(None)
+    }
+}
@@ -0,0 +1,82 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use reqwest::header::{HeaderMap, HeaderValue, COOKIE, SET_COOKIE};
+
+/// A minimal store of cookies set by the server, e.g. sticky-session cookies of a reverse proxy
+///
+/// Cookie attributes like domain, path and expiry dates are ignored, as all requests are sent
+/// to the same API host. Cookies are removed if the server sets them with `Max-Age` of zero.
+#[derive(Default)]
+pub(crate) struct CookieStore {
+    cookies: Mutex<BTreeMap<String, String>>,
+}
+
+impl CookieStore {
+    /// Stores all cookies set by given response headers
+    pub(crate) fn store(&self, headers: &HeaderMap) {
+        let Ok(mut cookies) = self.cookies.lock() else {
+            return;
+        };
+        for set_cookie in headers.get_all(SET_COOKIE) {
+            let Ok(set_cookie) = set_cookie.to_str() else {
+                continue;
+            };
+            let mut parts = set_cookie.split(';');
+            let Some((name, value)) = parts.next().and_then(|pair| pair.split_once('=')) else {
+                continue;
+            };
+            let expired = parts.any(|attribute| {
+                attribute
+                    .trim()
+                    .split_once('=')
+                    .is_some_and(|(key, value)| {
+                        key.eq_ignore_ascii_case("max-age")
+                            && value.trim().parse::<i64>().is_ok_and(|age| age <= 0)
+                    })
+            });
+            if expired {
+                cookies.remove(name.trim());
+            } else {
+                cookies.insert(name.trim().to_string(), value.trim().to_string());
+            }
+        }
+    }
+
+    /// Adds all stored cookies to given request headers
+    pub(crate) fn apply(&self, headers: &mut HeaderMap) {
+        let Ok(cookies) = self.cookies.lock() else {
+            return;
+        };
+        if cookies.is_empty() {
+            return;
+        }
+        let cookie = cookies
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+            headers.insert(COOKIE, cookie);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use reqwest::header::{HeaderValue, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use reqwest::{Request, Response, StatusCode};
+
+const CAPACITY: usize = 256;
+
+/// A cache of response bodies of polled GET endpoints and their `ETag`
+///
+/// Requests for cached URLs are sent with `If-None-Match`, and a `304 Not Modified` response
+/// is replaced by the cached body, so unchanged resources are not transferred again.
+#[derive(Default)]
+pub(super) struct EtagCache {
+    entries: Mutex<HashMap<String, (HeaderValue, Vec<u8>)>>,
+}
+
+impl EtagCache {
+    /// Adds `If-None-Match` to given request if a response for its URL has been cached
+    pub(super) fn apply(&self, request: &mut Request) {
+        let Ok(entries) = self.entries.lock() else {
+            return;
+        };
+        if let Some((etag, _)) = entries.get(request.url().as_str()) {
+            request.headers_mut().insert(IF_NONE_MATCH, etag.clone());
+        }
+    }
+
+    /// Returns the response to use for given URL
+    ///
+    /// Successful responses with an `ETag` are cached and `304 Not Modified` responses are
+    /// replaced by the cached body. All other responses are returned unchanged.
+    pub(super) async fn response(&self, url: &str, response: Response) -> Response {
+        match response.status() {
+            StatusCode::NOT_MODIFIED => {
+                let cached = self
+                    .entries
+                    .lock()
+                    .ok()
+                    .and_then(|entries| entries.get(url).map(|(_, body)| body.clone()));
+                match cached {
+                    Some(body) => {
+                        trace!("using cached response body");
+                        let mut cached = http::Response::new(body);
+                        cached
+                            .headers_mut()
+                            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                        Response::from(cached)
+                    }
+                    None => response,
+                }
+            }
+            StatusCode::OK => {
+                let Some(etag) = response.headers().get(ETAG).cloned() else {
+                    return response;
+                };
+                let headers = response.headers().clone();
+                let body = match response.bytes().await {
+                    Ok(body) => body.to_vec(),
+                    Err(_) => return Self::failed_response(),
+                };
+                if let Ok(mut entries) = self.entries.lock() {
+                    if entries.len() >= CAPACITY && !entries.contains_key(url) {
+                        entries.clear();
+                    }
+                    entries.insert(url.to_string(), (etag, body.clone()));
+                }
+                let mut fresh = http::Response::new(body);
+                *fresh.headers_mut() = headers;
+                Response::from(fresh)
+            }
+            _ => response,
+        }
+    }
+
+    fn failed_response() -> Response {
+        let mut response = http::Response::new(Vec::new());
+        *response.status_mut() = StatusCode::BAD_GATEWAY;
+        Response::from(response)
+    }
+}
@@ -0,0 +1,439 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Feedback values and requests of the client
+//!
+//! Feedback requests are available on `Client<LoggedIn>` and grouped by the sub-client `FeedbackClient`.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::client::ClientError::{
+    ConnectionError, FeedbackValueError, ParserError, RoomNotFoundError,
+};
+#[cfg(feature = "websocket")]
+use crate::client::FeedbackHandler;
+use crate::client::{Client, ClientError, LoggedIn};
+use crate::ids::{RoomId, ShortId};
+use crate::models::{FeedbackMode, SurveyResponse};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct Feedback {
+    pub very_good: u16,
+    pub good: u16,
+    pub bad: u16,
+    pub very_bad: u16,
+}
+
+impl Feedback {
+    pub fn from_values(values: [u16; 4]) -> Feedback {
+        Feedback {
+            very_good: values[0],
+            good: values[1],
+            bad: values[2],
+            very_bad: values[3],
+        }
+    }
+
+    /// Returns the total number of votes
+    pub fn count_votes(&self) -> u32 {
+        self.very_good as u32 + self.good as u32 + self.bad as u32 + self.very_bad as u32
+    }
+
+    /// Returns the number of votes for given feedback value
+    pub fn get(&self, value: &FeedbackValue) -> u16 {
+        match value {
+            FeedbackValue::VeryGood | FeedbackValue::A => self.very_good,
+            FeedbackValue::Good | FeedbackValue::B => self.good,
+            FeedbackValue::Bad | FeedbackValue::C => self.bad,
+            FeedbackValue::VeryBad | FeedbackValue::D => self.very_bad,
+        }
+    }
+
+    /// Returns the average score from 0.0 (all votes "very bad") to 1.0 (all votes "very good")
+    ///
+    /// Returns `None` if there are no votes.
+    pub fn score(&self) -> Option<f32> {
+        match self.count_votes() {
+            0 => None,
+            count => Some(
+                (3 * self.very_good as u32 + 2 * self.good as u32 + self.bad as u32) as f32
+                    / (3 * count) as f32,
+            ),
+        }
+    }
+
+    /// Calculates the `FeedbackDelta` between given previous and this feedback
+    pub fn delta(&self, previous: &Feedback) -> FeedbackDelta {
+        FeedbackDelta {
+            very_good: self.very_good as i32 - previous.very_good as i32,
+            good: self.good as i32 - previous.good as i32,
+            bad: self.bad as i32 - previous.bad as i32,
+            very_bad: self.very_bad as i32 - previous.very_bad as i32,
+        }
+    }
+}
+
+/// The change of feedback values between two consecutive `Feedback` snapshots
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedbackDelta {
+    pub very_good: i32,
+    pub good: i32,
+    pub bad: i32,
+    pub very_bad: i32,
+}
+
+impl FeedbackDelta {
+    /// Indicates if any feedback value has changed
+    pub fn has_changes(&self) -> bool {
+        self.changes().next().is_some()
+    }
+
+    /// Returns all changed feedback values and the amount of change
+    pub fn changes(&self) -> impl Iterator<Item = (FeedbackValue, i32)> {
+        [
+            (FeedbackValue::VeryGood, self.very_good),
+            (FeedbackValue::Good, self.good),
+            (FeedbackValue::Bad, self.bad),
+            (FeedbackValue::VeryBad, self.very_bad),
+        ]
+        .into_iter()
+        .filter(|(_, change)| *change != 0)
+    }
+}
+
+/// An event emitted on feedback subscriptions
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum FeedbackEvent {
+    /// The absolute feedback values have changed
+    Changed(Feedback),
+    /// The change compared to the previous feedback values.
+    /// This follows a `FeedbackEvent::Changed` if there was a previous snapshot.
+    Delta(FeedbackDelta),
+    /// Feedback collection was started, votes are accepted again
+    Started,
+    /// Feedback collection was stopped, votes are not accepted until it is started again
+    Stopped,
+    /// The feedback was reset by the server, either announced or detected by feedback without votes
+    Reset,
+    /// The mode of the feedback was changed, e.g. to use it as A/B/C/D survey
+    ModeChanged(FeedbackMode),
+    /// The vote last sent by this client was cleared by a server-side reset
+    VoteCleared,
+}
+
+/// A possible feedback value
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum FeedbackValue {
+    VeryGood,
+    A,
+    Good,
+    B,
+    Bad,
+    C,
+    VeryBad,
+    D,
+}
+
+impl FeedbackValue {
+    /// Returns the four feedback values as used in given mode, in order of their index
+    pub fn values(mode: &FeedbackMode) -> [FeedbackValue; 4] {
+        match mode {
+            FeedbackMode::Feedback => [
+                FeedbackValue::VeryGood,
+                FeedbackValue::Good,
+                FeedbackValue::Bad,
+                FeedbackValue::VeryBad,
+            ],
+            FeedbackMode::Survey => [
+                FeedbackValue::A,
+                FeedbackValue::B,
+                FeedbackValue::C,
+                FeedbackValue::D,
+            ],
+        }
+    }
+
+    /// Returns the caption of this feedback value to be shown in given mode
+    pub fn label(&self, mode: &FeedbackMode) -> &'static str {
+        let index = u8::from(self.clone()) as usize;
+        match mode {
+            FeedbackMode::Feedback => ["Very good", "Good", "Bad", "Very bad"][index],
+            FeedbackMode::Survey => ["A", "B", "C", "D"][index],
+        }
+    }
+}
+
+impl From<FeedbackValue> for u8 {
+    /// Returns internal u8 representation
+    fn from(value: FeedbackValue) -> Self {
+        match value {
+            FeedbackValue::VeryGood | FeedbackValue::A => 0,
+            FeedbackValue::Good | FeedbackValue::B => 1,
+            FeedbackValue::Bad | FeedbackValue::C => 2,
+            FeedbackValue::VeryBad | FeedbackValue::D => 3,
+        }
+    }
+}
+
+impl TryFrom<u8> for FeedbackValue {
+    type Error = ClientError;
+
+    /// Converts internal u8 representation into `FeedbackValue`
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(FeedbackValue::VeryGood),
+            1 => Ok(FeedbackValue::Good),
+            2 => Ok(FeedbackValue::Bad),
+            3 => Ok(FeedbackValue::VeryBad),
+            _ => Err(FeedbackValueError(value.to_string())),
+        }
+    }
+}
+
+impl FromStr for FeedbackValue {
+    type Err = ClientError;
+
+    /// Parses a `FeedbackValue` from its name ("very_good"), letter ("a")
+    /// or internal u8 representation from "0" (very good) to "3" (very bad), ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "very_good" => Ok(FeedbackValue::VeryGood),
+            "good" => Ok(FeedbackValue::Good),
+            "bad" => Ok(FeedbackValue::Bad),
+            "very_bad" => Ok(FeedbackValue::VeryBad),
+            "a" => Ok(FeedbackValue::A),
+            "b" => Ok(FeedbackValue::B),
+            "c" => Ok(FeedbackValue::C),
+            "d" => Ok(FeedbackValue::D),
+            value => value
+                .parse::<u8>()
+                .ok()
+                .and_then(|value| FeedbackValue::try_from(value).ok())
+                .ok_or_else(|| FeedbackValueError(s.to_string())),
+        }
+    }
+}
+
+impl Display for FeedbackValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            FeedbackValue::VeryGood => "very_good",
+            FeedbackValue::A => "a",
+            FeedbackValue::Good => "good",
+            FeedbackValue::B => "b",
+            FeedbackValue::Bad => "bad",
+            FeedbackValue::C => "c",
+            FeedbackValue::VeryBad => "very_bad",
+            FeedbackValue::D => "d",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl Client<LoggedIn> {
+    /// Requests `Feedback` for given 8-digit room ID
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_feedback(&self, short_id: &ShortId) -> Result<Feedback, ClientError> {
+        #[cfg(feature = "legacy")]
+        if self.is_legacy() {
+            return self.get_legacy_feedback(short_id).await;
+        }
+
+        let room_info = self.get_room_info(short_id).await?;
+
+        self.get_feedback_by_room_id(&room_info.id)
+            .await
+            .map_err(|err| match err {
+                RoomNotFoundError(_) => RoomNotFoundError(short_id.to_string()),
+                err => err,
+            })
+    }
+
+    /// Requests `Feedback` for given room ID of an already resolved `RoomInfo`
+    ///
+    /// Unlike `get_feedback()`, this does not request membership of the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_feedback_by_room_id(&self, room_id: &RoomId) -> Result<Feedback, ClientError> {
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!("room/{}/survey", room_id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(Feedback::from_values(
+                    res.json::<SurveyResponse>()
+                        .await
+                        .map_err(|err| ParserError(err.to_string()))?
+                        .values(),
+                )),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(room_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+}
+
+impl Client<LoggedIn> {
+    /// Returns a sub-client grouping feedback requests and subscriptions
+    pub fn feedback(&self) -> FeedbackClient<'_> {
+        FeedbackClient { client: self }
+    }
+}
+
+/// A sub-client grouping feedback requests and subscriptions of a logged in `Client`
+///
+/// All methods delegate to the equally named methods of the `Client`.
+pub struct FeedbackClient<'a> {
+    client: &'a Client<LoggedIn>,
+}
+
+impl FeedbackClient<'_> {
+    /// Requests `Feedback` for given 8-digit room ID, see `Client::get_feedback()`
+    pub async fn get(&self, short_id: &ShortId) -> Result<Feedback, ClientError> {
+        self.client.get_feedback(short_id).await
+    }
+
+    /// Requests `Feedback` for given room ID, see `Client::get_feedback_by_room_id()`
+    pub async fn get_by_room_id(&self, room_id: &RoomId) -> Result<Feedback, ClientError> {
+        self.client.get_feedback_by_room_id(room_id).await
+    }
+
+    /// Sends given feedback value to given 8-digit room ID, see `Client::send_feedback()`
+    #[cfg(feature = "websocket")]
+    pub async fn send(&self, short_id: &ShortId, value: FeedbackValue) -> Result<(), ClientError> {
+        self.client.send_feedback(short_id, value).await
+    }
+
+    /// Registers a handler for feedback changes, see `Client::on_feedback_changed()`
+    #[cfg(feature = "websocket")]
+    pub async fn on_changed(
+        &self,
+        short_id: &ShortId,
+        handler: FeedbackHandler,
+    ) -> Result<(), ClientError> {
+        self.client.on_feedback_changed(short_id, handler).await
+    }
+
+    /// Returns the feedback value last sent to given 8-digit room ID, see `Client::my_feedback()`
+    #[cfg(feature = "websocket")]
+    pub fn mine(&self, short_id: &ShortId) -> Option<FeedbackValue> {
+        self.client.my_feedback(short_id)
+    }
+
+    /// Resets the feedback of given 8-digit room ID, see `Client::reset_feedback()`
+    #[cfg(feature = "websocket")]
+    pub async fn reset(&self, short_id: &ShortId) -> Result<(), ClientError> {
+        self.client.reset_feedback(short_id).await
+    }
+
+    /// Starts feedback collection of given 8-digit room ID, see `Client::start_feedback()`
+    #[cfg(feature = "websocket")]
+    pub async fn start(&self, short_id: &ShortId) -> Result<(), ClientError> {
+        self.client.start_feedback(short_id).await
+    }
+
+    /// Stops feedback collection of given 8-digit room ID, see `Client::stop_feedback()`
+    #[cfg(feature = "websocket")]
+    pub async fn stop(&self, short_id: &ShortId) -> Result<(), ClientError> {
+        self.client.stop_feedback(short_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_VALUES: [FeedbackValue; 8] = [
+        FeedbackValue::VeryGood,
+        FeedbackValue::Good,
+        FeedbackValue::Bad,
+        FeedbackValue::VeryBad,
+        FeedbackValue::A,
+        FeedbackValue::B,
+        FeedbackValue::C,
+        FeedbackValue::D,
+    ];
+
+    #[test]
+    fn should_count_votes_without_overflow() {
+        let feedback = Feedback::from_values([u16::MAX; 4]);
+
+        assert_eq!(feedback.count_votes(), 4 * u16::MAX as u32);
+        assert_eq!(feedback.score(), Some(0.5));
+    }
+
+    #[test]
+    fn should_parse_displayed_values() {
+        for value in ALL_VALUES {
+            assert_eq!(
+                value.to_string().parse::<FeedbackValue>(),
+                Ok(value.clone())
+            );
+            assert_eq!(
+                value.to_string().to_uppercase().parse::<FeedbackValue>(),
+                Ok(value)
+            );
+        }
+    }
+
+    #[test]
+    fn should_parse_internal_representation() {
+        assert_eq!("0".parse::<FeedbackValue>(), Ok(FeedbackValue::VeryGood));
+        assert_eq!("1".parse::<FeedbackValue>(), Ok(FeedbackValue::Good));
+        assert_eq!("2".parse::<FeedbackValue>(), Ok(FeedbackValue::Bad));
+        assert_eq!(" 3 ".parse::<FeedbackValue>(), Ok(FeedbackValue::VeryBad));
+        assert_eq!(
+            "4".parse::<FeedbackValue>(),
+            Err(FeedbackValueError("4".to_string()))
+        );
+        assert_eq!(
+            "e".parse::<FeedbackValue>(),
+            Err(FeedbackValueError("e".to_string()))
+        );
+    }
+
+    #[test]
+    fn should_convert_internal_representation() {
+        for value in 0..4u8 {
+            assert_eq!(FeedbackValue::try_from(value).map(u8::from), Ok(value));
+        }
+        assert_eq!(
+            FeedbackValue::try_from(4),
+            Err(FeedbackValueError("4".to_string()))
+        );
+        assert_eq!(u8::from(FeedbackValue::C), 2);
+    }
+}
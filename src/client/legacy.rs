@@ -0,0 +1,245 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Compatibility with the REST API of ARSnova 2.x servers
+//!
+//! The legacy API uses a session cookie for authentication and the 8-digit room ID ("keyword")
+//! to address rooms. Room information, feedback and comments are mapped to the legacy endpoints,
+//! other requests are not available.
+
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::client::ClientError::{ConnectionError, LoginError, ParserError, RoomNotFoundError};
+use crate::client::{Client, ClientError, Feedback, FeedbackValue, LoggedIn, LoggedOut};
+use crate::ids::{CommentId, RoomId, ShortId, UserId};
+use crate::models::{Comment, RoomInfo, RoomInfoSettings};
+
+/// The REST API of the server
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ApiVersion {
+    /// The API of current ARSnova and Particify servers
+    #[default]
+    Current,
+    /// The API of ARSnova 2.x servers
+    Legacy,
+    /// Detects the API on login using the version of the server
+    Detect,
+}
+
+#[derive(Deserialize, Debug)]
+struct LegacyUser {
+    username: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LegacySession {
+    #[serde(rename = "_id")]
+    id: String,
+    keyword: String,
+    name: String,
+    #[serde(default)]
+    active: bool,
+    #[serde(default)]
+    feedback_lock: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct LegacyFeedback {
+    values: [u16; 4],
+}
+
+#[derive(Deserialize, Debug)]
+struct LegacyQuestion {
+    #[serde(rename = "_id")]
+    id: String,
+    #[serde(default)]
+    subject: String,
+    #[serde(default)]
+    text: String,
+    timestamp: Option<i64>,
+    #[serde(default)]
+    read: bool,
+}
+
+impl From<LegacySession> for RoomInfo {
+    fn from(session: LegacySession) -> Self {
+        RoomInfo {
+            id: RoomId::new(session.id),
+            short_id: ShortId::new(session.keyword),
+            name: session.name,
+            closed: !session.active,
+            settings: RoomInfoSettings {
+                feedback_locked: session.feedback_lock,
+            },
+            ..RoomInfo::default()
+        }
+    }
+}
+
+impl From<LegacyQuestion> for Comment {
+    fn from(question: LegacyQuestion) -> Self {
+        let body = match question.subject.is_empty() {
+            true => question.text,
+            false => format!("{}\n{}", question.subject, question.text),
+        };
+        Comment {
+            id: CommentId::new(question.id),
+            body,
+            timestamp: question.timestamp.map(|timestamp| timestamp.to_string()),
+            read: question.read,
+            ack: true,
+            ..Comment::default()
+        }
+    }
+}
+
+impl<State> Client<State> {
+    /// Indicates if requests are mapped to the legacy API
+    pub(super) fn is_legacy(&self) -> bool {
+        self.api_version == ApiVersion::Legacy
+    }
+
+    /// Sends a GET request to given legacy API path and parses the response
+    async fn legacy_get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        short_id: &ShortId,
+    ) -> Result<T, ClientError> {
+        match self.send(self.http_client.get(self.endpoint(path)?)).await {
+            Ok(res) => match res.status() {
+                StatusCode::OK => res
+                    .json::<T>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+}
+
+impl Client<LoggedOut> {
+    /// Indicates if the legacy API is used, detecting it using the server version if configured
+    pub(super) async fn uses_legacy_api(&self) -> bool {
+        match self.api_version {
+            ApiVersion::Current => false,
+            ApiVersion::Legacy => true,
+            ApiVersion::Detect => self.server_version().await.is_ok_and(|server_version| {
+                server_version
+                    .version
+                    .is_some_and(|version| version.starts_with("2."))
+            }),
+        }
+    }
+
+    /// Logs in as guest using the legacy API, which stores the session in a cookie
+    pub(super) async fn legacy_guest_login(self) -> Result<Client<LoggedIn>, ClientError> {
+        let res = self
+            .send(
+                self.http_client
+                    .post(self.endpoint("auth/login?type=guest")?),
+            )
+            .await?;
+        if !res.status().is_success() {
+            return Err(LoginError);
+        }
+        let token = res
+            .headers()
+            .get("arsnova-auth-token")
+            .and_then(|token| token.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+
+        let user = match self
+            .send(self.http_client.get(self.endpoint("auth/")?))
+            .await
+        {
+            Ok(res) => res.json::<LegacyUser>().await.map_err(|_| LoginError)?,
+            Err(_) => return Err(ConnectionError),
+        };
+
+        let mut client = self.into_state::<LoggedIn>(Some(token));
+        client.user_id = Some(UserId::new(user.username));
+        client.api_version = ApiVersion::Legacy;
+        Ok(client)
+    }
+}
+
+impl Client<LoggedIn> {
+    /// Requests `RoomInfo` of given 8-digit room ID using the legacy API
+    pub(super) async fn get_legacy_room_info(
+        &self,
+        short_id: &ShortId,
+    ) -> Result<RoomInfo, ClientError> {
+        self.legacy_get::<LegacySession>(&format!("session/{}", short_id), short_id)
+            .await
+            .map(RoomInfo::from)
+    }
+
+    /// Requests `Feedback` of given 8-digit room ID using the legacy API
+    pub(super) async fn get_legacy_feedback(
+        &self,
+        short_id: &ShortId,
+    ) -> Result<Feedback, ClientError> {
+        self.legacy_get::<LegacyFeedback>(&format!("session/{}/feedback", short_id), short_id)
+            .await
+            .map(|feedback| Feedback::from_values(feedback.values))
+    }
+
+    /// Requests all `Comment`s of given 8-digit room ID using the legacy API
+    pub(super) async fn get_legacy_comments(
+        &self,
+        short_id: &ShortId,
+    ) -> Result<Vec<Comment>, ClientError> {
+        self.legacy_get::<Vec<LegacyQuestion>>(
+            &format!("audiencequestion/?sessionkey={}", short_id),
+            short_id,
+        )
+        .await
+        .map(|questions| questions.into_iter().map(Comment::from).collect())
+    }
+
+    /// Sends a feedback value for given 8-digit room ID using the legacy API
+    #[cfg_attr(not(feature = "websocket"), allow(dead_code))]
+    pub(super) async fn send_legacy_feedback(
+        &self,
+        short_id: &ShortId,
+        value: FeedbackValue,
+    ) -> Result<(), ClientError> {
+        match self
+            .send(
+                self.http_client
+                    .post(self.endpoint(&format!("session/{}/feedback", short_id))?)
+                    .json(&u8::from(value)),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK | StatusCode::CREATED => Ok(()),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+}
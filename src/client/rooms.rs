@@ -0,0 +1,1264 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Rooms and their comments, contents and announcements
+//!
+//! Room requests are available on `Client<LoggedIn>` and grouped by the sub-client `RoomsClient`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use url::Url;
+
+use crate::client::ClientError::{
+    ConnectionError, ContentGroupNotFoundError, ParserError, RoomNotFoundError,
+};
+use crate::client::{Client, ClientError, LoggedIn, Service};
+use crate::ids::{CommentId, ContentGroupId, ContentId, RoomId, ShortId};
+use crate::models::{
+    Announcement, AnnouncementState, Answer, AnswerKind, AnswerStatistics, Comment, Content,
+    ContentGroup, ContentState, ContentStats, FocusFeature, FocusState, Membership, RoomBundle,
+    RoomFocus, RoomInfo, RoomMember, RoomRole, RoomStats, SummaryResponse, WordcloudTerm,
+};
+
+/// A cache for `RoomInfo` of joined rooms
+///
+/// Entries expire after the configured TTL. A TTL of zero disables caching.
+pub(super) struct RoomInfoCache {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<ShortId, (Instant, RoomInfo)>>,
+}
+
+impl RoomInfoCache {
+    pub(super) fn new(ttl: Duration, capacity: usize) -> RoomInfoCache {
+        RoomInfoCache {
+            ttl,
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(super) fn get(&self, short_id: &ShortId) -> Option<RoomInfo> {
+        let entries = self.entries.lock().ok()?;
+        match entries.get(short_id) {
+            Some((cached_at, room_info)) if cached_at.elapsed() < self.ttl => {
+                Some(room_info.clone())
+            }
+            _ => None,
+        }
+    }
+
+    pub(super) fn insert(&self, short_id: &ShortId, room_info: &RoomInfo) {
+        if self.ttl.is_zero() || self.capacity == 0 {
+            return;
+        }
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+            while entries.len() >= self.capacity {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (cached_at, _))| *cached_at)
+                    .map(|(short_id, _)| short_id.clone());
+                match oldest {
+                    Some(oldest) => entries.remove(&oldest),
+                    None => break,
+                };
+            }
+            entries.insert(short_id.clone(), (Instant::now(), room_info.clone()));
+        }
+    }
+
+    pub(super) fn invalidate(&self, short_id: &ShortId) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(short_id);
+        }
+    }
+
+    pub(super) fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    /// Returns all cached `RoomInfo` which has not expired yet
+    pub(super) fn room_infos(&self) -> Vec<RoomInfo> {
+        match self.entries.lock() {
+            Ok(entries) => entries
+                .values()
+                .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+                .map(|(_, room_info)| room_info.clone())
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+}
+
+impl Client<LoggedIn> {
+    /// Removes cached `RoomInfo` for given 8-digit room ID
+    ///
+    /// The next request for this room will request room membership again.
+    pub fn invalidate_room_info(&self, short_id: &ShortId) {
+        self.room_cache.invalidate(short_id)
+    }
+
+    /// Removes all cached `RoomInfo`
+    pub fn clear_room_cache(&self) {
+        self.room_cache.clear()
+    }
+
+    /// Requests `RoomInfo` for given 8-digit room ID
+    ///
+    /// The room information is cached until the configured TTL expires.
+    ///
+    /// This method fails on connection or response errors, and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_room_info(&self, short_id: &ShortId) -> Result<RoomInfo, ClientError> {
+        if let Some(room_info) = self.room_cache.get(short_id) {
+            trace!("using cached room information");
+            return Ok(room_info);
+        }
+
+        #[cfg(feature = "legacy")]
+        if self.is_legacy() {
+            let room_info = self.get_legacy_room_info(short_id).await?;
+            self.room_cache.insert(short_id, &room_info);
+            return Ok(room_info);
+        }
+
+        let token = self.token.as_ref().unwrap();
+
+        let room_info_response = match self
+            .send(
+                self.http_client
+                    .post(self.endpoint(&format!("room/~{}/request-membership", short_id))?)
+                    .bearer_auth(token.to_string())
+                    .header("ars-room-role", "PARTICIPANT")
+                    .header("content-type", "application/json")
+                    .body("{}"),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => {
+                    match self
+                        .send_cached(
+                            self.http_client
+                                .get(self.endpoint(&format!("room/~{}", short_id))?)
+                                .bearer_auth(token.to_string()),
+                        )
+                        .await
+                    {
+                        Ok(res) => match res.status() {
+                            StatusCode::OK => res
+                                .json::<RoomInfo>()
+                                .await
+                                .map_err(|err| ParserError(err.to_string()))?,
+                            StatusCode::NOT_FOUND => {
+                                return Err(RoomNotFoundError(short_id.to_string()))
+                            }
+                            _ => return Err(ConnectionError),
+                        },
+                        _ => return Err(ConnectionError),
+                    }
+                }
+                StatusCode::NOT_FOUND => return Err(RoomNotFoundError(short_id.to_string())),
+                _ => return Err(ConnectionError),
+            },
+            Err(_) => {
+                return Err(ConnectionError);
+            }
+        };
+
+        self.room_cache.insert(short_id, &room_info_response);
+
+        Ok(room_info_response)
+    }
+
+    /// Requests `RoomInfo` for given 8-digit room ID without joining the room
+    ///
+    /// Unlike `get_room_info()`, no membership is requested and the room information is not
+    /// cached, e.g. to let users confirm the room before joining it.
+    ///
+    /// This method fails on connection or response errors, and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn peek_room(&self, short_id: &ShortId) -> Result<RoomInfo, ClientError> {
+        #[cfg(feature = "legacy")]
+        if self.is_legacy() {
+            return self.get_legacy_room_info(short_id).await;
+        }
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!("room/~{}", short_id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => res
+                    .json::<RoomInfo>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Requests `RoomStats` for given 8-digit room ID
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_room_stats(&self, short_id: &ShortId) -> Result<RoomStats, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        self.get_room_stats_by_room_id(&room_info.id)
+            .await
+            .map_err(|err| match err {
+                RoomNotFoundError(_) => RoomNotFoundError(short_id.to_string()),
+                err => err,
+            })
+    }
+
+    /// Requests `RoomStats` for given room ID of an already resolved `RoomInfo`
+    ///
+    /// Unlike `get_room_stats()`, this does not request membership of the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_room_stats_by_room_id(
+        &self,
+        room_id: &RoomId,
+    ) -> Result<RoomStats, ClientError> {
+        match self
+            .send_cached(
+                self.http_client
+                    .get(self.endpoint(&format!("_view/room/summary?ids={}", room_id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(res
+                    .json::<Vec<SummaryResponse>>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string()))
+                    .map(|summary_response| {
+                        summary_response
+                            .first()
+                            .unwrap_or(&SummaryResponse::default())
+                            .stats
+                            .clone()
+                    }))?,
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(room_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Requests all `RoomMember`s of given 8-digit room ID with their roles and join times
+    ///
+    /// Requires the owner or moderator role in the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_room_members(
+        &self,
+        short_id: &ShortId,
+    ) -> Result<Vec<RoomMember>, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!("room/{}/member", room_info.id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => res
+                    .json::<Vec<RoomMember>>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Requests the role of the current user in given 8-digit room ID
+    ///
+    /// The most privileged role of the membership is returned, e.g. to decide which UI to show
+    /// before requesting endpoints which require the moderator or owner role.
+    /// Unlike `get_room_info()`, no membership is requested if the user did not join the room yet.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID or the user is no member of the room.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_my_role(&self, short_id: &ShortId) -> Result<RoomRole, ClientError> {
+        let room_id = match self.room_cache.get(short_id) {
+            Some(room_info) => room_info.id,
+            None => self.peek_room(short_id).await?.id,
+        };
+        let user_id = self.get_user_id()?;
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!("_view/membership/by-user/{}", user_id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => res
+                    .json::<Vec<Membership>>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string()))?
+                    .into_iter()
+                    .find(|membership| membership.room_id == room_id)
+                    .map(|membership| RoomRole::highest(&membership.roles))
+                    .ok_or_else(|| RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Requests all acknowledged `Comment`s of given 8-digit room ID
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_comments(&self, short_id: &ShortId) -> Result<Vec<Comment>, ClientError> {
+        #[cfg(feature = "legacy")]
+        if self.is_legacy() {
+            return self.get_legacy_comments(short_id).await;
+        }
+
+        let room_info = self.get_room_info(short_id).await?;
+        let query = serde_json::json!({
+            "properties": {
+                "roomId": room_info.id,
+                "ack": true
+            },
+            "externalFilters": {}
+        });
+
+        match self
+            .send(
+                self.http_client
+                    .post(self.service_endpoint(
+                        Service::Comments,
+                        &format!("room/{}/comment/find", room_info.id),
+                    )?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string())
+                    .json(&query),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => res
+                    .json::<Vec<Comment>>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Requests all `Announcement`s of given 8-digit room ID
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_announcements(
+        &self,
+        short_id: &ShortId,
+    ) -> Result<Vec<Announcement>, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        match self
+            .send_cached(
+                self.http_client
+                    .get(self.endpoint(&format!("room/{}/announcement/", room_info.id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => res
+                    .json::<Vec<Announcement>>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Creates an `Announcement` with given title and body in given 8-digit room ID
+    ///
+    /// Requires the owner role in the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn post_announcement(
+        &self,
+        short_id: &ShortId,
+        title: &str,
+        body: &str,
+    ) -> Result<Announcement, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+        let announcement = Announcement {
+            room_id: room_info.id.clone(),
+            creator_id: self.get_user_id()?,
+            title: title.to_string(),
+            body: body.to_string(),
+            ..Announcement::default()
+        };
+
+        match self
+            .send(
+                self.http_client
+                    .post(self.endpoint(&format!("room/{}/announcement/", room_info.id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string())
+                    .json(&announcement),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK | StatusCode::CREATED => res
+                    .json::<Announcement>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Requests the `AnnouncementState` of the logged in user, containing the number of unread announcements
+    ///
+    /// This method fails on connection or response errors.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_announcement_state(&self) -> Result<AnnouncementState, ClientError> {
+        let user_id = self.get_user_id()?;
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!("user/{}/announcement-state", user_id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => res
+                    .json::<AnnouncementState>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Requests the `Content` with given ID of given 8-digit room ID
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room or content is available with given IDs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_content(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+    ) -> Result<Content, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!("room/{}/content/{}", room_info.id, content_id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => res
+                    .json::<Content>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Requests all `ContentGroup`s of given 8-digit room ID
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_content_groups(
+        &self,
+        short_id: &ShortId,
+    ) -> Result<Vec<ContentGroup>, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!("room/{}/contentgroup/", room_info.id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => res
+                    .json::<Vec<ContentGroup>>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Requests the names of all content groups of given 8-digit room ID
+    ///
+    /// Unlike `get_content_groups()`, the content IDs of the groups are not requested.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_content_group_names(
+        &self,
+        short_id: &ShortId,
+    ) -> Result<Vec<String>, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!("room/{}/contentgroup/-/names", room_info.id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => res
+                    .json::<Vec<String>>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Deletes all answers of the `Content` with given ID, e.g. to reuse it in the next run of a lecture
+    ///
+    /// Requires the moderator role in the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room or content is available with given IDs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn delete_answers(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+    ) -> Result<(), ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        match self
+            .send(
+                self.http_client
+                    .delete(self.endpoint(&format!(
+                        "room/{}/content/{}/answer",
+                        room_info.id, content_id
+                    ))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK | StatusCode::NO_CONTENT => Ok(()),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Requests the `ContentStats` of all contents of the content group with given name
+    ///
+    /// The stats contain the answer count and the ratio of correct answers of each content
+    /// in its latest round, in the order of the content group.
+    ///
+    /// Each content is requested separately, so this takes 1 + 2N requests for N contents.
+    ///
+    /// This method fails on connection or response errors of any request, if
+    /// no room is available with given room ID and with `ContentGroupNotFoundError`
+    /// if the room has no content group with given name.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_content_group_stats(
+        &self,
+        short_id: &ShortId,
+        group: &str,
+    ) -> Result<Vec<ContentStats>, ClientError> {
+        let content_ids = self
+            .get_content_groups(short_id)
+            .await?
+            .into_iter()
+            .find(|content_group| content_group.name == group)
+            .map(|content_group| content_group.content_ids)
+            .ok_or_else(|| ContentGroupNotFoundError(group.to_string()))?;
+
+        let mut stats = vec![];
+        for content_id in content_ids {
+            let content = self.get_content(short_id, &content_id).await?;
+            let statistics = self.get_answer_stats(short_id, &content_id).await?;
+            stats.push(ContentStats::new(&content, &statistics));
+        }
+        Ok(stats)
+    }
+
+    /// Requests the terms of the wordcloud `Content` with given ID in its latest round, most frequent first
+    ///
+    /// Terms banned by a moderator are not contained.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room or content is available with given IDs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_wordcloud(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+    ) -> Result<Vec<WordcloudTerm>, ClientError> {
+        let stats = self.get_answer_stats(short_id, content_id).await?;
+        let mut terms = stats
+            .round_statistics
+            .into_iter()
+            .max_by_key(|round| round.round)
+            .map(|round| {
+                round
+                    .texts
+                    .into_iter()
+                    .zip(round.counts)
+                    .map(|(term, count)| WordcloudTerm { term, count })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        terms.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.term.cmp(&b.term)));
+        Ok(terms)
+    }
+
+    /// Bans given term from the results of the wordcloud `Content` with given ID
+    ///
+    /// Requires the moderator role in the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room or content is available with given IDs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn ban_wordcloud_term(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+        term: &str,
+    ) -> Result<(), ClientError> {
+        self.change_banned_keyword(short_id, content_id, term, reqwest::Method::POST)
+            .await
+    }
+
+    /// Removes the ban of given term from the results of the wordcloud `Content` with given ID
+    ///
+    /// Requires the moderator role in the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room or content is available with given IDs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn unban_wordcloud_term(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+        term: &str,
+    ) -> Result<(), ClientError> {
+        self.change_banned_keyword(short_id, content_id, term, reqwest::Method::DELETE)
+            .await
+    }
+
+    /// Adds (`POST`) or removes (`DELETE`) given term to or from the banned keywords of a content
+    async fn change_banned_keyword(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+        term: &str,
+        method: reqwest::Method,
+    ) -> Result<(), ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        match self
+            .send(
+                self.http_client
+                    .request(
+                        method,
+                        self.endpoint(&format!(
+                            "room/{}/content/{}/banned-keywords",
+                            room_info.id, content_id
+                        ))?,
+                    )
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string())
+                    .json(&serde_json::json!({ "keyword": term })),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK | StatusCode::NO_CONTENT => Ok(()),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Exports given 8-digit room ID with its settings, content groups, contents and acknowledged comments
+    ///
+    /// This method fails on connection or response errors of any request and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn export_room(&self, short_id: &ShortId) -> Result<RoomBundle, ClientError> {
+        let room = self.get_room_info(short_id).await?;
+        let content_groups = self.get_content_groups(short_id).await?;
+
+        let mut contents: Vec<Content> = vec![];
+        for content_id in content_groups.iter().flat_map(|group| &group.content_ids) {
+            if !contents.iter().any(|content| &content.id == content_id) {
+                contents.push(self.get_content(short_id, content_id).await?);
+            }
+        }
+
+        Ok(RoomBundle {
+            room,
+            content_groups,
+            contents,
+            comments: self.get_comments(short_id).await?,
+        })
+    }
+
+    /// Recreates the room of given `RoomBundle` as a new room owned by the logged in user
+    ///
+    /// Contents, content groups and comments get new IDs, the returned `RoomInfo`
+    /// contains the new room ID and 8-digit short ID.
+    ///
+    /// This method fails on connection or response errors of any request.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, bundle))
+    )]
+    pub async fn import_room(&self, bundle: &RoomBundle) -> Result<RoomInfo, ClientError> {
+        let room = RoomInfo {
+            id: RoomId::default(),
+            short_id: ShortId::default(),
+            ..bundle.room.clone()
+        };
+        let room: RoomInfo = self.create(self.endpoint("room/")?, &room).await?;
+
+        let mut content_ids = HashMap::new();
+        for content in &bundle.contents {
+            let created: Content = self
+                .create(
+                    self.endpoint(&format!("room/{}/content/", room.id))?,
+                    &Content {
+                        id: ContentId::default(),
+                        room_id: room.id.clone(),
+                        ..content.clone()
+                    },
+                )
+                .await?;
+            content_ids.insert(content.id.clone(), created.id);
+        }
+
+        for group in &bundle.content_groups {
+            let _: ContentGroup = self
+                .create(
+                    self.endpoint(&format!("room/{}/contentgroup/", room.id))?,
+                    &ContentGroup {
+                        id: ContentGroupId::default(),
+                        room_id: room.id.clone(),
+                        content_ids: group
+                            .content_ids
+                            .iter()
+                            .filter_map(|content_id| content_ids.get(content_id).cloned())
+                            .collect(),
+                        ..group.clone()
+                    },
+                )
+                .await?;
+        }
+
+        for comment in &bundle.comments {
+            let _: Comment = self
+                .create(
+                    self.service_endpoint(
+                        Service::Comments,
+                        &format!("room/{}/comment/", room.id),
+                    )?,
+                    &Comment {
+                        id: CommentId::default(),
+                        room_id: room.id.clone(),
+                        ..comment.clone()
+                    },
+                )
+                .await?;
+        }
+
+        Ok(room)
+    }
+
+    /// Creates given object using a POST request to given endpoint and returns the created object
+    async fn create<T: Serialize, R: DeserializeOwned>(
+        &self,
+        endpoint: Url,
+        body: &T,
+    ) -> Result<R, ClientError> {
+        match self
+            .send(
+                self.http_client
+                    .post(endpoint)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string())
+                    .json(body),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK | StatusCode::CREATED => res
+                    .json::<R>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Requests the `AnswerStatistics` of the `Content` with given ID
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room or content is available with given IDs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_answer_stats(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+    ) -> Result<AnswerStatistics, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!(
+                        "room/{}/content/{}/stats",
+                        room_info.id, content_id
+                    ))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => res
+                    .json::<AnswerStatistics>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Requests the `Answer` of the current user to the `Content` with given ID in its current round
+    ///
+    /// The answer contains its round and the chosen options, e.g. to restore the selection after
+    /// a restart. Results in `None` if the current user did not answer yet.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room or content is available with given IDs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_my_answer(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+    ) -> Result<Option<Answer>, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!(
+                        "room/{}/content/{}/myanswer",
+                        room_info.id, content_id
+                    ))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => {
+                    let body = res.bytes().await.map_err(|_| ConnectionError)?;
+                    if body.is_empty() {
+                        return Ok(None);
+                    }
+                    serde_json::from_slice::<Option<Answer>>(&body)
+                        .map_err(|err| ParserError(err.to_string()))
+                }
+                StatusCode::NO_CONTENT => Ok(None),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Changes the state of the `Content` with given ID, e.g. to publish it or to start a new round
+    ///
+    /// Requires the moderator role in the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room or content is available with given IDs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn set_content_state(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+        state: &ContentState,
+    ) -> Result<Content, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        match self
+            .send(
+                self.http_client
+                    .patch(self.endpoint(&format!("room/{}/content/{}", room_info.id, content_id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string())
+                    .json(&serde_json::json!({ "state": state })),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => res
+                    .json::<Content>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Sets the round of the `Content` with given ID, keeping its remaining state
+    ///
+    /// Requires the moderator role in the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room or content is available with given IDs.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn set_content_round(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+        round: u32,
+    ) -> Result<Content, ClientError> {
+        let content = self.get_content(short_id, content_id).await?;
+        let state = ContentState {
+            round,
+            ..content.state
+        };
+
+        self.set_content_state(short_id, content_id, &state).await
+    }
+
+    /// Sets what the presenter currently shows in given 8-digit room ID
+    ///
+    /// Requires the moderator role in the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn set_focus(
+        &self,
+        short_id: &ShortId,
+        focus: &RoomFocus,
+    ) -> Result<(), ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+
+        match self
+            .send(
+                self.http_client
+                    .post(self.endpoint(&format!("room/{}/focus-event", room_info.id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string())
+                    .json(focus),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK | StatusCode::CREATED | StatusCode::NO_CONTENT => Ok(()),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Presents the `Content` with given ID in given 8-digit room ID
+    ///
+    /// Requires the moderator role in the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn present_content(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+    ) -> Result<(), ClientError> {
+        let focus = RoomFocus {
+            feature: FocusFeature::Contents,
+            focus_state: FocusState {
+                content_id: Some(content_id.clone()),
+                ..FocusState::default()
+            },
+        };
+
+        self.set_focus(short_id, &focus).await
+    }
+
+    /// Answers given choice `Content` with the options at given indexes in its current round
+    ///
+    /// This method fails on connection or response errors, if
+    /// no room is available with given room ID or the content does not accept answers.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, content))
+    )]
+    pub async fn answer_choice(
+        &self,
+        short_id: &ShortId,
+        content: &Content,
+        selected: Vec<usize>,
+    ) -> Result<Answer, ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+        let answer = Answer {
+            content_id: content.id.clone(),
+            room_id: room_info.id.clone(),
+            creator_id: self.get_user_id()?,
+            round: content.state.round.max(1),
+            kind: AnswerKind::Choice {
+                selected_choice_indexes: selected,
+            },
+            ..Answer::default()
+        };
+
+        match self
+            .send(
+                self.http_client
+                    .post(self.endpoint(&format!("room/{}/answer/", room_info.id))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string())
+                    .json(&answer),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK | StatusCode::CREATED => res
+                    .json::<Answer>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string())),
+                StatusCode::NOT_FOUND => Err(RoomNotFoundError(short_id.to_string())),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+
+    /// Requests `RoomStats` for all given room IDs using a single request
+    ///
+    /// Rooms not included in the response are missing in the resulting map,
+    /// as well as returned summaries without or with a not requested room ID.
+    ///
+    /// This method fails on connection or response errors.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_rooms_stats(
+        &self,
+        room_ids: &[RoomId],
+    ) -> Result<HashMap<RoomId, RoomStats>, ClientError> {
+        if room_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let ids = room_ids
+            .iter()
+            .map(|room_id| room_id.as_str())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        match self
+            .send(
+                self.http_client
+                    .get(self.endpoint(&format!("_view/room/summary?ids={}", ids))?)
+                    .bearer_auth(self.token.as_ref().unwrap_or(&"".to_string()).to_string()),
+            )
+            .await
+        {
+            Ok(res) => match res.status() {
+                StatusCode::OK => Ok(res
+                    .json::<Vec<SummaryResponse>>()
+                    .await
+                    .map_err(|err| ParserError(err.to_string()))?
+                    .into_iter()
+                    .filter(|summary_response| room_ids.contains(&summary_response.id))
+                    .map(|summary_response| (summary_response.id, summary_response.stats))
+                    .collect()),
+                _ => Err(ConnectionError),
+            },
+            Err(_) => Err(ConnectionError),
+        }
+    }
+}
+
+impl Client<LoggedIn> {
+    /// Returns a sub-client grouping room requests
+    pub fn rooms(&self) -> RoomsClient<'_> {
+        RoomsClient { client: self }
+    }
+}
+
+/// A sub-client grouping room requests of a logged in `Client`
+///
+/// All methods delegate to the equally named methods of the `Client`.
+pub struct RoomsClient<'a> {
+    client: &'a Client<LoggedIn>,
+}
+
+impl RoomsClient<'_> {
+    /// Requests `RoomInfo` for given 8-digit room ID, see `Client::get_room_info()`
+    pub async fn info(&self, short_id: &ShortId) -> Result<RoomInfo, ClientError> {
+        self.client.get_room_info(short_id).await
+    }
+
+    /// Requests `RoomInfo` for given 8-digit room ID without joining, see `Client::peek_room()`
+    pub async fn peek(&self, short_id: &ShortId) -> Result<RoomInfo, ClientError> {
+        self.client.peek_room(short_id).await
+    }
+
+    /// Removes cached `RoomInfo` for given 8-digit room ID, see `Client::invalidate_room_info()`
+    pub fn invalidate(&self, short_id: &ShortId) {
+        self.client.invalidate_room_info(short_id)
+    }
+
+    /// Requests `RoomStats` for given 8-digit room ID, see `Client::get_room_stats()`
+    pub async fn stats(&self, short_id: &ShortId) -> Result<RoomStats, ClientError> {
+        self.client.get_room_stats(short_id).await
+    }
+
+    /// Requests `RoomStats` for given room ID, see `Client::get_room_stats_by_room_id()`
+    pub async fn stats_by_room_id(&self, room_id: &RoomId) -> Result<RoomStats, ClientError> {
+        self.client.get_room_stats_by_room_id(room_id).await
+    }
+
+    /// Requests `RoomStats` for all given room IDs, see `Client::get_rooms_stats()`
+    pub async fn stats_of_rooms(
+        &self,
+        room_ids: &[RoomId],
+    ) -> Result<HashMap<RoomId, RoomStats>, ClientError> {
+        self.client.get_rooms_stats(room_ids).await
+    }
+
+    /// Requests all `RoomMember`s of given 8-digit room ID, see `Client::get_room_members()`
+    pub async fn members(&self, short_id: &ShortId) -> Result<Vec<RoomMember>, ClientError> {
+        self.client.get_room_members(short_id).await
+    }
+
+    /// Requests the role of the current user in given 8-digit room ID, see `Client::get_my_role()`
+    pub async fn my_role(&self, short_id: &ShortId) -> Result<RoomRole, ClientError> {
+        self.client.get_my_role(short_id).await
+    }
+
+    /// Requests all acknowledged `Comment`s of given 8-digit room ID, see `Client::get_comments()`
+    pub async fn comments(&self, short_id: &ShortId) -> Result<Vec<Comment>, ClientError> {
+        self.client.get_comments(short_id).await
+    }
+
+    /// Requests all `Announcement`s of given 8-digit room ID, see `Client::get_announcements()`
+    pub async fn announcements(
+        &self,
+        short_id: &ShortId,
+    ) -> Result<Vec<Announcement>, ClientError> {
+        self.client.get_announcements(short_id).await
+    }
+
+    /// Requests all `ContentGroup`s of given 8-digit room ID, see `Client::get_content_groups()`
+    pub async fn content_groups(
+        &self,
+        short_id: &ShortId,
+    ) -> Result<Vec<ContentGroup>, ClientError> {
+        self.client.get_content_groups(short_id).await
+    }
+
+    /// Requests the names of all content groups of given 8-digit room ID,
+    /// see `Client::get_content_group_names()`
+    pub async fn content_group_names(
+        &self,
+        short_id: &ShortId,
+    ) -> Result<Vec<String>, ClientError> {
+        self.client.get_content_group_names(short_id).await
+    }
+
+    /// Exports given 8-digit room ID as `RoomBundle`, see `Client::export_room()`
+    pub async fn export(&self, short_id: &ShortId) -> Result<RoomBundle, ClientError> {
+        self.client.export_room(short_id).await
+    }
+
+    /// Recreates the room of given `RoomBundle`, see `Client::import_room()`
+    pub async fn import(&self, bundle: &RoomBundle) -> Result<RoomInfo, ClientError> {
+        self.client.import_room(bundle).await
+    }
+}
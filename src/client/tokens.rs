@@ -0,0 +1,87 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Persistent storage of guest tokens
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+
+/// Storage of guest tokens to reuse the same guest identity across runs
+///
+/// Tokens are stored per API URL. Implement this to use custom backends, e.g. a keyring.
+/// Storage errors should be ignored, a new guest login is requested if no token is available.
+pub trait TokenStore: Send + Sync {
+    /// Returns the stored token for given API URL
+    fn load(&self, api_url: &str) -> Option<String>;
+
+    /// Stores given token for given API URL, replacing any previously stored token
+    fn store(&self, api_url: &str, token: &str);
+}
+
+/// A `TokenStore` keeping tokens in a JSON file
+///
+/// The file contains an object mapping API URLs to tokens. On Unix, it is only
+/// readable by the current user.
+pub struct FileTokenStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileTokenStore {
+    /// Creates a token store using given file, which is created on first use
+    pub fn new<P: Into<PathBuf>>(path: P) -> FileTokenStore {
+        FileTokenStore {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn read(&self) -> BTreeMap<String, String> {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn write(&self, tokens: &BTreeMap<String, String>) -> std::io::Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&self.path)?;
+        file.write_all(serde_json::to_string_pretty(tokens)?.as_bytes())
+    }
+}
+
+impl TokenStore for FileTokenStore {
+    fn load(&self, api_url: &str) -> Option<String> {
+        let _lock = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+        self.read().remove(api_url)
+    }
+
+    fn store(&self, api_url: &str, token: &str) {
+        let _lock = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut tokens = self.read();
+        tokens.insert(api_url.to_string(), token.to_string());
+        let _ = self.write(&tokens);
+    }
+}
@@ -0,0 +1,2420 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use futures_util::{SinkExt, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::select;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::backoff::BackoffPolicy;
+use crate::client::ClientError::{ConnectionError, UrlError, VoteSuppressedError};
+use crate::client::{
+    AnnouncementEvent, Client, ClientError, CommentEvent, ContentEvent, Envelope, EventKinds,
+    Feedback, FeedbackEvent, FeedbackValue, FocusEvent, LoggedIn, LoggedOut, RoomEvent, Service,
+};
+use crate::ids::{CommentId, ContentId, RoomId, ShortId, UserId};
+use crate::metrics::{Counter, Metrics, SubscriptionGuard, Timing};
+use crate::models::{
+    Announcement, Comment, CommentChanges, ContentState, FeedbackExtension, FocusFeature,
+    RoomFocus, RoomStats,
+};
+use crate::recorder::Recorder;
+use crate::runtime::Runtime;
+use crate::transport::{WsRead, WsWrite};
+
+struct WsConnectMessage {
+    token: String,
+    heartbeat_interval: Duration,
+    server_heartbeat: Option<Duration>,
+}
+
+impl WsConnectMessage {
+    fn new(
+        token: &str,
+        heartbeat_interval: Duration,
+        server_heartbeat: Option<Duration>,
+    ) -> WsConnectMessage {
+        WsConnectMessage {
+            token: token.to_string(),
+            heartbeat_interval,
+            server_heartbeat,
+        }
+    }
+}
+
+impl Display for WsConnectMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = format!(
+            "CONNECT\ntoken:{}\naccept-version:{}\nheart-beat:{},{}\n\n\0",
+            self.token,
+            STOMP_VERSIONS.join(","),
+            self.heartbeat_interval.as_millis(),
+            self.server_heartbeat.unwrap_or_default().as_millis()
+        );
+        write!(f, "{}", str)
+    }
+}
+
+struct WsSubscribeMessage {
+    room_id: String,
+    subscription_id: String,
+}
+
+impl WsSubscribeMessage {
+    fn new(room_id: &RoomId, subscription_id: &str) -> WsSubscribeMessage {
+        WsSubscribeMessage {
+            room_id: room_id.to_string(),
+            subscription_id: subscription_id.to_string(),
+        }
+    }
+}
+
+impl Display for WsSubscribeMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = format!(
+            "SUBSCRIBE\nid:{}\ndestination:/topic/{}.stream\n\n\0",
+            self.subscription_id, self.room_id
+        );
+        write!(f, "{}", str)
+    }
+}
+
+struct WsSubscribeFeedbackMessage {
+    room_id: String,
+    subscription_id: String,
+}
+
+impl WsSubscribeFeedbackMessage {
+    fn new(room_id: &RoomId) -> WsSubscribeFeedbackMessage {
+        WsSubscribeFeedbackMessage {
+            room_id: room_id.to_string(),
+            subscription_id: "sub-6".to_string(),
+        }
+    }
+
+    fn with_subscription_id(mut self, subscription_id: &str) -> WsSubscribeFeedbackMessage {
+        self.subscription_id = subscription_id.to_string();
+        self
+    }
+}
+
+impl Display for WsSubscribeFeedbackMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = format!(
+            "SUBSCRIBE\nid:{}\ndestination:/topic/{}.feedback.stream\n\n\0",
+            self.subscription_id, self.room_id
+        );
+        write!(f, "{}", str)
+    }
+}
+
+struct WsSubscribeCommentMessage {
+    room_id: String,
+    subscription_id: String,
+}
+
+impl WsSubscribeCommentMessage {
+    fn new(room_id: &RoomId, subscription_id: &str) -> WsSubscribeCommentMessage {
+        WsSubscribeCommentMessage {
+            room_id: room_id.to_string(),
+            subscription_id: subscription_id.to_string(),
+        }
+    }
+}
+
+impl Display for WsSubscribeCommentMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SUBSCRIBE\nid:{}\ndestination:/topic/{}.comment.stream\n\n\0",
+            self.subscription_id, self.room_id
+        )
+    }
+}
+
+struct WsSubscribeFocusMessage {
+    room_id: String,
+    subscription_id: String,
+}
+
+impl WsSubscribeFocusMessage {
+    fn new(room_id: &RoomId, subscription_id: &str) -> WsSubscribeFocusMessage {
+        WsSubscribeFocusMessage {
+            room_id: room_id.to_string(),
+            subscription_id: subscription_id.to_string(),
+        }
+    }
+}
+
+impl Display for WsSubscribeFocusMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SUBSCRIBE\nid:{}\ndestination:/topic/{}.focus.state.stream\n\n\0",
+            self.subscription_id, self.room_id
+        )
+    }
+}
+
+/// The topic of a room to subscribe to
+#[derive(Clone, Copy, Debug)]
+enum Topic {
+    Feedback,
+    Comments,
+    Room,
+    Focus,
+}
+
+impl Topic {
+    /// Returns the topics providing given kinds of events
+    fn for_kinds(kinds: EventKinds) -> Vec<Topic> {
+        let mut topics = vec![];
+        if kinds.contains(EventKinds::FEEDBACK) {
+            topics.push(Topic::Feedback);
+        }
+        if kinds.contains(EventKinds::COMMENTS) {
+            topics.push(Topic::Comments);
+        }
+        if kinds.intersects(EventKinds::CONTENT | EventKinds::ANNOUNCEMENTS) {
+            topics.push(Topic::Room);
+        }
+        topics
+    }
+
+    fn subscribe_message(&self, room_id: &RoomId, subscription_id: &str) -> String {
+        match self {
+            Topic::Feedback => WsSubscribeFeedbackMessage::new(room_id)
+                .with_subscription_id(subscription_id)
+                .to_string(),
+            Topic::Comments => WsSubscribeCommentMessage::new(room_id, subscription_id).to_string(),
+            Topic::Room => WsSubscribeMessage::new(room_id, subscription_id).to_string(),
+            Topic::Focus => WsSubscribeFocusMessage::new(room_id, subscription_id).to_string(),
+        }
+    }
+}
+
+/// A STOMP frame borrowing its content from the received websocket message
+#[derive(Debug)]
+pub(crate) struct StompFrame<'a> {
+    pub(crate) command: &'a str,
+    headers: &'a str,
+    pub(crate) body: &'a str,
+}
+
+impl<'a> StompFrame<'a> {
+    pub(crate) fn parse(raw: &'a str) -> Option<StompFrame<'a>> {
+        // Skip heart-beat EOLs which may precede a frame
+        let raw = raw.trim_start_matches(['\r', '\n']);
+        let (head, body) = raw.split_once("\n\n")?;
+        let (command, headers) = head.split_once('\n').unwrap_or((head, ""));
+        let body = body.split_once('\0').map_or(body, |(body, _)| body);
+        Some(StompFrame {
+            command: command.trim_end_matches('\r'),
+            headers,
+            body: body.trim(),
+        })
+    }
+
+    pub(crate) fn header(&self, name: &str) -> Option<&'a str> {
+        self.headers
+            .lines()
+            .find_map(|line| match line.split_once(':') {
+                Some((key, value)) if key == name => Some(value.trim_end_matches('\r')),
+                _ => None,
+            })
+    }
+
+    /// Returns the server time given as milliseconds since UNIX epoch in the `timestamp` header
+    fn server_time(&self) -> Option<SystemTime> {
+        let millis = self.header("timestamp")?.parse::<u64>().ok()?;
+        UNIX_EPOCH.checked_add(Duration::from_millis(millis))
+    }
+}
+
+/// The STOMP protocol versions requested in CONNECT frames
+const STOMP_VERSIONS: [&str; 3] = ["1.2", "1.1", "1.0"];
+
+/// The state of a STOMP session on a websocket connection
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum StompState {
+    /// The CONNECT frame was sent and the CONNECTED frame is expected
+    Handshake,
+    /// The server accepted the session and subscriptions can be sent
+    Connected,
+    /// Subscriptions were sent and MESSAGE frames are expected
+    Subscribed,
+    /// The session ended by an ERROR frame or the connection was lost
+    Closed,
+}
+
+impl StompState {
+    /// Returns if a received frame with given command is expected in this state
+    ///
+    /// ERROR frames are expected in every state until the session was closed, RECEIPT frames
+    /// in every state after the session was accepted.
+    fn accepts(&self, command: &str) -> bool {
+        match self {
+            StompState::Handshake => command == "CONNECTED" || command == "ERROR",
+            StompState::Connected => command == "RECEIPT" || command == "ERROR",
+            StompState::Subscribed => {
+                command == "MESSAGE" || command == "RECEIPT" || command == "ERROR"
+            }
+            StompState::Closed => false,
+        }
+    }
+}
+
+/// The receiving side of a STOMP session
+///
+/// Received websocket messages are split into STOMP frames, as a message may contain
+/// more than one frame and a frame may be split across messages.
+struct StompSession {
+    state: StompState,
+    buffer: String,
+    max_frame_size: usize,
+    server_heartbeat: Option<Duration>,
+}
+
+impl StompSession {
+    fn new(max_frame_size: usize) -> StompSession {
+        StompSession {
+            state: StompState::Handshake,
+            buffer: String::new(),
+            max_frame_size,
+            server_heartbeat: None,
+        }
+    }
+
+    /// Changes the state of the session
+    ///
+    /// Session states only advance from `Handshake` over `Connected` and `Subscribed` to `Closed`,
+    /// a transition back to a previous state is rejected.
+    fn transition(&mut self, next: StompState) -> Result<(), ClientError> {
+        if next < self.state {
+            warn!(from = ?self.state, to = ?next, "rejecting backward STOMP session transition");
+            return Err(ConnectionError);
+        }
+        if next != self.state {
+            debug!(from = ?self.state, to = ?next, "STOMP session state changed");
+            self.state = next;
+        }
+        Ok(())
+    }
+
+    /// Closes the session, which is possible in every state
+    fn close(&mut self) {
+        let _ = self.transition(StompState::Closed);
+    }
+
+    /// Appends the text of a received websocket message
+    ///
+    /// If a frame exceeds the maximum frame size, the frame is rejected and counted, the session
+    /// is closed and `false` is returned.
+    fn push(&mut self, text: &str, metrics: &Metrics) -> bool {
+        self.buffer.push_str(text);
+        if self
+            .buffer
+            .split('\0')
+            .any(|frame| frame.len() > self.max_frame_size)
+        {
+            warn!(
+                max_frame_size = self.max_frame_size,
+                "STOMP frame too large, closing websocket connection"
+            );
+            metrics.increment(Counter::RejectedFrames);
+            self.buffer.clear();
+            self.close();
+            return false;
+        }
+        true
+    }
+
+    /// Takes the next complete frame, skipping heart-beat EOLs between frames
+    fn next_frame(&mut self) -> Option<String> {
+        let start = self.buffer.len() - self.buffer.trim_start_matches(['\r', '\n']).len();
+        self.buffer.drain(..start);
+        let end = self.buffer.find('\0')?;
+        Some(self.buffer.drain(..=end).collect())
+    }
+
+    /// Checks the command of given frame against the state of the session
+    ///
+    /// Unexpected frames are rejected and counted. Received ERROR frames close the session.
+    fn accept(&mut self, frame: &StompFrame, metrics: &Metrics) -> bool {
+        if !self.state.accepts(frame.command) {
+            warn!(command = frame.command, state = ?self.state, "rejecting unexpected STOMP frame");
+            metrics.increment(Counter::RejectedFrames);
+            return false;
+        }
+        if frame.command == "ERROR" {
+            warn!(message = frame.header("message"), "received STOMP error");
+            self.close();
+        }
+        true
+    }
+}
+
+/// The body of a received STOMP MESSAGE frame, tagged by the type of its payload
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type")]
+enum WsMessageBody {
+    FeedbackChanged {
+        payload: WsFeedbackPayload,
+    },
+    FeedbackStarted {},
+    FeedbackStopped {},
+    FeedbackReset {},
+    FeedbackTypeChanged {
+        payload: FeedbackExtension,
+    },
+    CommentCreated {
+        payload: Comment,
+    },
+    CommentPatched {
+        payload: WsCommentPatchedPayload,
+    },
+    CommentHighlighted {
+        payload: WsCommentHighlightedPayload,
+    },
+    CommentDeleted {
+        payload: WsCommentDeletedPayload,
+    },
+    ContentStateChanged {
+        payload: WsContentStatePayload,
+    },
+    AnnouncementCreated {
+        payload: Announcement,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+impl WsMessageBody {
+    /// Deserializes the body of a STOMP MESSAGE frame, counting bodies that cannot be parsed
+    fn from_frame(frame: &StompFrame, metrics: &Metrics) -> Option<WsMessageBody> {
+        parse_body(frame, metrics)
+    }
+
+    /// Returns the kind of events this message belongs to, unknown messages belong to none
+    fn kind(&self) -> EventKinds {
+        match self {
+            WsMessageBody::FeedbackChanged { .. }
+            | WsMessageBody::FeedbackStarted {}
+            | WsMessageBody::FeedbackStopped {}
+            | WsMessageBody::FeedbackReset {}
+            | WsMessageBody::FeedbackTypeChanged { .. } => EventKinds::FEEDBACK,
+            WsMessageBody::CommentCreated { .. }
+            | WsMessageBody::CommentPatched { .. }
+            | WsMessageBody::CommentHighlighted { .. }
+            | WsMessageBody::CommentDeleted { .. } => EventKinds::COMMENTS,
+            WsMessageBody::ContentStateChanged { .. } => EventKinds::CONTENT,
+            WsMessageBody::AnnouncementCreated { .. } => EventKinds::ANNOUNCEMENTS,
+            WsMessageBody::Unknown => EventKinds::empty(),
+        }
+    }
+
+    fn into_feedback_message(self, server_time: Option<SystemTime>) -> Option<FeedbackMessage> {
+        match self {
+            WsMessageBody::FeedbackChanged { payload } => Some(FeedbackMessage::Changed(
+                Envelope::new(payload.get_feedback(), server_time),
+            )),
+            WsMessageBody::FeedbackStarted {} => Some(FeedbackMessage::Status(Envelope::new(
+                FeedbackEvent::Started,
+                server_time,
+            ))),
+            WsMessageBody::FeedbackStopped {} => Some(FeedbackMessage::Status(Envelope::new(
+                FeedbackEvent::Stopped,
+                server_time,
+            ))),
+            WsMessageBody::FeedbackTypeChanged { payload } => Some(FeedbackMessage::Status(
+                Envelope::new(FeedbackEvent::ModeChanged(payload.mode), server_time),
+            )),
+            WsMessageBody::FeedbackReset {} => {
+                Some(FeedbackMessage::Reset(Envelope::new((), server_time)))
+            }
+            _ => None,
+        }
+    }
+
+    fn into_comment_event(self) -> Option<CommentEvent> {
+        match self {
+            WsMessageBody::CommentCreated { payload } => Some(CommentEvent::Created(payload)),
+            WsMessageBody::CommentPatched { payload } => {
+                Some(CommentEvent::Patched(payload.id, payload.changes))
+            }
+            WsMessageBody::CommentHighlighted { payload } => {
+                Some(CommentEvent::Highlighted(payload.id, payload.lights))
+            }
+            WsMessageBody::CommentDeleted { payload } => Some(CommentEvent::Deleted(payload.id)),
+            _ => None,
+        }
+    }
+
+    fn into_content_event(self) -> Option<ContentEvent> {
+        match self {
+            WsMessageBody::ContentStateChanged { payload } => {
+                Some(ContentEvent::StateChanged(payload.id, payload.state))
+            }
+            _ => None,
+        }
+    }
+
+    fn into_announcement_event(self) -> Option<AnnouncementEvent> {
+        match self {
+            WsMessageBody::AnnouncementCreated { payload } => {
+                Some(AnnouncementEvent::Created(payload))
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts into a `RoomEvent`
+    ///
+    /// Feedback resets are not converted, as the resulting events depend on the state of the
+    /// subscription, see `FeedbackMessage::Reset`.
+    fn into_room_event(self) -> Option<RoomEvent> {
+        match self.kind() {
+            EventKinds::FEEDBACK => match self.into_feedback_message(None)? {
+                FeedbackMessage::Changed(envelope) => {
+                    Some(RoomEvent::Feedback(FeedbackEvent::Changed(envelope.event)))
+                }
+                FeedbackMessage::Status(envelope) => Some(RoomEvent::Feedback(envelope.event)),
+                FeedbackMessage::Reset(_) => None,
+            },
+            EventKinds::COMMENTS => self.into_comment_event().map(RoomEvent::Comment),
+            EventKinds::CONTENT => self.into_content_event().map(RoomEvent::Content),
+            EventKinds::ANNOUNCEMENTS => {
+                self.into_announcement_event().map(RoomEvent::Announcement)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Deserializes the body of a STOMP MESSAGE frame, counting bodies that cannot be parsed
+fn parse_body<T: DeserializeOwned>(frame: &StompFrame, metrics: &Metrics) -> Option<T> {
+    trace!(command = frame.command, "received STOMP frame");
+    if frame.command != "MESSAGE" {
+        return None;
+    }
+    match serde_json::from_str::<T>(frame.body) {
+        Ok(body) => Some(body),
+        Err(_) => {
+            metrics.increment(Counter::ParseFailures);
+            None
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct WsContentStatePayload {
+    id: ContentId,
+    #[serde(default)]
+    state: ContentState,
+}
+
+#[derive(Deserialize, Debug)]
+struct WsCommentPatchedPayload {
+    id: CommentId,
+    #[serde(default)]
+    changes: CommentChanges,
+}
+
+#[derive(Deserialize, Debug)]
+struct WsCommentHighlightedPayload {
+    id: CommentId,
+    #[serde(default)]
+    lights: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct WsCommentDeletedPayload {
+    id: CommentId,
+}
+
+#[derive(Deserialize, Debug)]
+struct WsFeedbackPayload {
+    values: [u16; 4],
+}
+
+impl WsFeedbackPayload {
+    fn get_feedback(self) -> Feedback {
+        Feedback::from_values(self.values)
+    }
+}
+
+struct WsDisconnectMessage {
+    receipt: String,
+}
+
+impl WsDisconnectMessage {
+    fn new(receipt: &str) -> WsDisconnectMessage {
+        WsDisconnectMessage {
+            receipt: receipt.to_string(),
+        }
+    }
+}
+
+impl Display for WsDisconnectMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DISCONNECT\nreceipt:{}\n\n\0", self.receipt)
+    }
+}
+
+#[derive(Debug)]
+struct WsCreateFeedbackMessage {
+    room_id: String,
+    user_id: String,
+    value: u8,
+}
+
+impl WsCreateFeedbackMessage {
+    fn new(room_id: &RoomId, user_id: &UserId, value: FeedbackValue) -> WsCreateFeedbackMessage {
+        WsCreateFeedbackMessage {
+            room_id: room_id.to_string(),
+            user_id: user_id.to_string(),
+            value: value.into(),
+        }
+    }
+}
+
+impl Display for WsCreateFeedbackMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let payload = json!({
+            "type": "CreateFeedback",
+            "payload": {
+                "roomId": self.room_id,
+                "userId": self.user_id,
+                "value": self.value
+            }
+        })
+        .to_string();
+
+        write!(f,
+                "SEND\ndestination:/queue/feedback.command\ncontent-type:application/json\ncontent-length:{}\n\n{}\0",
+                payload.len(),
+                payload,
+            )
+    }
+}
+
+/// A moderator command changing the live feedback of a room
+#[derive(Clone, Copy, Debug)]
+enum FeedbackCommand {
+    Reset,
+    Start,
+    Stop,
+}
+
+struct WsFeedbackCommandMessage {
+    command: FeedbackCommand,
+    room_id: String,
+}
+
+impl WsFeedbackCommandMessage {
+    fn new(command: FeedbackCommand, room_id: &RoomId) -> WsFeedbackCommandMessage {
+        WsFeedbackCommandMessage {
+            command,
+            room_id: room_id.to_string(),
+        }
+    }
+}
+
+impl Display for WsFeedbackCommandMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (destination, command_type) = match self.command {
+            FeedbackCommand::Reset => ("reset", "ResetFeedback"),
+            FeedbackCommand::Start => ("start", "StartFeedback"),
+            FeedbackCommand::Stop => ("stop", "StopFeedback"),
+        };
+        let payload = json!({
+            "type": command_type,
+            "payload": {
+                "roomId": self.room_id
+            }
+        })
+        .to_string();
+
+        write!(f,
+                "SEND\ndestination:/queue/feedback.command.{}\ncontent-type:application/json\ncontent-length:{}\n\n{}\0",
+                destination,
+                payload.len(),
+                payload,
+            )
+    }
+}
+
+/// The feedback values last sent by this client and the time they were sent at, per room
+#[derive(Debug, Default)]
+pub(crate) struct OwnVotes {
+    votes: Mutex<HashMap<ShortId, (FeedbackValue, Instant)>>,
+}
+
+impl OwnVotes {
+    fn record(&self, short_id: &ShortId, value: FeedbackValue) {
+        if let Ok(mut votes) = self.votes.lock() {
+            votes.insert(short_id.clone(), (value, Instant::now()));
+        }
+    }
+
+    fn get(&self, short_id: &ShortId) -> Option<FeedbackValue> {
+        self.votes
+            .lock()
+            .ok()?
+            .get(short_id)
+            .map(|(value, _)| value.clone())
+    }
+
+    /// Indicates if sending given value is permitted by the configured vote policy
+    ///
+    /// Sending the same value again within the dedup interval and sending any value
+    /// within the minimum vote interval is not permitted.
+    fn permits(
+        &self,
+        short_id: &ShortId,
+        value: &FeedbackValue,
+        config: &SubscriptionConfig,
+    ) -> bool {
+        let Ok(votes) = self.votes.lock() else {
+            return true;
+        };
+        let Some((last_value, sent_at)) = votes.get(short_id) else {
+            return true;
+        };
+        let elapsed = sent_at.elapsed();
+        let duplicate = config
+            .vote_dedup_interval
+            .is_some_and(|interval| last_value == value && elapsed < interval);
+        let too_early = config
+            .min_vote_interval
+            .is_some_and(|interval| elapsed < interval);
+        !duplicate && !too_early
+    }
+
+    /// Forgets the own vote, e.g. after a server-side reset
+    ///
+    /// Returns `true` if there was an own vote that has been cleared.
+    fn clear(&self, short_id: &ShortId) -> bool {
+        match self.votes.lock() {
+            Ok(mut votes) => votes.remove(short_id).is_some(),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Detects server-side resets of the feedback of a room not announced by a reset message
+///
+/// Feedback without any votes following feedback with votes is considered a reset.
+#[derive(Debug, Default)]
+struct ResetDetector {
+    had_votes: bool,
+}
+
+impl ResetDetector {
+    /// Returns `true` if given feedback indicates a reset
+    fn detect(&mut self, feedback: &Feedback) -> bool {
+        let has_votes = feedback.count_votes() > 0;
+        let reset = self.had_votes && !has_votes;
+        self.had_votes = has_votes;
+        reset
+    }
+
+    /// Notes an announced reset to not detect it again on following feedback without votes
+    fn reset(&mut self) {
+        self.had_votes = false;
+    }
+}
+
+/// The configuration of websocket subscriptions
+#[derive(Clone)]
+pub(crate) struct SubscriptionConfig {
+    pub(crate) coalesce_interval: Option<Duration>,
+    pub(crate) event_queue_capacity: usize,
+    pub(crate) outgoing_queue_capacity: usize,
+    pub(crate) heartbeat_interval: Duration,
+    pub(crate) server_heartbeat: Option<Duration>,
+    pub(crate) max_frame_size: usize,
+    pub(crate) reconnect_backoff: Option<Arc<dyn BackoffPolicy>>,
+    pub(crate) vote_dedup_interval: Option<Duration>,
+    pub(crate) min_vote_interval: Option<Duration>,
+}
+
+impl Default for SubscriptionConfig {
+    fn default() -> Self {
+        SubscriptionConfig {
+            coalesce_interval: None,
+            event_queue_capacity: 64,
+            outgoing_queue_capacity: 32,
+            heartbeat_interval: Duration::from_secs(15),
+            server_heartbeat: None,
+            max_frame_size: 1024 * 1024,
+            reconnect_backoff: None,
+            vote_dedup_interval: None,
+            min_vote_interval: None,
+        }
+    }
+}
+
+/// The state of the websocket subscriptions of a client
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// No subscription is connected
+    #[default]
+    Disconnected,
+    /// All subscriptions are connected
+    Connected,
+    /// At least one subscription lost its connection and is reconnecting
+    Reconnecting,
+}
+
+/// Tracks open websocket connections of a client to report their state and close them on request
+pub(crate) struct Shutdown {
+    closed: watch::Sender<bool>,
+    connections: watch::Sender<usize>,
+    reconnecting: AtomicUsize,
+    state: watch::Sender<ConnectionState>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Shutdown {
+            closed: watch::channel(false).0,
+            connections: watch::channel(0).0,
+            reconnecting: AtomicUsize::new(0),
+            state: watch::channel(ConnectionState::default()).0,
+        }
+    }
+}
+
+impl Shutdown {
+    fn is_closed(&self) -> bool {
+        *self.closed.borrow()
+    }
+
+    /// Updates the connection state using the number of open and reconnecting connections
+    fn update_state(&self) {
+        self.state.send_if_modified(|state| {
+            let next = if self.reconnecting.load(Ordering::SeqCst) > 0 {
+                ConnectionState::Reconnecting
+            } else if *self.connections.borrow() > 0 {
+                ConnectionState::Connected
+            } else {
+                ConnectionState::Disconnected
+            };
+            std::mem::replace(state, next) != next
+        });
+    }
+
+    /// Waits until the client was closed
+    async fn wait_closed(mut closed: watch::Receiver<bool>) {
+        let _ = closed.wait_for(|closed| *closed).await;
+    }
+}
+
+/// Counts an open websocket connection until dropped
+struct ConnectionGuard(Arc<Shutdown>);
+
+impl ConnectionGuard {
+    fn new(shutdown: Arc<Shutdown>) -> ConnectionGuard {
+        shutdown.connections.send_modify(|count| *count += 1);
+        shutdown.update_state();
+        ConnectionGuard(shutdown)
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.connections.send_modify(|count| *count -= 1);
+        self.0.update_state();
+    }
+}
+
+/// Marks a lost connection as reconnecting until dropped
+struct ReconnectGuard<'a>(&'a Shutdown);
+
+impl ReconnectGuard<'_> {
+    fn new(shutdown: &Shutdown) -> ReconnectGuard<'_> {
+        shutdown.reconnecting.fetch_add(1, Ordering::SeqCst);
+        shutdown.update_state();
+        ReconnectGuard(shutdown)
+    }
+}
+
+impl Drop for ReconnectGuard<'_> {
+    fn drop(&mut self) {
+        self.0.reconnecting.fetch_sub(1, Ordering::SeqCst);
+        self.0.update_state();
+    }
+}
+
+/// A queue of outgoing websocket messages written by a dedicated task
+///
+/// This prevents sending messages from stalling the processing of incoming messages.
+/// The task ends when the `WsWriter` is dropped, the connection is closed or the client
+/// was closed. Queued messages are sent before the connection is closed.
+struct WsWriter {
+    tx: Sender<Message>,
+    metrics: Arc<Metrics>,
+}
+
+impl WsWriter {
+    fn spawn(
+        mut write: WsWrite,
+        capacity: usize,
+        metrics: Arc<Metrics>,
+        recorder: Option<Arc<Recorder>>,
+        guard: ConnectionGuard,
+        runtime: &dyn Runtime,
+    ) -> WsWriter {
+        let (tx, mut rx) = channel::<Message>(capacity.max(1));
+        let writer = WsWriter {
+            tx,
+            metrics: metrics.clone(),
+        };
+        runtime.spawn(Box::pin(async move {
+            let closed = guard.0.closed.subscribe();
+            let mut closing = false;
+            loop {
+                let msg = select! {
+                    msg = rx.recv() => msg,
+                    _ = Shutdown::wait_closed(closed.clone()), if !closing => {
+                        // Stop accepting messages but send the queued ones
+                        rx.close();
+                        closing = true;
+                        continue;
+                    }
+                };
+                let Some(msg) = msg else {
+                    break;
+                };
+                trace!(
+                    frame = msg.to_text().unwrap_or_default().lines().next(),
+                    "sending STOMP frame"
+                );
+                if let Some(recorder) = &recorder {
+                    recorder.stomp_sent(msg.to_text().unwrap_or_default());
+                }
+                if write.feed(msg).await.is_err() {
+                    debug!("websocket writer closed");
+                    return;
+                }
+                metrics.increment(Counter::MessagesSent);
+                // Batch all queued messages into a single flush
+                while let Ok(msg) = rx.try_recv() {
+                    trace!(
+                        frame = msg.to_text().unwrap_or_default().lines().next(),
+                        "sending STOMP frame"
+                    );
+                    if let Some(recorder) = &recorder {
+                        recorder.stomp_sent(msg.to_text().unwrap_or_default());
+                    }
+                    if write.feed(msg).await.is_err() {
+                        return;
+                    }
+                    metrics.increment(Counter::MessagesSent);
+                }
+                if write.flush().await.is_err() {
+                    return;
+                }
+            }
+            let _ = write.close().await;
+            drop(guard);
+        }));
+        writer
+    }
+
+    /// Queues given message without waiting and returns whether it was queued
+    ///
+    /// The message is discarded and counted as dropped event if the queue is full.
+    fn send(&self, msg: Message) -> bool {
+        match self.tx.try_send(msg) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                warn!("websocket send queue full, discarding message");
+                self.metrics.increment(Counter::DroppedEvents);
+                false
+            }
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+}
+
+/// An established STOMP connection subscribed to the feedback of one or more rooms
+struct WsConnection {
+    writer: WsWriter,
+    read: WsRead,
+    session: StompSession,
+    subscriptions: HashMap<String, ShortId>,
+    last_received: Instant,
+    last_heartbeat: Instant,
+    metrics: Arc<Metrics>,
+    recorder: Option<Arc<Recorder>>,
+    runtime: Arc<dyn Runtime>,
+    closed: watch::Receiver<bool>,
+}
+
+impl WsConnection {
+    /// Waits for the next MESSAGE frame of a known subscription while sending heart-beats
+    ///
+    /// Returns `None` if the connection or the client was closed, if the server sent an ERROR frame
+    /// or if no message was received within twice the negotiated server heart-beat interval.
+    async fn next_frame(&mut self, config: &SubscriptionConfig) -> Option<String> {
+        let frame = self.receive_frame(config).await;
+        if frame.is_none() {
+            self.session.close();
+        }
+        frame
+    }
+
+    async fn receive_frame(&mut self, config: &SubscriptionConfig) -> Option<String> {
+        loop {
+            while let Some(raw) = self.session.next_frame() {
+                let Some(frame) = StompFrame::parse(&raw) else {
+                    continue;
+                };
+                if !self.session.accept(&frame, &self.metrics) {
+                    continue;
+                }
+                match frame.command {
+                    "ERROR" => return None,
+                    "RECEIPT" => continue,
+                    _ => {}
+                }
+                let subscribed = frame.header("subscription").is_some_and(|subscription_id| {
+                    self.subscriptions.contains_key(subscription_id)
+                });
+                if subscribed {
+                    return Some(raw);
+                }
+                warn!("rejecting STOMP frame of unknown subscription");
+                self.metrics.increment(Counter::RejectedFrames);
+            }
+
+            let heartbeat = (self.last_heartbeat + config.heartbeat_interval)
+                .saturating_duration_since(Instant::now());
+            select! {
+                next = self.read.next() => match next {
+                    Some(Ok(msg)) => {
+                        self.last_received = Instant::now();
+                        self.metrics.increment(Counter::MessagesReceived);
+                        if let Message::Text(text) = msg {
+                            if let Some(recorder) = &self.recorder {
+                                recorder.stomp_received(&text);
+                            }
+                            if !self.session.push(&text, &self.metrics) {
+                                return None;
+                            }
+                        }
+                    }
+                    _ => {
+                        debug!("websocket connection closed");
+                        return None;
+                    }
+                },
+                _ = self.runtime.sleep(heartbeat) => {
+                    trace!("sending heart-beat");
+                    self.writer.send(Message::Text("\n".to_string()));
+                    self.last_heartbeat = Instant::now();
+                }
+                _ = liveness_timeout(self.runtime.as_ref(), self.last_received, self.session.server_heartbeat) => {
+                    warn!("missing server heart-beat, closing websocket connection");
+                    return None;
+                }
+                _ = Shutdown::wait_closed(self.closed.clone()) => {
+                    debug!("client closed, closing websocket connection");
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// Negotiates the interval of server heart-beats using the `heart-beat` header of a CONNECTED frame
+///
+/// As of STOMP 1.2, the larger of the requested interval and the interval offered by the server
+/// is used. Server heart-beats are disabled if either side uses 0 or the header is missing.
+fn negotiate_heartbeat(requested: Option<Duration>, header: Option<&str>) -> Option<Duration> {
+    let requested = requested.filter(|interval| !interval.is_zero())?;
+    let offered = header?.split(',').next()?.trim().parse::<u64>().ok()?;
+    if offered == 0 {
+        return None;
+    }
+    Some(requested.max(Duration::from_millis(offered)))
+}
+
+/// Waits until no message was received within twice the negotiated server heart-beat interval
+///
+/// Waits forever if server heart-beats are disabled.
+async fn liveness_timeout(
+    runtime: &dyn Runtime,
+    last_received: Instant,
+    server_heartbeat: Option<Duration>,
+) {
+    match server_heartbeat {
+        Some(interval) => {
+            runtime
+                .sleep((last_received + interval * 2).saturating_duration_since(Instant::now()))
+                .await
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// The interval used to poll room statistics of a monitoring stream
+const STATS_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The state of a multi-room monitoring stream
+struct MonitorState<'a> {
+    client: &'a Client<LoggedIn>,
+    rooms: Vec<(RoomId, ShortId)>,
+    kinds: EventKinds,
+    topics: Vec<Topic>,
+    connection: WsConnection,
+    last_feedback: HashMap<ShortId, Feedback>,
+    resets: HashMap<ShortId, ResetDetector>,
+    last_stats: HashMap<ShortId, RoomStats>,
+    next_stats_poll: Instant,
+    pending: VecDeque<(ShortId, Envelope<RoomEvent>)>,
+    _subscription: SubscriptionGuard,
+}
+
+impl MonitorState<'_> {
+    async fn next_event(&mut self) -> Option<(ShortId, Envelope<RoomEvent>)> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            let poll_stats = self.kinds.contains(EventKinds::STATS);
+            let stats_due = self
+                .next_stats_poll
+                .saturating_duration_since(Instant::now());
+            select! {
+                next = self.connection.next_frame(&self.client.subscription) => match next {
+                    Some(text) => self.handle_frame(&text),
+                    None => {
+                        self.connection = self.client.resubscribe(&self.topics, &self.rooms).await?
+                    }
+                },
+                _ = self.client.runtime.sleep(stats_due), if poll_stats => self.poll_stats().await,
+            }
+        }
+    }
+
+    /// Requests statistics of all rooms and queues those which have changed
+    async fn poll_stats(&mut self) {
+        self.next_stats_poll = Instant::now() + STATS_POLL_INTERVAL;
+        for (room_id, short_id) in self.rooms.clone() {
+            let Ok(stats) = self.client.get_room_stats_by_room_id(&room_id).await else {
+                continue;
+            };
+            if self.last_stats.get(&short_id) != Some(&stats) {
+                self.last_stats.insert(short_id.clone(), stats.clone());
+                self.push_event(short_id, Envelope::new(RoomEvent::Stats(stats), None));
+            }
+        }
+    }
+
+    fn handle_frame(&mut self, text: &str) {
+        let frame = match StompFrame::parse(text) {
+            Some(frame) => frame,
+            None => return,
+        };
+        let short_id = match frame
+            .header("subscription")
+            .and_then(|subscription_id| self.connection.subscriptions.get(subscription_id))
+        {
+            Some(short_id) => short_id.clone(),
+            None => return,
+        };
+        let body = match WsMessageBody::from_frame(&frame, &self.client.metrics) {
+            Some(body) if self.kinds.intersects(body.kind()) => body,
+            _ => return,
+        };
+        if body.kind() == EventKinds::FEEDBACK {
+            let message = body.into_feedback_message(frame.server_time());
+            self.handle_feedback_message(short_id, message);
+        } else if let Some(event) = body.into_room_event() {
+            self.push_event(short_id, Envelope::new(event, frame.server_time()));
+        }
+    }
+
+    fn handle_feedback_message(&mut self, short_id: ShortId, message: Option<FeedbackMessage>) {
+        match message {
+            Some(FeedbackMessage::Changed(envelope)) => {
+                let reset = self
+                    .resets
+                    .entry(short_id.clone())
+                    .or_default()
+                    .detect(&envelope.event)
+                    .then(|| {
+                        self.client
+                            .reset_events(&short_id, envelope.clone().map(|_| ()))
+                    });
+                let delta = self
+                    .last_feedback
+                    .insert(short_id.clone(), envelope.event.clone())
+                    .map(|previous| envelope.event.delta(&previous));
+                let delta = delta
+                    .filter(|delta| delta.has_changes())
+                    .map(|delta| envelope.clone().map(|_| FeedbackEvent::Delta(delta)));
+                self.push_feedback_event(short_id.clone(), envelope.map(FeedbackEvent::Changed));
+                if let Some(delta) = delta {
+                    self.push_feedback_event(short_id.clone(), delta);
+                }
+                for event in reset.into_iter().flatten() {
+                    self.push_feedback_event(short_id.clone(), event);
+                }
+            }
+            Some(FeedbackMessage::Reset(envelope)) => {
+                self.resets.entry(short_id.clone()).or_default().reset();
+                for event in self.client.reset_events(&short_id, envelope) {
+                    self.push_feedback_event(short_id.clone(), event);
+                }
+            }
+            Some(FeedbackMessage::Status(envelope)) => {
+                self.client.invalidate_room_info(&short_id);
+                self.push_feedback_event(short_id, envelope);
+            }
+            None => {}
+        }
+    }
+
+    fn push_feedback_event(&mut self, short_id: ShortId, event: Envelope<FeedbackEvent>) {
+        self.push_event(short_id, event.map(RoomEvent::Feedback));
+    }
+
+    /// Queues given event, dropping the oldest queued event if capacity is reached
+    fn push_event(&mut self, short_id: ShortId, event: Envelope<RoomEvent>) {
+        while self.pending.len() >= self.client.subscription.event_queue_capacity.max(1) {
+            self.pending.pop_front();
+            self.client.metrics.increment(Counter::DroppedEvents);
+        }
+        self.pending.push_back((short_id, event));
+    }
+}
+
+#[allow(dead_code)]
+pub enum FeedbackHandler {
+    /// Handle incoming `Feedback` using a fn
+    Fn(fn(&Feedback)),
+    /// Handle incoming `Feedback` by sending it to a `Sender<Feedback>`
+    Sender(Sender<Feedback>),
+    /// Bidirectional handler for incoming `Feedback` and outgoing `FeedbackValue`
+    SenderReceiver(Sender<Feedback>, Receiver<FeedbackValue>),
+    /// Handle incoming `Feedback` and its `FeedbackDelta` by sending it to a `Sender<Envelope<FeedbackEvent>>`
+    EventSender(Sender<Envelope<FeedbackEvent>>),
+}
+
+impl<State> Client<State> {
+    /// Returns the runtime used to spawn background tasks
+    pub(crate) fn runtime(&self) -> Arc<dyn Runtime> {
+        self.runtime.clone()
+    }
+
+    /// Returns the number of subscription events dropped since the client was created
+    ///
+    /// Events are dropped if a subscription channel or the event queue of a monitoring
+    /// stream is full because the consumer does not keep up, or if outgoing messages
+    /// cannot be queued because the websocket send queue is full.
+    pub fn dropped_events(&self) -> u64 {
+        self.metrics.dropped_events()
+    }
+
+    /// Fails with `ConnectionError` if the legacy API is used, which provides no websocket
+    fn check_websocket_support(&self) -> Result<(), ClientError> {
+        #[cfg(feature = "legacy")]
+        if self.is_legacy() {
+            warn!("websocket subscriptions are not supported by the legacy API");
+            return Err(ConnectionError);
+        }
+        Ok(())
+    }
+
+    async fn connect_ws(&self) -> Result<(WsWrite, WsRead), ClientError> {
+        if self.shutdown.is_closed() {
+            return Err(ConnectionError);
+        }
+        let mut ws_url = self.service_endpoint(Service::Websocket, "websocket")?;
+        let scheme = if ws_url.scheme() == "https" {
+            "wss"
+        } else {
+            "ws"
+        };
+        ws_url.set_scheme(scheme).map_err(|_| UrlError)?;
+        let mut request = ws_url
+            .as_str()
+            .into_client_request()
+            .map_err(|_| UrlError)?;
+        request.headers_mut().extend(self.default_headers.clone());
+        if let Some(cookies) = &self.cookies {
+            cookies.apply(request.headers_mut());
+        }
+        self.middlewares
+            .iter()
+            .for_each(|middleware| middleware(request.headers_mut()));
+        debug!(url = %ws_url, "connecting websocket");
+        let connection = match self.ws_transport.connect(request).await {
+            Ok(connection) => connection,
+            Err(err) => {
+                warn!(url = %ws_url, "cannot connect websocket");
+                return Err(err);
+            }
+        };
+        debug!(url = %ws_url, "websocket connected");
+        Ok(connection)
+    }
+
+    /// Measures the time until the server confirms a DISCONNECT frame using a receipt
+    pub(crate) async fn stomp_latency(&self, token: &str) -> Result<Duration, ClientError> {
+        let (mut write, mut read) = self.connect_ws().await?;
+        let mut session = self.stomp_connect(&mut write, &mut read, token).await?;
+
+        let start = Instant::now();
+        self.send_frame(&mut write, WsDisconnectMessage::new("ping").to_string())
+            .await?;
+        self.wait_for_frame(&mut read, &mut session, "RECEIPT")
+            .await?;
+        let latency = start.elapsed();
+        self.metrics.timing(Timing::StompReceipt, latency);
+        Ok(latency)
+    }
+
+    /// Sends a CONNECT frame and waits up to 10 seconds for the server to accept the STOMP session
+    ///
+    /// Frames received along with the CONNECTED frame are kept in the resulting session.
+    /// This method fails if the server rejects the session, sends any other frame first
+    /// or accepts an unsupported protocol version.
+    async fn stomp_connect(
+        &self,
+        write: &mut WsWrite,
+        read: &mut WsRead,
+        token: &str,
+    ) -> Result<StompSession, ClientError> {
+        self.send_frame(
+            write,
+            WsConnectMessage::new(
+                token,
+                self.subscription.heartbeat_interval,
+                self.subscription.server_heartbeat,
+            )
+            .to_string(),
+        )
+        .await?;
+
+        let mut session = StompSession::new(self.subscription.max_frame_size);
+        let handshake = async {
+            loop {
+                while let Some(raw) = session.next_frame() {
+                    let Some(frame) = StompFrame::parse(&raw) else {
+                        continue;
+                    };
+                    if !session.accept(&frame, &self.metrics) || frame.command == "ERROR" {
+                        return Err(ConnectionError);
+                    }
+                    let version = frame.header("version");
+                    if version.is_some_and(|version| !STOMP_VERSIONS.contains(&version)) {
+                        warn!(version, "unsupported STOMP version");
+                        return Err(ConnectionError);
+                    }
+                    session.server_heartbeat = negotiate_heartbeat(
+                        self.subscription.server_heartbeat,
+                        frame.header("heart-beat"),
+                    );
+                    return session.transition(StompState::Connected);
+                }
+                self.receive_into(read, &mut session).await?;
+            }
+        };
+        select! {
+            result = handshake => result?,
+            _ = self.runtime.sleep(Duration::from_secs(10)) => {
+                warn!("no CONNECTED frame received");
+                return Err(ConnectionError);
+            }
+        }
+        Ok(session)
+    }
+
+    /// Waits up to 10 seconds for the next STOMP frame with given command, ignoring all other frames
+    ///
+    /// Frames are read through the session, so frames batched into one websocket message are
+    /// not lost. This method fails if the server sends an ERROR frame or closes the connection.
+    async fn wait_for_frame(
+        &self,
+        read: &mut WsRead,
+        session: &mut StompSession,
+        command: &str,
+    ) -> Result<(), ClientError> {
+        let wait = async {
+            loop {
+                while let Some(raw) = session.next_frame() {
+                    let Some(frame) = StompFrame::parse(&raw) else {
+                        continue;
+                    };
+                    if !session.accept(&frame, &self.metrics) {
+                        continue;
+                    }
+                    if frame.command == "ERROR" {
+                        return Err(ConnectionError);
+                    }
+                    if frame.command == command {
+                        return Ok(());
+                    }
+                }
+                self.receive_into(read, session).await?;
+            }
+        };
+        select! {
+            result = wait => result,
+            _ = self.runtime.sleep(Duration::from_secs(10)) => Err(ConnectionError),
+        }
+    }
+
+    /// Reads the next websocket message and appends its text to given session
+    async fn receive_into(
+        &self,
+        read: &mut WsRead,
+        session: &mut StompSession,
+    ) -> Result<(), ClientError> {
+        match read.next().await {
+            Some(Ok(Message::Text(text))) => {
+                self.metrics.increment(Counter::MessagesReceived);
+                if let Some(recorder) = &self.recorder {
+                    recorder.stomp_received(&text);
+                }
+                if !session.push(&text, &self.metrics) {
+                    return Err(ConnectionError);
+                }
+                Ok(())
+            }
+            Some(Ok(_)) => Ok(()),
+            _ => Err(ConnectionError),
+        }
+    }
+
+    /// Sends given STOMP frame without queueing, used to set up the connection
+    async fn send_frame(&self, write: &mut WsWrite, frame: String) -> Result<(), ClientError> {
+        if let Some(recorder) = &self.recorder {
+            recorder.stomp_sent(&frame);
+        }
+        self.metrics.increment(Counter::MessagesSent);
+        write
+            .send(Message::Text(frame))
+            .await
+            .map_err(|_| ConnectionError)
+    }
+}
+
+impl Client<LoggedIn> {
+    /// Connects the websocket and subscribes to the feedback of given rooms
+    async fn subscribe_feedback(
+        &self,
+        rooms: &[(RoomId, ShortId)],
+    ) -> Result<WsConnection, ClientError> {
+        self.subscribe(&[Topic::Feedback], rooms).await
+    }
+
+    /// Re-establishes the feedback subscription of given rooms after the connection was lost
+    ///
+    /// Returns `None` if reconnecting is disabled, the backoff policy gave up or the client
+    /// was closed, otherwise retries until reconnected.
+    async fn resubscribe_feedback(&self, rooms: &[(RoomId, ShortId)]) -> Option<WsConnection> {
+        self.resubscribe(&[Topic::Feedback], rooms).await
+    }
+
+    /// Connects the websocket and subscribes to given topics of given rooms
+    async fn subscribe(
+        &self,
+        topics: &[Topic],
+        rooms: &[(RoomId, ShortId)],
+    ) -> Result<WsConnection, ClientError> {
+        let (mut write, mut read) = self.connect_ws().await?;
+        let mut session = self
+            .stomp_connect(&mut write, &mut read, self.token.as_ref().unwrap())
+            .await?;
+
+        let mut subscriptions = HashMap::new();
+        for topic in topics {
+            for (room_id, short_id) in rooms {
+                let subscription_id = format!("sub-{}", subscriptions.len());
+                self.send_frame(
+                    &mut write,
+                    topic.subscribe_message(room_id, &subscription_id),
+                )
+                .await?;
+                subscriptions.insert(subscription_id, short_id.clone());
+            }
+        }
+        session.transition(StompState::Subscribed)?;
+
+        Ok(WsConnection {
+            writer: WsWriter::spawn(
+                write,
+                self.subscription.outgoing_queue_capacity,
+                self.metrics.clone(),
+                self.recorder.clone(),
+                ConnectionGuard::new(self.shutdown.clone()),
+                self.runtime.as_ref(),
+            ),
+            read,
+            session,
+            subscriptions,
+            last_received: Instant::now(),
+            last_heartbeat: Instant::now(),
+            metrics: self.metrics.clone(),
+            recorder: self.recorder.clone(),
+            runtime: self.runtime.clone(),
+            closed: self.shutdown.closed.subscribe(),
+        })
+    }
+
+    /// Re-establishes the subscription of given topics and rooms after the connection was lost
+    ///
+    /// Returns `None` if reconnecting is disabled, the backoff policy gave up or the client
+    /// was closed, otherwise retries until reconnected.
+    async fn resubscribe(
+        &self,
+        topics: &[Topic],
+        rooms: &[(RoomId, ShortId)],
+    ) -> Option<WsConnection> {
+        let backoff = self.subscription.reconnect_backoff.clone()?;
+        let _reconnecting = ReconnectGuard::new(&self.shutdown);
+        let mut attempt = 0;
+        while let Some(delay) = backoff.delay(attempt + 1) {
+            attempt += 1;
+            select! {
+                _ = self.runtime.sleep(delay) => {},
+                _ = Shutdown::wait_closed(self.shutdown.closed.subscribe()) => return None,
+            }
+            warn!(attempt, "reconnecting websocket");
+            self.metrics.increment(Counter::Reconnects);
+            if let Ok(connection) = self.subscribe(topics, rooms).await {
+                return Some(connection);
+            }
+        }
+        warn!("giving up reconnecting websocket");
+        None
+    }
+
+    /// Returns `Ok` if the client was closed, otherwise a `ConnectionError`
+    ///
+    /// This is used to end subscriptions whose connection was lost and not re-established.
+    fn closed_or_error(&self) -> Result<(), ClientError> {
+        match self.shutdown.is_closed() {
+            true => Ok(()),
+            false => Err(ConnectionError),
+        }
+    }
+
+    /// Returns a receiver of the state of all websocket subscriptions of this client
+    ///
+    /// Use this to show if live updates are received, e.g. while reconnecting after the
+    /// connection was lost. Short-lived connections used to send single frames are not included.
+    pub fn connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.shutdown.state.subscribe()
+    }
+
+    /// Closes all websocket connections of this client
+    ///
+    /// Queued outgoing messages are sent before each connection is closed. Running
+    /// subscriptions end successfully, monitoring streams end and no new connections are
+    /// established. Resolves once all connections are closed or after at most 10 seconds.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn close(&self) {
+        self.shutdown.closed.send_replace(true);
+        let mut connections = self.shutdown.connections.subscribe();
+        select! {
+            _ = connections.wait_for(|count| *count == 0) => {
+                debug!("all websocket connections closed");
+            }
+            _ = self.runtime.sleep(Duration::from_secs(10)) => {
+                warn!("timeout closing websocket connections");
+            }
+        }
+    }
+
+    /// Closes all websocket connections like `close()` and logs out the client
+    pub async fn close_and_logout(self) -> Client<LoggedOut> {
+        self.close().await;
+        self.logout()
+    }
+
+    /// Sends a single feedback value for given 8-digit room ID
+    ///
+    /// A short-lived websocket connection is used, which is closed after the server
+    /// confirmed the feedback. Use `register_feedback_receiver()` to send multiple values.
+    ///
+    /// This method fails on connection or response errors, if
+    /// no room is available with given room ID and with `VoteSuppressedError`
+    /// if the vote is suppressed by the configured vote policy.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn send_feedback(
+        &self,
+        short_id: &ShortId,
+        value: FeedbackValue,
+    ) -> Result<(), ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+        let user_id = self.get_user_id()?;
+
+        if !self.permits_vote(short_id, &value) {
+            return Err(VoteSuppressedError);
+        }
+        #[cfg(feature = "legacy")]
+        if self.is_legacy() {
+            self.send_legacy_feedback(short_id, value.clone()).await?;
+            self.own_votes.record(short_id, value);
+            return Ok(());
+        }
+        self.send_command(
+            WsCreateFeedbackMessage::new(&room_info.id, &user_id, value.clone()).to_string(),
+        )
+        .await?;
+        self.own_votes.record(short_id, value);
+        Ok(())
+    }
+
+    /// Returns the feedback value last sent by this client for given 8-digit room ID
+    ///
+    /// The value is cleared if a subscription receives a server-side reset of the feedback.
+    pub fn my_feedback(&self, short_id: &ShortId) -> Option<FeedbackValue> {
+        self.own_votes.get(short_id)
+    }
+
+    /// Indicates if sending given value is permitted by the configured vote policy, counting suppressed votes
+    fn permits_vote(&self, short_id: &ShortId, value: &FeedbackValue) -> bool {
+        let permitted = self.own_votes.permits(short_id, value, &self.subscription);
+        if !permitted {
+            self.metrics.increment(Counter::SuppressedVotes);
+        }
+        permitted
+    }
+
+    /// Clears the own vote after a reset of the feedback of given room and returns the events to emit
+    fn reset_events(&self, short_id: &ShortId, at: Envelope<()>) -> Vec<Envelope<FeedbackEvent>> {
+        let mut events = vec![at.clone().map(|_| FeedbackEvent::Reset)];
+        if self.own_votes.clear(short_id) {
+            events.push(at.map(|_| FeedbackEvent::VoteCleared));
+        }
+        events
+    }
+
+    /// Resets the live feedback of given 8-digit room ID, e.g. between lecture segments
+    ///
+    /// This requires the logged in user to be an owner or moderator of the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID,
+    /// or if the legacy API is used.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn reset_feedback(&self, short_id: &ShortId) -> Result<(), ClientError> {
+        self.check_websocket_support()?;
+        let room_info = self.get_room_info(short_id).await?;
+
+        self.send_command(
+            WsFeedbackCommandMessage::new(FeedbackCommand::Reset, &room_info.id).to_string(),
+        )
+        .await
+    }
+
+    /// Starts collecting live feedback in given 8-digit room ID by unlocking the feedback feature
+    ///
+    /// This requires the logged in user to be an owner or moderator of the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID,
+    /// or if the legacy API is used.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn start_feedback(&self, short_id: &ShortId) -> Result<(), ClientError> {
+        self.check_websocket_support()?;
+        self.send_feedback_command(short_id, FeedbackCommand::Start)
+            .await
+    }
+
+    /// Stops collecting live feedback in given 8-digit room ID by locking the feedback feature
+    ///
+    /// This requires the logged in user to be an owner or moderator of the room.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID,
+    /// or if the legacy API is used.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn stop_feedback(&self, short_id: &ShortId) -> Result<(), ClientError> {
+        self.check_websocket_support()?;
+        self.send_feedback_command(short_id, FeedbackCommand::Stop)
+            .await
+    }
+
+    /// Sends given feedback command and invalidates the cached `RoomInfo` containing the lock state
+    async fn send_feedback_command(
+        &self,
+        short_id: &ShortId,
+        command: FeedbackCommand,
+    ) -> Result<(), ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+        let result = self
+            .send_command(WsFeedbackCommandMessage::new(command, &room_info.id).to_string())
+            .await;
+        self.invalidate_room_info(short_id);
+        result
+    }
+
+    /// Sends a single command frame using a short-lived websocket connection
+    ///
+    /// The connection is closed after the server confirmed the command.
+    async fn send_command(&self, command: String) -> Result<(), ClientError> {
+        let (mut write, mut read) = self.connect_ws().await?;
+        let mut session = self
+            .stomp_connect(&mut write, &mut read, self.token.as_ref().unwrap())
+            .await?;
+
+        self.send_frame(&mut write, command).await?;
+        self.send_frame(&mut write, WsDisconnectMessage::new("feedback").to_string())
+            .await?;
+        self.wait_for_frame(&mut read, &mut session, "RECEIPT")
+            .await
+    }
+
+    /// Subscribes to comment changes of given 8-digit room ID and sends them to given channel
+    ///
+    /// Events not fitting into a full channel are dropped. The subscription ends
+    /// when the channel is closed.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID,
+    /// or if the legacy API is used.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, sender))
+    )]
+    pub async fn on_comments_changed(
+        &self,
+        short_id: &ShortId,
+        sender: Sender<Envelope<CommentEvent>>,
+    ) -> Result<(), ClientError> {
+        self.check_websocket_support()?;
+        self.forward_events(Topic::Comments, short_id, sender, parse_comment_event)
+            .await
+    }
+
+    /// Subscribes to content state changes of given 8-digit room ID and sends them to given channel
+    ///
+    /// This is used to follow the contents published by the presenter, e.g. to answer a quiz.
+    /// Events not fitting into a full channel are dropped. The subscription ends
+    /// when the channel is closed.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID,
+    /// or if the legacy API is used.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, sender))
+    )]
+    pub async fn on_content_changed(
+        &self,
+        short_id: &ShortId,
+        sender: Sender<Envelope<ContentEvent>>,
+    ) -> Result<(), ClientError> {
+        self.check_websocket_support()?;
+        self.forward_events(Topic::Room, short_id, sender, parse_content_event)
+            .await
+    }
+
+    /// Subscribes to announcements of given 8-digit room ID and sends them to given channel
+    ///
+    /// This is used to notify participants about new announcements immediately.
+    /// Events not fitting into a full channel are dropped. The subscription ends
+    /// when the channel is closed.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID,
+    /// or if the legacy API is used.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, sender))
+    )]
+    pub async fn on_announcement_created(
+        &self,
+        short_id: &ShortId,
+        sender: Sender<Envelope<AnnouncementEvent>>,
+    ) -> Result<(), ClientError> {
+        self.check_websocket_support()?;
+        self.forward_events(Topic::Room, short_id, sender, parse_announcement_event)
+            .await
+    }
+
+    /// Subscribes to presenter focus changes of given 8-digit room ID and sends them to given channel
+    ///
+    /// This is used to follow what the presenter currently shows, e.g. a content or the comments.
+    /// Events not fitting into a full channel are dropped. The subscription ends
+    /// when the channel is closed.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID,
+    /// or if the legacy API is used.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, sender))
+    )]
+    pub async fn on_focus_changed(
+        &self,
+        short_id: &ShortId,
+        sender: Sender<Envelope<FocusEvent>>,
+    ) -> Result<(), ClientError> {
+        self.check_websocket_support()?;
+        self.forward_events(Topic::Focus, short_id, sender, parse_focus_event)
+            .await
+    }
+
+    /// Sends the number of users in given 8-digit room ID to given channel whenever it changes
+    ///
+    /// The server does not push user count changes, so the room statistics are polled using
+    /// given interval of at least one second. The current number of users is sent first.
+    /// Failed polls are retried after the interval. The subscription ends when the channel is closed.
+    ///
+    /// This method fails on connection or response errors of the first request and if
+    /// no room is available with given room ID.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, sender))
+    )]
+    pub async fn on_user_count_changed(
+        &self,
+        short_id: &ShortId,
+        interval: Duration,
+        sender: Sender<Envelope<usize>>,
+    ) -> Result<(), ClientError> {
+        let interval = interval.max(Duration::from_secs(1));
+        let mut last_count = None;
+
+        loop {
+            match self.get_room_stats(short_id).await {
+                Ok(stats) if last_count != Some(stats.room_user_count) => {
+                    last_count = Some(stats.room_user_count);
+                    try_send(
+                        &sender,
+                        Envelope::new(stats.room_user_count, None),
+                        &self.metrics,
+                    );
+                }
+                Ok(_) => {}
+                Err(err) if last_count.is_none() => return Err(err),
+                Err(_) => {}
+            }
+            select! {
+                _ = self.runtime.sleep(interval) => {},
+                _ = sender.closed() => return Ok(()),
+                _ = Shutdown::wait_closed(self.shutdown.closed.subscribe()) => return Ok(()),
+            }
+        }
+    }
+
+    /// Subscribes to given topic of given room and sends parsed events to given channel until it is closed
+    async fn forward_events<T>(
+        &self,
+        topic: Topic,
+        short_id: &ShortId,
+        sender: Sender<Envelope<T>>,
+        parse: fn(&str, &Metrics) -> Option<Envelope<T>>,
+    ) -> Result<(), ClientError> {
+        let room_info = self.get_room_info(short_id).await?;
+        let rooms = [(room_info.id.clone(), short_id.clone())];
+
+        let mut connection = self.subscribe(&[topic], &rooms).await?;
+        let _subscription = self.metrics.subscription();
+
+        loop {
+            select! {
+                next = connection.next_frame(&self.subscription) => match next {
+                    Some(text) => {
+                        if let Some(envelope) = parse(&text, &self.metrics) {
+                            try_send(&sender, envelope, &self.metrics);
+                        }
+                    }
+                    None => match self.resubscribe(&[topic], &rooms).await {
+                        Some(reconnected) => connection = reconnected,
+                        None => return self.closed_or_error(),
+                    }
+                },
+                _ = sender.closed() => return Ok(()),
+            }
+        }
+    }
+
+    /// Register feedback channel receiver and send incoming feedback to service
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID,
+    /// or if the legacy API is used.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, receiver))
+    )]
+    pub async fn register_feedback_receiver(
+        &self,
+        short_id: &ShortId,
+        mut receiver: Receiver<FeedbackValue>,
+    ) -> Result<(), ClientError> {
+        self.check_websocket_support()?;
+        let room_info = self.get_room_info(short_id).await?;
+        let rooms = [(room_info.id.clone(), short_id.clone())];
+
+        let mut connection = self.subscribe_feedback(&rooms).await?;
+        let _subscription = self.metrics.subscription();
+        let user_id = self.get_user_id().unwrap_or_default();
+
+        loop {
+            select!(
+                Some(value) = receiver.recv() => {
+                    if !self.permits_vote(short_id, &value) {
+                        continue;
+                    }
+                    let msg = WsCreateFeedbackMessage::new(&room_info.id, &user_id, value.clone()).to_string();
+                    if connection.writer.send(Message::Text(msg)) {
+                        self.own_votes.record(short_id, value);
+                    }
+                },
+                next = connection.next_frame(&self.subscription) => {
+                    if next.is_none() {
+                        match self.resubscribe_feedback(&rooms).await {
+                            Some(reconnected) => connection = reconnected,
+                            None => return self.closed_or_error(),
+                        }
+                    }
+                }
+            )
+        }
+    }
+
+    /// Monitors all given rooms using a single websocket connection
+    ///
+    /// All rooms are joined and their feedback and comment events are multiplexed into the
+    /// resulting stream, each event tagged with the 8-digit room ID it belongs to.
+    /// The stream ends if the websocket connection is closed and reconnecting is disabled.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with any of the given room IDs,
+    /// or if the legacy API is used.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn monitor_rooms(
+        &self,
+        short_ids: &[ShortId],
+    ) -> Result<impl Stream<Item = (ShortId, Envelope<RoomEvent>)> + '_, ClientError> {
+        self.check_websocket_support()?;
+        // Keep the order of given rooms to use the same subscription IDs on every connection
+        let mut rooms: Vec<(RoomId, ShortId)> = vec![];
+        for short_id in short_ids {
+            let room_info = self.get_room_info(short_id).await?;
+            if !rooms.iter().any(|(room_id, _)| *room_id == room_info.id) {
+                rooms.push((room_info.id, short_id.clone()));
+            }
+        }
+
+        self.monitor(rooms, EventKinds::FEEDBACK | EventKinds::COMMENTS)
+            .await
+    }
+
+    /// Subscribes to given kinds of events of given 8-digit room ID using a single websocket connection
+    ///
+    /// All events are merged into the resulting stream of `RoomEvent`s. Room statistics are not
+    /// pushed by the server, so they are polled every 10 seconds and sent first and whenever
+    /// they change. The stream ends if the websocket connection is closed and reconnecting is disabled.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID,
+    /// or if the legacy API is used.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn subscribe_room_events(
+        &self,
+        short_id: &ShortId,
+        kinds: EventKinds,
+    ) -> Result<impl Stream<Item = Envelope<RoomEvent>> + '_, ClientError> {
+        self.check_websocket_support()?;
+        let room_info = self.get_room_info(short_id).await?;
+        let rooms = vec![(room_info.id, short_id.clone())];
+
+        Ok(self.monitor(rooms, kinds).await?.map(|(_, event)| event))
+    }
+
+    /// Subscribes to given kinds of events of given rooms and multiplexes them into a stream
+    async fn monitor(
+        &self,
+        rooms: Vec<(RoomId, ShortId)>,
+        kinds: EventKinds,
+    ) -> Result<impl Stream<Item = (ShortId, Envelope<RoomEvent>)> + '_, ClientError> {
+        let topics = Topic::for_kinds(kinds);
+        let state = MonitorState {
+            client: self,
+            connection: self.subscribe(&topics, &rooms).await?,
+            rooms,
+            kinds,
+            topics,
+            last_feedback: HashMap::new(),
+            resets: HashMap::new(),
+            last_stats: HashMap::new(),
+            next_stats_poll: Instant::now(),
+            pending: VecDeque::new(),
+            _subscription: self.metrics.subscription(),
+        };
+
+        Ok(futures_util::stream::unfold(
+            state,
+            |mut state| async move { state.next_event().await.map(|event| (event, state)) },
+        ))
+    }
+
+    /// Registers a handler to get notifications on feedback change.
+    ///
+    /// This is done by using websocket connections to ARSnova.
+    /// The current feedback of the room is handled first, followed by all changes.
+    /// If configured, rapid feedback changes are coalesced and only the latest
+    /// feedback is handled at most once per configured interval.
+    ///
+    /// This method fails on connection or response errors and if
+    /// no room is available with given room ID,
+    /// or if the legacy API is used.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, handler))
+    )]
+    pub async fn on_feedback_changed(
+        &self,
+        short_id: &ShortId,
+        handler: FeedbackHandler,
+    ) -> Result<(), ClientError> {
+        self.check_websocket_support()?;
+        let room_info = self.get_room_info(short_id).await?;
+        let rooms = [(room_info.id.clone(), short_id.clone())];
+
+        let mut connection = self.subscribe_feedback(&rooms).await?;
+
+        let (handler, mut receiver) = match handler {
+            FeedbackHandler::SenderReceiver(tx, rx) => (FeedbackHandler::Sender(tx), Some(rx)),
+            handler => (handler, None),
+        };
+
+        let _subscription = self.metrics.subscription();
+        let user_id = self.get_user_id().unwrap_or_default();
+        let mut coalescer = FeedbackCoalescer::new(self.subscription.coalesce_interval);
+        let mut last_feedback = None;
+        let mut resets = ResetDetector::default();
+
+        // Request initial feedback after subscribing to not miss changes in between
+        let feedback = self.get_feedback_by_room_id(&room_info.id).await?;
+        resets.detect(&feedback);
+        handle_feedback(
+            &handler,
+            Envelope::new(feedback, None),
+            &mut last_feedback,
+            &self.metrics,
+        );
+
+        loop {
+            select! {
+                next = connection.next_frame(&self.subscription) => match next {
+                    Some(text) => match parse_feedback(&text, &self.metrics) {
+                        Some(FeedbackMessage::Changed(envelope)) => {
+                            let reset = resets
+                                .detect(&envelope.event)
+                                .then(|| self.reset_events(short_id, envelope.clone().map(|_| ())));
+                            if let Some(envelope) = coalescer.push(envelope) {
+                                handle_feedback(&handler, envelope, &mut last_feedback, &self.metrics);
+                            }
+                            for event in reset.into_iter().flatten() {
+                                handle_status(&handler, event, &self.metrics);
+                            }
+                        }
+                        Some(FeedbackMessage::Reset(envelope)) => {
+                            resets.reset();
+                            for event in self.reset_events(short_id, envelope) {
+                                handle_status(&handler, event, &self.metrics);
+                            }
+                        }
+                        Some(FeedbackMessage::Status(envelope)) => {
+                            self.invalidate_room_info(short_id);
+                            handle_status(&handler, envelope, &self.metrics);
+                        }
+                        None => {}
+                    },
+                    None => match self.resubscribe_feedback(&rooms).await {
+                        Some(reconnected) => connection = reconnected,
+                        None => return self.closed_or_error(),
+                    }
+                },
+                _ = coalescer.wait(self.runtime.as_ref()), if coalescer.has_pending() => {
+                    if let Some(envelope) = coalescer.take() {
+                        handle_feedback(&handler, envelope, &mut last_feedback, &self.metrics);
+                    }
+                }
+                Some(value) = recv_feedback_value(&mut receiver) => {
+                    if !self.permits_vote(short_id, &value) {
+                        continue;
+                    }
+                    let msg = WsCreateFeedbackMessage::new(&room_info.id, &user_id, value.clone()).to_string();
+                    if connection.writer.send(Message::Text(msg)) {
+                        self.own_votes.record(short_id, value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A received message of a feedback subscription
+enum FeedbackMessage {
+    /// Changed feedback values
+    Changed(Envelope<Feedback>),
+    /// Feedback collection was started or stopped or the feedback mode was changed
+    Status(Envelope<FeedbackEvent>),
+    /// The feedback was reset by the server
+    Reset(Envelope<()>),
+}
+
+/// Parses a received feedback message and the time it was received at
+fn parse_feedback(text: &str, metrics: &Metrics) -> Option<FeedbackMessage> {
+    feedback_message(&StompFrame::parse(text)?, metrics)
+}
+
+/// Converts the body of a feedback STOMP frame into a `FeedbackMessage`
+fn feedback_message(frame: &StompFrame, metrics: &Metrics) -> Option<FeedbackMessage> {
+    WsMessageBody::from_frame(frame, metrics)?.into_feedback_message(frame.server_time())
+}
+
+/// Parses a received comment message into a `CommentEvent` and the time it was received at
+fn parse_comment_event(text: &str, metrics: &Metrics) -> Option<Envelope<CommentEvent>> {
+    let frame = StompFrame::parse(text)?;
+    let event = WsMessageBody::from_frame(&frame, metrics)?.into_comment_event()?;
+    Some(Envelope::new(event, frame.server_time()))
+}
+
+/// Parses a received room message into a `ContentEvent` and the time it was received at
+fn parse_content_event(text: &str, metrics: &Metrics) -> Option<Envelope<ContentEvent>> {
+    let frame = StompFrame::parse(text)?;
+    let event = WsMessageBody::from_frame(&frame, metrics)?.into_content_event()?;
+    Some(Envelope::new(event, frame.server_time()))
+}
+
+/// Parses a received room message into an `AnnouncementEvent` and the time it was received at
+fn parse_announcement_event(text: &str, metrics: &Metrics) -> Option<Envelope<AnnouncementEvent>> {
+    let frame = StompFrame::parse(text)?;
+    let event = WsMessageBody::from_frame(&frame, metrics)?.into_announcement_event()?;
+    Some(Envelope::new(event, frame.server_time()))
+}
+
+/// Parses a received focus message into a `FocusEvent` and the time it was received at
+fn parse_focus_event(text: &str, metrics: &Metrics) -> Option<Envelope<FocusEvent>> {
+    let frame = StompFrame::parse(text)?;
+    let event = focus_event(parse_body::<RoomFocus>(&frame, metrics)?)?;
+    Some(Envelope::new(event, frame.server_time()))
+}
+
+/// Converts a `RoomFocus` into a `FocusEvent`, unknown features and contents without ID are ignored
+fn focus_event(focus: RoomFocus) -> Option<FocusEvent> {
+    match focus.feature {
+        FocusFeature::Contents => focus.focus_state.content_id.map(FocusEvent::Content),
+        FocusFeature::Comments => Some(FocusEvent::Comments),
+        FocusFeature::Feedback => Some(FocusEvent::Feedback),
+        FocusFeature::Unknown => None,
+    }
+}
+
+/// Receives next outgoing `FeedbackValue` if there is a receiver, otherwise waits forever
+async fn recv_feedback_value(
+    receiver: &mut Option<Receiver<FeedbackValue>>,
+) -> Option<FeedbackValue> {
+    match receiver {
+        Some(receiver) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Passes incoming `Feedback` to given handler
+///
+/// The last handled feedback is used to emit `FeedbackDelta` events.
+/// Items not fitting into a full channel are dropped and counted to not stall the subscription.
+fn handle_feedback(
+    handler: &FeedbackHandler,
+    envelope: Envelope<Feedback>,
+    last_feedback: &mut Option<Feedback>,
+    metrics: &Metrics,
+) {
+    match handler {
+        FeedbackHandler::Fn(f) => f(&envelope.event),
+        FeedbackHandler::Sender(tx) | FeedbackHandler::SenderReceiver(tx, _) => {
+            try_send(tx, envelope.event, metrics);
+        }
+        FeedbackHandler::EventSender(tx) => {
+            let delta = last_feedback
+                .as_ref()
+                .map(|previous| envelope.event.delta(previous));
+            *last_feedback = Some(envelope.event.clone());
+            let delta = delta
+                .filter(|delta| delta.has_changes())
+                .map(|delta| envelope.clone().map(|_| FeedbackEvent::Delta(delta)));
+            try_send(tx, envelope.map(FeedbackEvent::Changed), metrics);
+            if let Some(delta) = delta {
+                try_send(tx, delta, metrics);
+            }
+        }
+    }
+}
+
+/// Passes a `FeedbackEvent` without feedback values, e.g. `FeedbackEvent::Started`, to given handler
+///
+/// Only event handlers receive these events, other handlers ignore them.
+fn handle_status(handler: &FeedbackHandler, envelope: Envelope<FeedbackEvent>, metrics: &Metrics) {
+    if let FeedbackHandler::EventSender(tx) = handler {
+        try_send(tx, envelope, metrics);
+    }
+}
+
+/// Sends given item without waiting, counting the item as dropped if the channel is full
+fn try_send<T>(tx: &Sender<T>, item: T, metrics: &Metrics) {
+    if let Err(TrySendError::Full(_)) = tx.try_send(item) {
+        metrics.increment(Counter::DroppedEvents);
+    }
+}
+
+/// Coalesces rapid feedback changes to emit at most one feedback per interval
+///
+/// Without interval, every feedback is emitted immediately.
+struct FeedbackCoalescer {
+    interval: Option<Duration>,
+    last_emitted: Option<Instant>,
+    pending: Option<Envelope<Feedback>>,
+}
+
+impl FeedbackCoalescer {
+    fn new(interval: Option<Duration>) -> FeedbackCoalescer {
+        FeedbackCoalescer {
+            interval,
+            last_emitted: None,
+            pending: None,
+        }
+    }
+
+    /// Returns given feedback if it can be emitted now, otherwise keeps it as pending feedback
+    fn push(&mut self, feedback: Envelope<Feedback>) -> Option<Envelope<Feedback>> {
+        match (self.interval, self.last_emitted) {
+            (Some(interval), Some(last_emitted)) if last_emitted.elapsed() < interval => {
+                self.pending = Some(feedback);
+                None
+            }
+            _ => {
+                self.last_emitted = Some(Instant::now());
+                self.pending = None;
+                Some(feedback)
+            }
+        }
+    }
+
+    fn has_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Waits until pending feedback can be emitted
+    async fn wait(&self, runtime: &dyn Runtime) {
+        if let (Some(interval), Some(last_emitted)) = (self.interval, self.last_emitted) {
+            runtime
+                .sleep(interval.saturating_sub(last_emitted.elapsed()))
+                .await;
+        }
+    }
+
+    /// Takes pending feedback to be emitted now
+    fn take(&mut self) -> Option<Envelope<Feedback>> {
+        self.last_emitted = Some(Instant::now());
+        self.pending.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::NoopMetricsSink;
+
+    fn metrics() -> Metrics {
+        Metrics::new(Arc::new(NoopMetricsSink))
+    }
+
+    #[test]
+    fn should_split_batched_message_into_frames() {
+        let mut session = StompSession::new(1024);
+
+        assert!(session.push(
+            "CONNECTED\nversion:1.2\n\n\0\nRECEIPT\nreceipt-id:ping\n\n\0",
+            &metrics()
+        ));
+
+        let connected = session.next_frame().unwrap();
+        assert_eq!(StompFrame::parse(&connected).unwrap().command, "CONNECTED");
+        let receipt = session.next_frame().unwrap();
+        assert_eq!(StompFrame::parse(&receipt).unwrap().command, "RECEIPT");
+        assert_eq!(session.next_frame(), None);
+    }
+
+    #[test]
+    fn should_keep_incomplete_frame_until_completed() {
+        let mut session = StompSession::new(1024);
+
+        assert!(session.push("RECEIPT\nreceipt-", &metrics()));
+        assert_eq!(session.next_frame(), None);
+        assert!(session.push("id:ping\n\n\0", &metrics()));
+
+        let receipt = session.next_frame().unwrap();
+        assert_eq!(
+            StompFrame::parse(&receipt).unwrap().header("receipt-id"),
+            Some("ping")
+        );
+    }
+
+    /// Asserts that the `content-length` header of given frame is the byte length of its body
+    fn assert_content_length(frame: &str) {
+        let frame = frame.trim_end_matches('\0');
+        let (head, body) = frame.split_once("\n\n").unwrap();
+        let length = head
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .unwrap();
+        assert_eq!(length.parse::<usize>().unwrap(), body.len());
+    }
+
+    #[test]
+    fn should_use_byte_length_as_content_length() {
+        let room_id = RoomId::from("räum-ü");
+        let user_id = UserId::from("nutzer-ß");
+
+        let feedback =
+            WsCreateFeedbackMessage::new(&room_id, &user_id, FeedbackValue::Good).to_string();
+        let command = WsFeedbackCommandMessage::new(FeedbackCommand::Reset, &room_id).to_string();
+
+        assert_content_length(&feedback);
+        assert_content_length(&command);
+    }
+
+    #[test]
+    fn should_parse_frame() {
+        let frame =
+            StompFrame::parse("\n\nMESSAGE\r\nsubscription:sub-0\r\ntimestamp:1000\n\n{}\0")
+                .unwrap();
+
+        assert_eq!(frame.command, "MESSAGE");
+        assert_eq!(frame.header("subscription"), Some("sub-0"));
+        assert_eq!(frame.header("destination"), None);
+        assert_eq!(frame.body, "{}");
+        assert_eq!(
+            frame.server_time(),
+            Some(UNIX_EPOCH + Duration::from_secs(1))
+        );
+        assert!(StompFrame::parse("MESSAGE\nsubscription:sub-0").is_none());
+    }
+
+    #[test]
+    fn should_accept_frames_depending_on_state() {
+        assert!(StompState::Handshake.accepts("CONNECTED"));
+        assert!(!StompState::Handshake.accepts("MESSAGE"));
+        assert!(StompState::Connected.accepts("RECEIPT"));
+        assert!(!StompState::Connected.accepts("CONNECTED"));
+        assert!(StompState::Subscribed.accepts("MESSAGE"));
+        assert!(StompState::Subscribed.accepts("ERROR"));
+        assert!(!StompState::Closed.accepts("ERROR"));
+    }
+
+    #[test]
+    fn should_close_session_on_error_frame() {
+        let mut session = StompSession::new(1024);
+        session.transition(StompState::Subscribed).unwrap();
+        let frame = StompFrame::parse("ERROR\nmessage:failure\n\n\0").unwrap();
+
+        assert!(session.accept(&frame, &metrics()));
+        assert_eq!(session.state, StompState::Closed);
+        assert!(!session.accept(&frame, &metrics()));
+    }
+
+    #[test]
+    fn should_close_session_on_oversized_frame() {
+        let mut session = StompSession::new(16);
+        session.transition(StompState::Subscribed).unwrap();
+
+        assert!(!session.push("MESSAGE\n\n01234567890123456789\0", &metrics()));
+        assert_eq!(session.state, StompState::Closed);
+        assert_eq!(session.next_frame(), None);
+    }
+
+    #[test]
+    fn should_negotiate_heartbeat() {
+        let requested = Some(Duration::from_secs(10));
+
+        assert_eq!(
+            negotiate_heartbeat(requested, Some("20000,0")),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(
+            negotiate_heartbeat(requested, Some("5000,0")),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(negotiate_heartbeat(requested, Some("0,0")), None);
+        assert_eq!(negotiate_heartbeat(requested, None), None);
+        assert_eq!(negotiate_heartbeat(None, Some("5000,0")), None);
+        assert_eq!(
+            negotiate_heartbeat(Some(Duration::ZERO), Some("5000,0")),
+            None
+        );
+    }
+
+    #[test]
+    fn should_advance_session_states() {
+        let mut session = StompSession::new(1024);
+
+        assert_eq!(session.transition(StompState::Connected), Ok(()));
+        assert_eq!(session.transition(StompState::Connected), Ok(()));
+        assert_eq!(session.transition(StompState::Subscribed), Ok(()));
+        assert_eq!(session.state, StompState::Subscribed);
+    }
+
+    #[test]
+    fn should_reject_backward_transition() {
+        let mut session = StompSession::new(1024);
+        session.transition(StompState::Subscribed).unwrap();
+
+        assert_eq!(
+            session.transition(StompState::Handshake),
+            Err(ConnectionError)
+        );
+        assert_eq!(session.state, StompState::Subscribed);
+
+        session.close();
+        assert_eq!(
+            session.transition(StompState::Connected),
+            Err(ConnectionError)
+        );
+        assert_eq!(session.state, StompState::Closed);
+    }
+}
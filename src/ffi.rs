@@ -0,0 +1,342 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::ffi::{c_char, c_void, CStr};
+use std::ptr;
+
+use crate::blocking;
+#[cfg(feature = "websocket")]
+use crate::client::FeedbackHandler;
+use crate::client::{ClientError, Feedback, LoggedIn, LoggedOut};
+use crate::ids::ShortId;
+
+/// An ARSnova client created by `arsnova_client_new()`
+pub struct ArsnovaClient {
+    api_url: String,
+    state: ClientState,
+}
+
+/// A handle to cancel a feedback subscription, created by `arsnova_subscription_new()`
+#[cfg(feature = "websocket")]
+pub struct ArsnovaSubscription {
+    cancelled: tokio::sync::watch::Sender<bool>,
+}
+
+enum ClientState {
+    LoggedOut(blocking::Client<LoggedOut>),
+    LoggedIn(blocking::Client<LoggedIn>),
+    Invalid,
+}
+
+/// The result of FFI functions
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArsnovaResult {
+    Ok = 0,
+    ConnectionError = 1,
+    LoginError = 2,
+    RoomNotFoundError = 3,
+    ParserError = 4,
+    UrlError = 5,
+    FeedbackValueError = 6,
+    CertificateError = 7,
+    InvalidArgument = 8,
+    NotLoggedIn = 9,
+    InvalidHeaderError = 10,
+    ContentGroupNotFoundError = 11,
+    VoteSuppressedError = 12,
+}
+
+impl From<ClientError> for ArsnovaResult {
+    fn from(err: ClientError) -> Self {
+        match err {
+            ClientError::ConnectionError => ArsnovaResult::ConnectionError,
+            ClientError::LoginError => ArsnovaResult::LoginError,
+            ClientError::RoomNotFoundError(_) => ArsnovaResult::RoomNotFoundError,
+            ClientError::ParserError(_) => ArsnovaResult::ParserError,
+            ClientError::UrlError => ArsnovaResult::UrlError,
+            ClientError::FeedbackValueError(_) => ArsnovaResult::FeedbackValueError,
+            ClientError::CertificateError => ArsnovaResult::CertificateError,
+            ClientError::InvalidHeaderError(_) => ArsnovaResult::InvalidHeaderError,
+            ClientError::ContentGroupNotFoundError(_) => ArsnovaResult::ContentGroupNotFoundError,
+            ClientError::VoteSuppressedError => ArsnovaResult::VoteSuppressedError,
+        }
+    }
+}
+
+/// A snapshot of the feedback of a room
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ArsnovaFeedback {
+    pub very_good: u16,
+    pub good: u16,
+    pub bad: u16,
+    pub very_bad: u16,
+}
+
+impl From<Feedback> for ArsnovaFeedback {
+    fn from(feedback: Feedback) -> Self {
+        ArsnovaFeedback {
+            very_good: feedback.very_good,
+            good: feedback.good,
+            bad: feedback.bad,
+            very_bad: feedback.very_bad,
+        }
+    }
+}
+
+/// A callback receiving feedback changes and the user data given on subscription
+pub type ArsnovaFeedbackCallback =
+    extern "C" fn(feedback: *const ArsnovaFeedback, user_data: *mut c_void);
+
+/// Returns the string of given C string pointer, if not null and valid UTF-8
+unsafe fn to_str<'a>(value: *const c_char) -> Option<&'a str> {
+    if value.is_null() {
+        return None;
+    }
+    CStr::from_ptr(value).to_str().ok()
+}
+
+/// Creates a new client for given API URL
+///
+/// Returns a null pointer if the URL cannot be parsed. The client must be freed using
+/// `arsnova_client_free()`.
+///
+/// # Safety
+///
+/// `api_url` must be a null pointer or point to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn arsnova_client_new(api_url: *const c_char) -> *mut ArsnovaClient {
+    let Some(api_url) = to_str(api_url) else {
+        return ptr::null_mut();
+    };
+    match blocking::Client::new(api_url) {
+        Ok(client) => Box::into_raw(Box::new(ArsnovaClient {
+            api_url: api_url.to_string(),
+            state: ClientState::LoggedOut(client),
+        })),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Frees a client created by `arsnova_client_new()`
+///
+/// # Safety
+///
+/// `client` must be a null pointer or a client returned by `arsnova_client_new()`
+/// which has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn arsnova_client_free(client: *mut ArsnovaClient) {
+    if !client.is_null() {
+        drop(Box::from_raw(client));
+    }
+}
+
+/// Requests a guest login
+///
+/// Logging in an already logged in client has no effect.
+///
+/// # Safety
+///
+/// `client` must be a null pointer or a valid client returned by `arsnova_client_new()`.
+#[no_mangle]
+pub unsafe extern "C" fn arsnova_client_guest_login(client: *mut ArsnovaClient) -> ArsnovaResult {
+    let Some(client) = client.as_mut() else {
+        return ArsnovaResult::InvalidArgument;
+    };
+    match std::mem::replace(&mut client.state, ClientState::Invalid) {
+        ClientState::LoggedOut(logged_out) => match logged_out.guest_login() {
+            Ok(logged_in) => {
+                client.state = ClientState::LoggedIn(logged_in);
+                ArsnovaResult::Ok
+            }
+            Err(err) => {
+                // A failed login consumes the client, so a new one is created to allow retries
+                if let Ok(logged_out) = blocking::Client::new(client.api_url.as_str()) {
+                    client.state = ClientState::LoggedOut(logged_out);
+                }
+                err.into()
+            }
+        },
+        state => {
+            client.state = state;
+            ArsnovaResult::Ok
+        }
+    }
+}
+
+/// Requests the current feedback of the room with given 8-digit room ID
+///
+/// On success, the feedback is written to `feedback`.
+///
+/// # Safety
+///
+/// `client` must be a null pointer or a valid client returned by `arsnova_client_new()`,
+/// `short_id` must be a null pointer or point to a null-terminated string and `feedback`
+/// must be a null pointer or point to a writable `ArsnovaFeedback`.
+#[no_mangle]
+pub unsafe extern "C" fn arsnova_client_get_feedback(
+    client: *const ArsnovaClient,
+    short_id: *const c_char,
+    feedback: *mut ArsnovaFeedback,
+) -> ArsnovaResult {
+    let (Some(client), Some(short_id), Some(feedback)) =
+        (client.as_ref(), to_str(short_id), feedback.as_mut())
+    else {
+        return ArsnovaResult::InvalidArgument;
+    };
+    let ClientState::LoggedIn(client) = &client.state else {
+        return ArsnovaResult::NotLoggedIn;
+    };
+    match client.get_feedback(&ShortId::from(short_id)) {
+        Ok(result) => {
+            *feedback = result.into();
+            ArsnovaResult::Ok
+        }
+        Err(err) => err.into(),
+    }
+}
+
+/// Creates a handle to cancel a feedback subscription
+///
+/// The handle must be freed using `arsnova_subscription_free()` after the subscription ended.
+#[cfg(feature = "websocket")]
+#[no_mangle]
+pub extern "C" fn arsnova_subscription_new() -> *mut ArsnovaSubscription {
+    let (cancelled, _) = tokio::sync::watch::channel(false);
+    Box::into_raw(Box::new(ArsnovaSubscription { cancelled }))
+}
+
+/// Cancels the subscription using given handle, ending `arsnova_client_subscribe_feedback()`
+///
+/// This can be called from any thread, including the callback. A cancelled handle cannot
+/// be used for another subscription.
+///
+/// # Safety
+///
+/// `subscription` must be a null pointer or a handle returned by `arsnova_subscription_new()`
+/// which has not been freed yet.
+#[cfg(feature = "websocket")]
+#[no_mangle]
+pub unsafe extern "C" fn arsnova_subscription_cancel(subscription: *const ArsnovaSubscription) {
+    if let Some(subscription) = subscription.as_ref() {
+        subscription.cancelled.send_replace(true);
+    }
+}
+
+/// Frees a handle created by `arsnova_subscription_new()`
+///
+/// # Safety
+///
+/// `subscription` must be a null pointer or a handle returned by `arsnova_subscription_new()`
+/// which has not been freed yet and is not used by a running subscription.
+#[cfg(feature = "websocket")]
+#[no_mangle]
+pub unsafe extern "C" fn arsnova_subscription_free(subscription: *mut ArsnovaSubscription) {
+    if !subscription.is_null() {
+        drop(Box::from_raw(subscription));
+    }
+}
+
+/// Subscribes to feedback changes of the room with given 8-digit room ID
+///
+/// The callback is called with each feedback change and given user data. This function blocks
+/// the calling thread until the subscription ends or is cancelled using given handle and
+/// `arsnova_subscription_cancel()`. Without a handle, the subscription cannot be cancelled.
+///
+/// # Safety
+///
+/// `client` must be a null pointer or a valid client returned by `arsnova_client_new()`,
+/// `short_id` must be a null pointer or point to a null-terminated string and `subscription`
+/// must be a null pointer or a valid handle returned by `arsnova_subscription_new()`.
+/// The feedback passed to the callback is only valid during the callback.
+#[cfg(feature = "websocket")]
+#[no_mangle]
+pub unsafe extern "C" fn arsnova_client_subscribe_feedback(
+    client: *const ArsnovaClient,
+    short_id: *const c_char,
+    callback: ArsnovaFeedbackCallback,
+    user_data: *mut c_void,
+    subscription: *const ArsnovaSubscription,
+) -> ArsnovaResult {
+    let (Some(client), Some(short_id)) = (client.as_ref(), to_str(short_id)) else {
+        return ArsnovaResult::InvalidArgument;
+    };
+    let ClientState::LoggedIn(client) = &client.state else {
+        return ArsnovaResult::NotLoggedIn;
+    };
+
+    let short_id = ShortId::from(short_id);
+    let mut cancelled = subscription
+        .as_ref()
+        .map(|subscription| subscription.cancelled.subscribe());
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<Feedback>(10);
+    let result = client.runtime.block_on(async {
+        let subscription = client
+            .inner
+            .on_feedback_changed(&short_id, FeedbackHandler::Sender(tx));
+        let notify = async {
+            while let Some(feedback) = rx.recv().await {
+                callback(&ArsnovaFeedback::from(feedback), user_data);
+            }
+        };
+        let cancel = async {
+            match &mut cancelled {
+                Some(cancelled) => {
+                    let _ = cancelled.wait_for(|cancelled| *cancelled).await;
+                }
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            result = subscription => result,
+            _ = notify => Ok(()),
+            _ = cancel => Ok(()),
+        }
+    });
+
+    match result {
+        Ok(()) => ArsnovaResult::Ok,
+        Err(err) => err.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_map_new_error_variants() {
+        assert_eq!(
+            ArsnovaResult::from(ClientError::InvalidHeaderError("user-agent".to_string())),
+            ArsnovaResult::InvalidHeaderError
+        );
+        assert_eq!(ArsnovaResult::InvalidHeaderError as i32, 10);
+        assert_eq!(
+            ArsnovaResult::from(ClientError::ContentGroupNotFoundError("a".to_string())),
+            ArsnovaResult::ContentGroupNotFoundError
+        );
+        assert_eq!(ArsnovaResult::ContentGroupNotFoundError as i32, 11);
+        assert_eq!(
+            ArsnovaResult::from(ClientError::VoteSuppressedError),
+            ArsnovaResult::VoteSuppressedError
+        );
+        assert_eq!(ArsnovaResult::VoteSuppressedError as i32, 12);
+    }
+}
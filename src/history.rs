@@ -0,0 +1,307 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io::Write;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::{Feedback, FeedbackValue};
+
+/// A `Feedback` snapshot with the time it was recorded
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FeedbackSnapshot {
+    pub timestamp: SystemTime,
+    pub feedback: Feedback,
+}
+
+/// The aggregated `Feedback` snapshots recorded within a fixed interval
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FeedbackBucket {
+    /// The start of the interval
+    pub start: SystemTime,
+    /// The number of snapshots recorded within the interval
+    pub snapshots: usize,
+    /// The average score of all snapshots with votes, see `Feedback::score()`
+    pub average_score: Option<f32>,
+    /// The maximum number of votes of a snapshot within the interval
+    pub participants: u32,
+}
+
+/// A bounded history of `Feedback` snapshots
+///
+/// If the capacity is reached, the oldest snapshot will be removed on recording a new one.
+#[derive(Debug, Clone)]
+pub struct FeedbackHistory {
+    capacity: usize,
+    snapshots: VecDeque<FeedbackSnapshot>,
+}
+
+impl FeedbackHistory {
+    /// Constructs a new history keeping up to `capacity` snapshots
+    pub fn new(capacity: usize) -> FeedbackHistory {
+        FeedbackHistory {
+            capacity,
+            snapshots: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Records given feedback with current time
+    pub fn record(&mut self, feedback: Feedback) {
+        self.record_at(SystemTime::now(), feedback)
+    }
+
+    /// Records given feedback with given time
+    pub fn record_at(&mut self, timestamp: SystemTime, feedback: Feedback) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.snapshots.len() >= self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(FeedbackSnapshot {
+            timestamp,
+            feedback,
+        });
+    }
+
+    /// Returns the maximum number of snapshots kept
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of recorded snapshots
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Indicates if there are no recorded snapshots
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Removes all recorded snapshots
+    pub fn clear(&mut self) {
+        self.snapshots.clear()
+    }
+
+    /// Returns all recorded snapshots, oldest first
+    pub fn snapshots(&self) -> impl Iterator<Item = &FeedbackSnapshot> {
+        self.snapshots.iter()
+    }
+
+    /// Returns the most recent snapshot
+    pub fn latest(&self) -> Option<&FeedbackSnapshot> {
+        self.snapshots.back()
+    }
+
+    /// Returns all snapshots recorded within the last given duration, oldest first
+    pub fn last(&self, duration: Duration) -> impl Iterator<Item = &FeedbackSnapshot> {
+        let since = SystemTime::now()
+            .checked_sub(duration)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        self.snapshots
+            .iter()
+            .filter(move |snapshot| snapshot.timestamp >= since)
+    }
+
+    /// Returns the minimum number of votes for given feedback value within the last given duration
+    pub fn min(&self, value: &FeedbackValue, duration: Duration) -> Option<u16> {
+        self.last(duration)
+            .map(|snapshot| snapshot.feedback.get(value))
+            .min()
+    }
+
+    /// Returns the maximum number of votes for given feedback value within the last given duration
+    pub fn max(&self, value: &FeedbackValue, duration: Duration) -> Option<u16> {
+        self.last(duration)
+            .map(|snapshot| snapshot.feedback.get(value))
+            .max()
+    }
+
+    /// Returns the change of the average score within the last given duration
+    ///
+    /// A positive value indicates an improving mood, a negative value a worsening mood.
+    /// Returns `None` if there are less than two snapshots with votes within given duration.
+    pub fn trend(&self, duration: Duration) -> Option<f32> {
+        let mut scores = self
+            .last(duration)
+            .filter_map(|snapshot| snapshot.feedback.score());
+        let first = scores.next()?;
+        let last = scores.last()?;
+        Some(last - first)
+    }
+
+    /// Aggregates all recorded snapshots into buckets of given interval, oldest first
+    ///
+    /// Buckets start at multiples of the interval since the UNIX epoch. Only intervals containing
+    /// snapshots result in a bucket, so there are never more buckets than recorded snapshots.
+    /// A zero interval results in no buckets.
+    pub fn aggregate(&self, interval: Duration) -> Vec<FeedbackBucket> {
+        let interval = interval.as_millis();
+        if interval == 0 {
+            return vec![];
+        }
+
+        let mut buckets = BTreeMap::<u128, (FeedbackBucket, f32, usize)>::new();
+        for snapshot in &self.snapshots {
+            let index = snapshot
+                .timestamp
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis()
+                / interval;
+            let (bucket, sum, count) = buckets.entry(index).or_insert_with(|| {
+                let start =
+                    Duration::from_millis((index * interval).try_into().unwrap_or(u64::MAX));
+                let bucket = FeedbackBucket {
+                    start: SystemTime::UNIX_EPOCH + start,
+                    snapshots: 0,
+                    average_score: None,
+                    participants: 0,
+                };
+                (bucket, 0.0, 0)
+            });
+            bucket.snapshots += 1;
+            bucket.participants = bucket.participants.max(snapshot.feedback.count_votes());
+            if let Some(score) = snapshot.feedback.score() {
+                *sum += score;
+                *count += 1;
+            }
+        }
+
+        buckets
+            .into_values()
+            .map(|(bucket, sum, count)| FeedbackBucket {
+                average_score: (count > 0).then(|| sum / count as f32),
+                ..bucket
+            })
+            .collect()
+    }
+
+    /// Writes all recorded snapshots as CSV to given writer, oldest first
+    ///
+    /// The first row contains the column names. Each following row contains the timestamp
+    /// as milliseconds since the UNIX epoch and the number of votes for each feedback value.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(writer, "timestamp,very_good,good,bad,very_bad")?;
+        for snapshot in &self.snapshots {
+            let timestamp = snapshot
+                .timestamp
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis();
+            let feedback = &snapshot.feedback;
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                timestamp, feedback.very_good, feedback.good, feedback.bad, feedback.very_bad
+            )?;
+        }
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn should_aggregate_empty_history() {
+        let history = FeedbackHistory::new(10);
+
+        assert_eq!(history.aggregate(Duration::from_secs(30)), vec![]);
+    }
+
+    #[test]
+    fn should_aggregate_single_snapshot() {
+        let mut history = FeedbackHistory::new(10);
+        history.record_at(at(65), Feedback::from_values([2, 0, 0, 2]));
+
+        assert_eq!(
+            history.aggregate(Duration::from_secs(30)),
+            vec![FeedbackBucket {
+                start: at(60),
+                snapshots: 1,
+                average_score: Feedback::from_values([2, 0, 0, 2]).score(),
+                participants: 4,
+            }]
+        );
+    }
+
+    #[test]
+    fn should_skip_intervals_without_snapshots() {
+        let mut history = FeedbackHistory::new(10);
+        history.record_at(at(0), Feedback::from_values([1, 0, 0, 0]));
+        history.record_at(at(10), Feedback::from_values([0, 0, 0, 0]));
+        history.record_at(at(86_400 * 365), Feedback::from_values([0, 0, 0, 3]));
+
+        let buckets = history.aggregate(Duration::from_secs(30));
+
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].start, at(0));
+        assert_eq!(buckets[0].snapshots, 2);
+        assert_eq!(buckets[0].participants, 1);
+        assert_eq!(
+            buckets[0].average_score,
+            Feedback::from_values([1, 0, 0, 0]).score()
+        );
+        assert_eq!(buckets[1].start, at(86_400 * 365));
+        assert_eq!(buckets[1].participants, 3);
+    }
+
+    #[test]
+    fn should_write_csv() {
+        let mut history = FeedbackHistory::new(10);
+        history.record_at(at(1), Feedback::from_values([1, 2, 3, 4]));
+        history.record_at(at(2), Feedback::from_values([0, 0, 0, 5]));
+
+        let mut csv = vec![];
+        history.write_csv(&mut csv).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "timestamp,very_good,good,bad,very_bad\n1000,1,2,3,4\n2000,0,0,0,5\n"
+        );
+    }
+
+    #[test]
+    fn should_write_csv_header_only_for_empty_history() {
+        let mut csv = vec![];
+        FeedbackHistory::new(10).write_csv(&mut csv).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "timestamp,very_good,good,bad,very_bad\n"
+        );
+    }
+
+    #[test]
+    fn should_not_aggregate_with_zero_interval() {
+        let mut history = FeedbackHistory::new(10);
+        history.record_at(at(0), Feedback::from_values([1, 0, 0, 0]));
+
+        assert_eq!(history.aggregate(Duration::ZERO), vec![]);
+    }
+}
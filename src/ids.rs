@@ -0,0 +1,150 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+macro_rules! id_type {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            /// Constructs a new identifier from given value
+            pub fn new<S: Into<String>>(value: S) -> $name {
+                $name(value.into())
+            }
+
+            /// Returns the identifier as string slice
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                $name::new(value)
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                $name::new(value)
+            }
+        }
+
+        impl From<&String> for $name {
+            fn from(value: &String) -> Self {
+                $name::new(value)
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                self.as_str()
+            }
+        }
+    };
+}
+
+id_type!(
+    /// The internal ID of a room as used by the ARSnova API
+    RoomId
+);
+
+id_type!(
+    /// The 8-digit short ID of a room as shown to participants
+    ShortId
+);
+
+id_type!(
+    /// The ID of a user, e.g. the guest account of a logged in client
+    UserId
+);
+
+id_type!(
+    /// The ID of a content (question) within a room
+    ContentId
+);
+
+id_type!(
+    /// The ID of a comment (question of a participant) within a room
+    CommentId
+);
+
+id_type!(
+    /// The ID of an answer to a content
+    AnswerId
+);
+
+id_type!(
+    /// The ID of an announcement within a room
+    AnnouncementId
+);
+
+id_type!(
+    /// The ID of a content group within a room
+    ContentGroupId
+);
+
+id_type!(
+    /// The ID of a file attached to a content, e.g. an image illustrating the question
+    AttachmentId
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_serialize_ids_as_plain_strings() {
+        let short_id = ShortId::from("12345678");
+
+        assert_eq!(serde_json::to_string(&short_id).unwrap(), r#""12345678""#);
+        assert_eq!(
+            serde_json::from_str::<ShortId>(r#""12345678""#).unwrap(),
+            short_id
+        );
+    }
+
+    #[test]
+    fn should_convert_ids_from_and_to_strings() {
+        let value = String::from("a1b2");
+
+        assert_eq!(RoomId::from(&value), RoomId::new("a1b2"));
+        assert_eq!(RoomId::from(value.clone()).as_str(), "a1b2");
+        assert_eq!(RoomId::from("a1b2").to_string(), value);
+        assert_eq!(ContentId::from("a1b2").as_ref(), "a1b2");
+    }
+
+    #[test]
+    fn should_reject_non_string_ids() {
+        assert!(serde_json::from_str::<ShortId>("12345678").is_err());
+        assert!(serde_json::from_str::<RoomId>("null").is_err());
+    }
+}
@@ -17,6 +17,65 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+#[macro_use]
+mod trace;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!("arsnova_client");
+
+pub mod backoff;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod client;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod history;
+pub mod ids;
+#[cfg(feature = "markdown")]
+pub mod markdown;
+pub mod metrics;
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+#[cfg(feature = "test-util")]
+pub mod mock;
+pub mod models;
+#[cfg(feature = "python")]
+mod python;
+pub mod recorder;
+pub mod replay;
+pub mod report;
+#[cfg(feature = "websocket")]
+pub mod runtime;
+#[cfg(feature = "websocket")]
+pub mod supervisor;
+pub mod transport;
 
-pub use client::{Client, Feedback, FeedbackHandler, FeedbackValue, RoomInfo};
+pub use backoff::{BackoffPolicy, ExponentialBackoff, FixedBackoff};
+#[cfg(feature = "legacy")]
+pub use client::ApiVersion;
+pub use client::{
+    AnnouncementEvent, AttachmentDownload, Client, ClientBuilder, CommentEvent, ContentEvent,
+    Envelope, EventKinds, Feedback, FeedbackClient, FeedbackDelta, FeedbackEvent, FeedbackValue,
+    FileTokenStore, FocusEvent, Latency, RoomEvent, RoomInfo, RoomsClient, Service, Session,
+    TokenStore,
+};
+#[cfg(feature = "websocket")]
+pub use client::{ConnectionState, FeedbackHandler};
+pub use history::{FeedbackBucket, FeedbackHistory, FeedbackSnapshot};
+pub use ids::{
+    AnnouncementId, AnswerId, AttachmentId, CommentId, ContentGroupId, ContentId, RoomId, ShortId,
+    UserId,
+};
+pub use metrics::{Counter, Gauge, MetricsSink, NoopMetricsSink, Timing};
+#[cfg(feature = "test-util")]
+pub use mock::MockServer;
+pub use recorder::{Record, RecordedEvent, Recorder};
+pub use replay::Replay;
+pub use report::{ContentReport, SessionReport};
+#[cfg(feature = "websocket")]
+pub use runtime::{Runtime, TokioRuntime};
+#[cfg(feature = "websocket")]
+pub use supervisor::{RestartPolicy, Supervisor, TaskHandle, TaskHealth};
+pub use transport::{HttpTransport, ReqwestTransport};
+#[cfg(feature = "websocket")]
+pub use transport::{TungsteniteTransport, WsTransport};
@@ -0,0 +1,388 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Rendering of Markdown bodies with optional LaTeX formulas for terminal clients
+//!
+//! Bodies of contents and comments are written in Markdown and may contain LaTeX formulas
+//! enclosed in `$` or `$$`. Formulas are shown as text, using Unicode symbols for common commands.
+
+use pulldown_cmark::{Event, HeadingLevel, LinkType, Options, Parser, Tag, TagEnd};
+
+/// Renders given Markdown as plain text
+pub fn to_plain_text(markdown: &str) -> String {
+    Renderer::new(false).render(markdown)
+}
+
+/// Renders given Markdown as text using ANSI escape sequences for emphasis, headings and code
+pub fn to_ansi(markdown: &str) -> String {
+    Renderer::new(true).render(markdown)
+}
+
+/// Converts given LaTeX formula to text, using Unicode symbols for common commands
+///
+/// Unknown commands and braces are removed, fractions are shown as `a/b`.
+pub fn latex_to_text(latex: &str) -> String {
+    let mut text = String::new();
+    let mut chars = latex.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let mut command = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                    command.push(c);
+                }
+                if command.is_empty() {
+                    match chars.next() {
+                        Some('\\') | Some(',') | Some(';') | Some(' ') => text.push(' '),
+                        Some(c) => text.push(c),
+                        None => {}
+                    }
+                } else if command == "frac" {
+                    text.push_str(&latex_group(&mut chars));
+                    text.push('/');
+                    text.push_str(&latex_group(&mut chars));
+                } else if let Some(symbol) = latex_symbol(&command) {
+                    text.push_str(symbol);
+                }
+            }
+            '^' | '_' => {
+                text.push(c);
+                text.push_str(&latex_group(&mut chars));
+            }
+            '{' | '}' => {}
+            c => text.push(c),
+        }
+    }
+    text
+}
+
+/// Converts the next group in braces, or the next char, of a LaTeX formula to text
+fn latex_group(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    match chars.next() {
+        Some('{') => {
+            let mut group = String::new();
+            let mut depth = 1;
+            for c in chars.by_ref() {
+                match c {
+                    '{' => depth += 1,
+                    '}' if depth == 1 => break,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                group.push(c);
+            }
+            let group = latex_to_text(&group);
+            match group.chars().count() {
+                1 => group,
+                _ => format!("({})", group),
+            }
+        }
+        Some(c) => c.to_string(),
+        None => String::new(),
+    }
+}
+
+/// Returns the Unicode symbol of given LaTeX command
+fn latex_symbol(command: &str) -> Option<&'static str> {
+    let symbol = match command {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" | "varepsilon" => "ε",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "pi" => "π",
+        "rho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "phi" | "varphi" => "φ",
+        "omega" => "ω",
+        "Delta" => "Δ",
+        "Sigma" => "Σ",
+        "Omega" => "Ω",
+        "cdot" => "·",
+        "times" => "×",
+        "div" => "÷",
+        "pm" => "±",
+        "le" | "leq" => "≤",
+        "ge" | "geq" => "≥",
+        "neq" | "ne" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "infty" => "∞",
+        "sum" => "∑",
+        "prod" => "∏",
+        "int" => "∫",
+        "sqrt" => "√",
+        "partial" => "∂",
+        "in" => "∈",
+        "notin" => "∉",
+        "subset" => "⊂",
+        "cup" => "∪",
+        "cap" => "∩",
+        "forall" => "∀",
+        "exists" => "∃",
+        "neg" | "lnot" => "¬",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "to" | "rightarrow" => "→",
+        "leftarrow" => "←",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftrightarrow" | "iff" => "⇔",
+        "ldots" | "dots" | "cdots" => "…",
+        "quad" | "qquad" => " ",
+        _ => return None,
+    };
+    Some(symbol)
+}
+
+/// Writes text of Markdown events, keeping track of nested blocks
+struct Renderer {
+    ansi: bool,
+    text: String,
+    line_start: bool,
+    pending_blank_line: bool,
+    quote_depth: usize,
+    lists: Vec<Option<u64>>,
+    links: Vec<(LinkType, String)>,
+    code_block: bool,
+}
+
+impl Renderer {
+    fn new(ansi: bool) -> Renderer {
+        Renderer {
+            ansi,
+            text: String::new(),
+            line_start: true,
+            pending_blank_line: false,
+            quote_depth: 0,
+            lists: vec![],
+            links: vec![],
+            code_block: false,
+        }
+    }
+
+    fn render(mut self, markdown: &str) -> String {
+        let options = Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_TABLES
+            | Options::ENABLE_TASKLISTS
+            | Options::ENABLE_MATH;
+        Parser::new_ext(markdown, options).for_each(|event| self.event(event));
+        self.text.trim_end().to_string()
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.code_block => {
+                text.lines().for_each(|line| {
+                    self.write("    ");
+                    self.styled(line, "\x1b[36m", "\x1b[39m");
+                    self.newline();
+                });
+            }
+            Event::Text(text) => self.write(&text),
+            Event::Code(code) => self.styled(&code, "\x1b[36m", "\x1b[39m"),
+            Event::InlineMath(latex) => self.styled(&latex_to_text(&latex), "\x1b[3m", "\x1b[23m"),
+            Event::DisplayMath(latex) => {
+                self.block_break();
+                self.write("    ");
+                self.styled(&latex_to_text(&latex), "\x1b[3m", "\x1b[23m");
+                self.block_break();
+            }
+            Event::SoftBreak => self.write(" "),
+            Event::HardBreak => self.newline(),
+            Event::Rule => {
+                self.block_break();
+                self.write("───");
+                self.blank_line();
+            }
+            Event::TaskListMarker(checked) => self.write(if checked { "[x] " } else { "[ ] " }),
+            Event::FootnoteReference(name) => self.write(&format!("[{}]", name)),
+            Event::Html(_) | Event::InlineHtml(_) => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading { level, .. } => {
+                self.block_break();
+                self.escape(match level {
+                    HeadingLevel::H1 | HeadingLevel::H2 => "\x1b[1;4m",
+                    _ => "\x1b[1m",
+                });
+            }
+            Tag::BlockQuote(_) => {
+                self.block_break();
+                self.flush_blank_line();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(_) => {
+                self.block_break();
+                self.code_block = true;
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.block_break();
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.newline();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        let marker = format!("{}. ", number);
+                        *number += 1;
+                        marker
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.write(&format!("{}{}", indent, marker));
+            }
+            Tag::Emphasis => self.escape("\x1b[3m"),
+            Tag::Strong => self.escape("\x1b[1m"),
+            Tag::Strikethrough => self.escape("\x1b[9m"),
+            Tag::Link {
+                link_type,
+                dest_url,
+                ..
+            } => {
+                self.escape("\x1b[4m");
+                self.links.push((link_type, dest_url.to_string()));
+            }
+            Tag::Image { .. } => self.write("["),
+            Tag::TableRow | Tag::TableHead => self.newline(),
+            Tag::TableCell => {
+                if !self.line_start {
+                    self.write(" | ");
+                }
+            }
+            Tag::Paragraph
+            | Tag::HtmlBlock
+            | Tag::FootnoteDefinition(_)
+            | Tag::DefinitionList
+            | Tag::DefinitionListTitle
+            | Tag::DefinitionListDefinition
+            | Tag::Table(_)
+            | Tag::Superscript
+            | Tag::Subscript
+            | Tag::MetadataBlock(_) => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph if !self.lists.is_empty() => self.newline(),
+            TagEnd::Paragraph | TagEnd::Table => self.blank_line(),
+            TagEnd::Heading(_) => {
+                self.escape("\x1b[0m");
+                self.blank_line();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+                self.blank_line();
+            }
+            TagEnd::CodeBlock => {
+                self.code_block = false;
+                self.blank_line();
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blank_line();
+                }
+            }
+            TagEnd::Emphasis => self.escape("\x1b[23m"),
+            TagEnd::Strong => self.escape("\x1b[22m"),
+            TagEnd::Strikethrough => self.escape("\x1b[29m"),
+            TagEnd::Link => {
+                self.escape("\x1b[24m");
+                if let Some((link_type, url)) = self.links.pop() {
+                    if !matches!(link_type, LinkType::Autolink | LinkType::Email) {
+                        self.write(&format!(" ({})", url));
+                    }
+                }
+            }
+            TagEnd::Image => self.write("]"),
+            _ => {}
+        }
+    }
+
+    /// Writes given text, prefixed with quote markers at the start of a line
+    fn write(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.flush_blank_line();
+        if self.line_start {
+            self.text.push_str(&"> ".repeat(self.quote_depth));
+            self.line_start = false;
+        }
+        self.text.push_str(text);
+    }
+
+    /// Writes given text enclosed in given escape sequences
+    fn styled(&mut self, text: &str, start: &str, end: &str) {
+        self.escape(start);
+        self.write(text);
+        self.escape(end);
+    }
+
+    /// Writes given escape sequence if rendering using ANSI escape sequences
+    fn escape(&mut self, sequence: &str) {
+        if self.ansi {
+            self.flush_blank_line();
+            self.text.push_str(sequence);
+        }
+    }
+
+    /// Starts a new line unless already at the start of a line
+    fn newline(&mut self) {
+        if !self.line_start {
+            self.text.push('\n');
+            self.line_start = true;
+        }
+    }
+
+    /// Ends the current block with an empty line, written once the next block starts
+    fn blank_line(&mut self) {
+        self.newline();
+        self.pending_blank_line = !self.text.is_empty();
+    }
+
+    /// Writes a pending empty line, prefixed with quote markers of the current block
+    fn flush_blank_line(&mut self) {
+        if self.pending_blank_line {
+            self.text.push_str("> ".repeat(self.quote_depth).trim_end());
+            self.text.push('\n');
+            self.pending_blank_line = false;
+        }
+    }
+
+    /// Separates a new block from preceding text
+    fn block_break(&mut self) {
+        if !self.line_start {
+            self.blank_line();
+        }
+    }
+}
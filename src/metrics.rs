@@ -0,0 +1,140 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A counter of client activity
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Counter {
+    /// An HTTP request was sent
+    HttpRequests,
+    /// A websocket message was received
+    MessagesReceived,
+    /// A websocket message was sent
+    MessagesSent,
+    /// A websocket connection was re-established
+    Reconnects,
+    /// A received message could not be parsed
+    ParseFailures,
+    /// An event was dropped because the consumer or the websocket writer did not keep up
+    DroppedEvents,
+    /// An outgoing vote was suppressed by the configured vote policy
+    SuppressedVotes,
+    /// A received STOMP frame was rejected because it was unexpected in the session state
+    RejectedFrames,
+}
+
+/// A gauge of the current client state
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Gauge {
+    /// The number of currently active subscriptions
+    Subscriptions,
+}
+
+/// A measured duration
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Timing {
+    /// The round-trip time of an HTTP request
+    HttpRoundTrip,
+    /// The time until the server confirmed a STOMP frame using a receipt
+    StompReceipt,
+}
+
+/// A sink for client metrics
+///
+/// Implement this trait to forward metrics to a monitoring system like Prometheus or statsd.
+/// All methods have a default implementation discarding the metric.
+pub trait MetricsSink: Send + Sync {
+    /// Increments given counter by given value
+    fn increment(&self, counter: Counter, value: u64) {
+        let _ = (counter, value);
+    }
+
+    /// Sets given gauge to given value
+    fn gauge(&self, gauge: Gauge, value: i64) {
+        let _ = (gauge, value);
+    }
+
+    /// Records given measured duration
+    fn timing(&self, timing: Timing, duration: Duration) {
+        let _ = (timing, duration);
+    }
+}
+
+/// A `MetricsSink` discarding all metrics
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopMetricsSink;
+
+impl MetricsSink for NoopMetricsSink {}
+
+/// Internal metrics of a client forwarding all metrics to the configured `MetricsSink`
+pub(crate) struct Metrics {
+    sink: Arc<dyn MetricsSink>,
+    dropped_events: AtomicU64,
+    subscriptions: AtomicI64,
+}
+
+#[cfg_attr(not(feature = "websocket"), allow(dead_code))]
+impl Metrics {
+    pub(crate) fn new(sink: Arc<dyn MetricsSink>) -> Metrics {
+        Metrics {
+            sink,
+            dropped_events: AtomicU64::new(0),
+            subscriptions: AtomicI64::new(0),
+        }
+    }
+
+    pub(crate) fn increment(&self, counter: Counter) {
+        if counter == Counter::DroppedEvents {
+            self.dropped_events.fetch_add(1, Ordering::Relaxed);
+        }
+        self.sink.increment(counter, 1);
+    }
+
+    pub(crate) fn timing(&self, timing: Timing, duration: Duration) {
+        self.sink.timing(timing, duration);
+    }
+
+    pub(crate) fn dropped_events(&self) -> u64 {
+        self.dropped_events.load(Ordering::Relaxed)
+    }
+
+    /// Tracks a new active subscription until the returned guard is dropped
+    pub(crate) fn subscription(self: &Arc<Self>) -> SubscriptionGuard {
+        let value = self.subscriptions.fetch_add(1, Ordering::Relaxed) + 1;
+        self.sink.gauge(Gauge::Subscriptions, value);
+        SubscriptionGuard {
+            metrics: self.clone(),
+        }
+    }
+}
+
+/// Decrements the number of active subscriptions on drop
+pub(crate) struct SubscriptionGuard {
+    metrics: Arc<Metrics>,
+}
+
+impl Drop for SubscriptionGuard {
+    fn drop(&mut self) {
+        let value = self.metrics.subscriptions.fetch_sub(1, Ordering::Relaxed) - 1;
+        self.metrics.sink.gauge(Gauge::Subscriptions, value);
+    }
+}
@@ -0,0 +1,131 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc::channel;
+
+use crate::client::ClientError::{ConnectionError, LoginError};
+use crate::client::{Client, ClientError, Feedback, FeedbackHandler, FeedbackValue, LoggedIn};
+use crate::ids::ShortId;
+
+/// Receives feedback changes of a subscription
+#[uniffi::export(with_foreign)]
+pub trait FeedbackListener: Send + Sync {
+    fn on_feedback(&self, feedback: Feedback);
+}
+
+/// A client for native mobile apps using Kotlin or Swift bindings
+///
+/// All methods block the calling thread and should be called from a background thread.
+#[derive(uniffi::Object)]
+pub struct ArsnovaClient {
+    api_url: String,
+    runtime: Runtime,
+    client: RwLock<Option<Arc<Client<LoggedIn>>>>,
+}
+
+impl ArsnovaClient {
+    fn logged_in(&self) -> Result<Arc<Client<LoggedIn>>, ClientError> {
+        self.client
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .ok_or(LoginError)
+    }
+}
+
+#[uniffi::export]
+impl ArsnovaClient {
+    /// Creates a new client for given API URL
+    ///
+    /// This fails whenever the supplied URL cannot be parsed.
+    #[uniffi::constructor]
+    pub fn new(api_url: String) -> Result<Arc<ArsnovaClient>, ClientError> {
+        Client::new(api_url.as_str())?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .map_err(|_| ConnectionError)?;
+        Ok(Arc::new(ArsnovaClient {
+            api_url,
+            runtime,
+            client: RwLock::new(None),
+        }))
+    }
+
+    /// Requests a guest login, replacing a previous login
+    pub fn guest_login(&self) -> Result<(), ClientError> {
+        let client = self
+            .runtime
+            .block_on(Client::new(self.api_url.as_str())?.guest_login())?;
+        *self.client.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(client));
+        Ok(())
+    }
+
+    /// Returns the user ID of the logged in guest
+    pub fn user_id(&self) -> Result<String, ClientError> {
+        Ok(self.logged_in()?.get_user_id()?.to_string())
+    }
+
+    /// Requests the current feedback for given 8-digit room ID
+    pub fn get_feedback(&self, short_id: String) -> Result<Feedback, ClientError> {
+        let client = self.logged_in()?;
+        self.runtime
+            .block_on(client.get_feedback(&ShortId::from(short_id)))
+    }
+
+    /// Sends a feedback value for given 8-digit room ID
+    pub fn send_feedback(&self, short_id: String, value: FeedbackValue) -> Result<(), ClientError> {
+        let client = self.logged_in()?;
+        self.runtime
+            .block_on(client.send_feedback(&ShortId::from(short_id), value))
+    }
+
+    /// Returns the feedback value last sent for given 8-digit room ID
+    pub fn my_feedback(&self, short_id: String) -> Result<Option<FeedbackValue>, ClientError> {
+        Ok(self.logged_in()?.my_feedback(&ShortId::from(short_id)))
+    }
+
+    /// Subscribes to feedback changes for given 8-digit room ID
+    ///
+    /// This method blocks until the subscription ends.
+    pub fn subscribe_feedback(
+        &self,
+        short_id: String,
+        listener: Arc<dyn FeedbackListener>,
+    ) -> Result<(), ClientError> {
+        let client = self.logged_in()?;
+        let short_id = ShortId::from(short_id);
+        let (tx, mut rx) = channel::<Feedback>(10);
+        self.runtime.block_on(async {
+            let notify = async {
+                while let Some(feedback) = rx.recv().await {
+                    listener.on_feedback(feedback);
+                }
+            };
+            tokio::select! {
+                result = client.on_feedback_changed(&short_id, FeedbackHandler::Sender(tx)) => result,
+                _ = notify => Ok(()),
+            }
+        })
+    }
+}
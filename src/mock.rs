@@ -0,0 +1,1219 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::Engine;
+use reqwest::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use reqwest::{Method, Request, Response, StatusCode};
+#[cfg(feature = "websocket")]
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc::UnboundedSender;
+
+#[cfg(feature = "websocket")]
+use futures_util::StreamExt;
+#[cfg(feature = "websocket")]
+use tokio_tungstenite::tungstenite::handshake::client::Request as WsRequest;
+#[cfg(feature = "websocket")]
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+
+#[cfg(feature = "websocket")]
+use crate::client::StompFrame;
+use crate::client::{decode_user_id, ClientError, Feedback, RoomInfo, RoomInfoSettings, RoomStats};
+use crate::ids::{
+    AnnouncementId, AnswerId, AttachmentId, CommentId, ContentGroupId, ContentId, RoomId, ShortId,
+    UserId,
+};
+use crate::models::{
+    Announcement, Answer, AnswerKind, AnswerStatistics, Attachment, CombinatedCount, Comment,
+    Content, ContentGroup, ContentKind, ContentState, FeedbackMode, Membership, Person,
+    RoomExtensions, RoomFocus, RoomMember, RoomRole, RoundStatistics, UserProfile,
+};
+use crate::transport::{BoxFuture, HttpTransport};
+#[cfg(feature = "websocket")]
+use crate::transport::{WsRead, WsTransport, WsWrite};
+
+struct MockRoom {
+    info: RoomInfo,
+    stats: RoomStats,
+    feedback: [u16; 4],
+    votes: HashMap<String, u8>,
+    comments: Vec<Comment>,
+    contents: Vec<Content>,
+    answers: Vec<Answer>,
+    focus: Option<RoomFocus>,
+    announcements: Vec<Announcement>,
+    members: Vec<RoomMember>,
+    attachments: Vec<(Attachment, Vec<u8>)>,
+}
+
+impl MockRoom {
+    /// Returns the content groups of this room, derived from the group names of its contents
+    fn content_groups(&self) -> Vec<ContentGroup> {
+        let mut names = self
+            .contents
+            .iter()
+            .flat_map(|content| content.groups.iter())
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        names
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| ContentGroup {
+                id: ContentGroupId::new(format!("{:032x}", index + 1)),
+                room_id: self.info.id.clone(),
+                name: name.clone(),
+                content_ids: self
+                    .contents
+                    .iter()
+                    .filter(|content| content.groups.contains(name))
+                    .map(|content| content.id.clone())
+                    .collect(),
+                published: true,
+            })
+            .collect()
+    }
+
+    /// Returns the answer statistics of given content, counting selected options and wordcloud terms
+    ///
+    /// Wordcloud terms banned by a moderator are not counted.
+    fn answer_stats(&self, content_id: &ContentId) -> AnswerStatistics {
+        let banned = self
+            .contents
+            .iter()
+            .find(|content| &content.id == content_id)
+            .map(|content| match &content.kind {
+                ContentKind::Wordcloud {
+                    banned_keywords, ..
+                } => banned_keywords.clone(),
+                _ => vec![],
+            })
+            .unwrap_or_default();
+        let mut rounds: Vec<RoundStatistics> = vec![];
+        for answer in self
+            .answers
+            .iter()
+            .filter(|answer| &answer.content_id == content_id)
+        {
+            let index = match rounds.iter().position(|stats| stats.round == answer.round) {
+                Some(index) => index,
+                None => {
+                    rounds.push(RoundStatistics {
+                        round: answer.round,
+                        ..RoundStatistics::default()
+                    });
+                    rounds.len() - 1
+                }
+            };
+            let stats = &mut rounds[index];
+            stats.answer_count += 1;
+            match &answer.kind {
+                AnswerKind::Choice {
+                    selected_choice_indexes,
+                } => {
+                    for selected in selected_choice_indexes {
+                        if stats.independent_counts.len() <= *selected {
+                            stats.independent_counts.resize(selected + 1, 0);
+                        }
+                        stats.independent_counts[*selected] += 1;
+                    }
+                    match stats.combinated_counts.iter_mut().find(|combination| {
+                        &combination.selected_choice_indexes == selected_choice_indexes
+                    }) {
+                        Some(combination) => combination.count += 1,
+                        None => stats.combinated_counts.push(CombinatedCount {
+                            selected_choice_indexes: selected_choice_indexes.clone(),
+                            count: 1,
+                        }),
+                    }
+                }
+                AnswerKind::Multiple { texts } => {
+                    for text in texts.iter().filter(|text| !banned.contains(text)) {
+                        match stats.texts.iter().position(|term| term == text) {
+                            Some(index) => stats.counts[index] += 1,
+                            None => {
+                                stats.texts.push(text.clone());
+                                stats.counts.push(1);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        rounds.sort_by_key(|stats| stats.round);
+        AnswerStatistics {
+            content_id: content_id.clone(),
+            round_statistics: rounds,
+        }
+    }
+}
+
+struct MockSubscriber {
+    destination: String,
+    subscription_id: String,
+    sender: UnboundedSender<String>,
+}
+
+#[derive(Default)]
+struct MockState {
+    rooms: Vec<MockRoom>,
+    features: HashMap<String, bool>,
+    guests: usize,
+    connections: usize,
+    persons: HashMap<UserId, Person>,
+    subscribers: Vec<MockSubscriber>,
+}
+
+impl MockState {
+    fn room_mut(&mut self, short_id: &ShortId) -> Option<&mut MockRoom> {
+        self.rooms
+            .iter_mut()
+            .find(|room| &room.info.short_id == short_id)
+    }
+
+    /// Adds an open room with given short ID and name and returns its room information
+    fn add_room(&mut self, short_id: &ShortId, name: &str) -> RoomInfo {
+        let info = RoomInfo {
+            id: RoomId::new(format!("{:032x}", self.rooms.len() + 1)),
+            short_id: short_id.clone(),
+            name: name.to_string(),
+            description: String::new(),
+            closed: false,
+            settings: RoomInfoSettings {
+                feedback_locked: false,
+            },
+            extensions: RoomExtensions::default(),
+        };
+        self.rooms.push(MockRoom {
+            info: info.clone(),
+            stats: RoomStats {
+                content_count: 0,
+                ack_comment_count: 0,
+                room_user_count: 0,
+            },
+            feedback: [0; 4],
+            votes: HashMap::new(),
+            comments: vec![],
+            contents: vec![],
+            answers: vec![],
+            focus: None,
+            announcements: vec![],
+            members: vec![],
+            attachments: vec![],
+        });
+        info
+    }
+
+    /// Adds given comment to given room and notifies all subscribers of its comment stream
+    fn add_comment(&mut self, room_id: &RoomId, comment: Comment) -> Option<Comment> {
+        let room = self
+            .rooms
+            .iter_mut()
+            .find(|room| &room.info.id == room_id)?;
+        let comment = Comment {
+            id: CommentId::new(format!("{:032x}", room.comments.len() + 1)),
+            room_id: room_id.clone(),
+            ..comment
+        };
+        room.comments.push(comment.clone());
+        let body = json!({
+            "type": "CommentCreated",
+            "payload": comment
+        })
+        .to_string();
+        self.broadcast(&format!("/topic/{}.comment.stream", room_id), &body);
+        Some(comment)
+    }
+
+    /// Adds given content to given room and returns it with its assigned ID
+    fn add_content(&mut self, room_id: &RoomId, content: Content) -> Option<Content> {
+        let room = self
+            .rooms
+            .iter_mut()
+            .find(|room| &room.info.id == room_id)?;
+        let content = Content {
+            id: ContentId::new(format!("{:032x}", room.contents.len() + 1)),
+            room_id: room_id.clone(),
+            ..content
+        };
+        room.contents.push(content.clone());
+        Some(content)
+    }
+
+    /// Sends the current feedback of given room to all subscribers of its feedback stream
+    fn broadcast_feedback(&mut self, room_id: &RoomId) {
+        let Some(room) = self.rooms.iter().find(|room| &room.info.id == room_id) else {
+            return;
+        };
+        let body = json!({
+            "type": "FeedbackChanged",
+            "payload": {
+                "values": room.feedback
+            }
+        })
+        .to_string();
+
+        self.broadcast(&format!("/topic/{}.feedback.stream", room_id), &body);
+    }
+
+    /// Changes the state of given content and notifies all subscribers of the room stream
+    fn update_content_state(
+        &mut self,
+        room_id: &RoomId,
+        content_id: &ContentId,
+        content_state: ContentState,
+    ) -> Option<Content> {
+        let content = self
+            .rooms
+            .iter_mut()
+            .find(|room| &room.info.id == room_id)?
+            .contents
+            .iter_mut()
+            .find(|content| &content.id == content_id)?;
+        content.state = content_state.clone();
+        let content = content.clone();
+        let body = json!({
+            "type": "ContentStateChanged",
+            "payload": {
+                "id": content_id,
+                "state": content_state
+            }
+        })
+        .to_string();
+        self.broadcast(&format!("/topic/{}.stream", room_id), &body);
+        Some(content)
+    }
+
+    /// Sends given message body to all subscribers of given destination
+    fn broadcast(&mut self, destination: &str, body: &str) {
+        self.subscribers.retain(|subscriber| {
+            if subscriber.destination != destination {
+                return true;
+            }
+            let frame = format!(
+                "MESSAGE\ndestination:{}\nsubscription:{}\ncontent-type:application/json\ncontent-length:{}\n\n{}\0",
+                destination,
+                subscriber.subscription_id,
+                body.len(),
+                body
+            );
+            subscriber.sender.send(frame).is_ok()
+        });
+    }
+}
+
+#[cfg(feature = "websocket")]
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+#[allow(clippy::enum_variant_names)]
+enum MockCommand {
+    CreateFeedback { payload: MockCreateFeedbackPayload },
+    ResetFeedback { payload: MockRoomPayload },
+    StartFeedback { payload: MockRoomPayload },
+    StopFeedback { payload: MockRoomPayload },
+}
+
+#[cfg(feature = "websocket")]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MockCreateFeedbackPayload {
+    room_id: RoomId,
+    user_id: String,
+    value: u8,
+}
+
+#[cfg(feature = "websocket")]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MockRoomPayload {
+    room_id: RoomId,
+}
+
+/// An in-process mock of an ARSnova server for integration tests of applications
+///
+/// The mock server answers the HTTP requests used by the client (guest login, user profiles,
+/// room creation, membership and members, room information and statistics, comments, contents,
+/// content attachments, content groups, answers and their statistics, announcements, presenter
+/// focus and feedback) and, using the `websocket` feature, provides feedback, comment, content,
+/// announcement and focus streams.
+/// Feedback sent by clients is counted once per user and propagated to all subscribers of the room.
+/// Use `ClientBuilder::mock_server()` to connect a client.
+///
+/// Clones share the same state, so rooms and feedback can be changed while clients are connected.
+#[derive(Clone, Default)]
+pub struct MockServer {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockServer {
+    /// Creates a mock server without any rooms
+    pub fn new() -> MockServer {
+        MockServer::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Adds an open room with given short ID and name and returns its room information
+    pub fn add_room(&self, short_id: &ShortId, name: &str) -> RoomInfo {
+        self.state().add_room(short_id, name)
+    }
+
+    /// Locks or unlocks feedback of given room and notifies all subscribers
+    ///
+    /// Feedback sent to a locked room is ignored.
+    pub fn set_feedback_locked(&self, short_id: &ShortId, locked: bool) {
+        let room_id = self
+            .state()
+            .room_mut(short_id)
+            .map(|room| room.info.id.clone());
+        if let Some(room_id) = room_id {
+            self.lock_feedback(&room_id, locked);
+        }
+    }
+
+    /// Sets the feedback mode of given room and notifies all subscribers
+    pub fn set_feedback_mode(&self, short_id: &ShortId, mode: FeedbackMode) {
+        let mut state = self.state();
+        let Some(room) = state.room_mut(short_id) else {
+            return;
+        };
+        room.info.extensions.feedback.mode = mode.clone();
+        let destination = format!("/topic/{}.feedback.stream", room.info.id);
+        let body = json!({
+            "type": "FeedbackTypeChanged",
+            "payload": { "type": mode }
+        })
+        .to_string();
+        state.broadcast(&destination, &body);
+    }
+
+    /// Sets the statistics returned for given room
+    pub fn set_room_stats(&self, short_id: &ShortId, stats: RoomStats) {
+        if let Some(room) = self.state().room_mut(short_id) {
+            room.stats = stats;
+        }
+    }
+
+    /// Replaces the feedback of given room and notifies all subscribers
+    pub fn set_feedback(&self, short_id: &ShortId, feedback: Feedback) {
+        let mut state = self.state();
+        let Some(room) = state.room_mut(short_id) else {
+            return;
+        };
+        room.feedback = [
+            feedback.very_good,
+            feedback.good,
+            feedback.bad,
+            feedback.very_bad,
+        ];
+        room.votes.clear();
+        let room_id = room.info.id.clone();
+        state.broadcast_feedback(&room_id);
+    }
+
+    /// Returns the current feedback of given room
+    pub fn feedback(&self, short_id: &ShortId) -> Option<Feedback> {
+        self.state()
+            .room_mut(short_id)
+            .map(|room| Feedback::from_values(room.feedback))
+    }
+
+    /// Adds an acknowledged comment with given body to given room and notifies all subscribers
+    pub fn add_comment(&self, short_id: &ShortId, body: &str) -> Option<Comment> {
+        let mut state = self.state();
+        let room_id = state.room_mut(short_id)?.info.id.clone();
+        let comment = Comment {
+            body: body.to_string(),
+            ack: true,
+            ..Comment::default()
+        };
+        state.add_comment(&room_id, comment)
+    }
+
+    /// Adds given content to given room and returns it with its assigned ID
+    pub fn add_content(&self, short_id: &ShortId, content: Content) -> Option<Content> {
+        let mut state = self.state();
+        let room_id = state.room_mut(short_id)?.info.id.clone();
+        state.add_content(&room_id, content)
+    }
+
+    /// Changes the state of given content, e.g. to publish it, and notifies all subscribers
+    pub fn set_content_state(
+        &self,
+        short_id: &ShortId,
+        content_id: &ContentId,
+        content_state: ContentState,
+    ) {
+        let mut state = self.state();
+        let Some(room_id) = state.room_mut(short_id).map(|room| room.info.id.clone()) else {
+            return;
+        };
+        state.update_content_state(&room_id, content_id, content_state);
+    }
+
+    /// Returns what the presenter currently shows in given room
+    pub fn focus(&self, short_id: &ShortId) -> Option<RoomFocus> {
+        self.state().room_mut(short_id)?.focus.clone()
+    }
+
+    /// Returns all answers given to given content
+    pub fn answers(&self, short_id: &ShortId, content_id: &ContentId) -> Vec<Answer> {
+        self.state()
+            .room_mut(short_id)
+            .map(|room| {
+                room.answers
+                    .iter()
+                    .filter(|answer| &answer.content_id == content_id)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Enables or disables given feature in the server configuration, e.g. `comments`
+    pub fn set_feature_enabled(&self, feature: &str, enabled: bool) {
+        self.state().features.insert(feature.to_string(), enabled);
+    }
+
+    /// Returns the number of guest logins
+    pub fn guest_logins(&self) -> usize {
+        self.state().guests
+    }
+
+    /// Returns the number of STOMP sessions connected by clients
+    pub fn connections(&self) -> usize {
+        self.state().connections
+    }
+
+    /// Closes all websocket connections with subscriptions, e.g. to test reconnects
+    pub fn close_connections(&self) {
+        for subscriber in self.state().subscribers.drain(..) {
+            let _ = subscriber.sender.send(String::new());
+        }
+    }
+
+    /// Locks or unlocks the feedback of given room and notifies all subscribers
+    fn lock_feedback(&self, room_id: &RoomId, locked: bool) {
+        let mut state = self.state();
+        let Some(room) = state.rooms.iter_mut().find(|room| &room.info.id == room_id) else {
+            return;
+        };
+        room.info.settings.feedback_locked = locked;
+        let body = json!({
+            "type": if locked { "FeedbackStopped" } else { "FeedbackStarted" },
+            "payload": {}
+        })
+        .to_string();
+        state.broadcast(&format!("/topic/{}.feedback.stream", room_id), &body);
+    }
+
+    /// Removes all votes of given room and notifies all subscribers
+    #[cfg(feature = "websocket")]
+    fn reset_votes(&self, room_id: &RoomId) {
+        let mut state = self.state();
+        let Some(room) = state.rooms.iter_mut().find(|room| &room.info.id == room_id) else {
+            return;
+        };
+        room.feedback = [0; 4];
+        room.votes.clear();
+        let body = json!({ "type": "FeedbackReset", "payload": {} }).to_string();
+        state.broadcast(&format!("/topic/{}.feedback.stream", room_id), &body);
+        state.broadcast_feedback(room_id);
+    }
+
+    /// Counts a feedback vote of given user, replacing a previous vote of this user
+    #[cfg_attr(not(feature = "websocket"), allow(dead_code))]
+    fn vote(&self, room_id: &RoomId, user_id: &str, value: u8) {
+        let mut state = self.state();
+        let Some(room) = state.rooms.iter_mut().find(|room| &room.info.id == room_id) else {
+            return;
+        };
+        if room.info.is_feedback_locked() || value > 3 {
+            return;
+        }
+        if let Some(previous) = room.votes.insert(user_id.to_string(), value) {
+            room.feedback[previous as usize] = room.feedback[previous as usize].saturating_sub(1);
+        }
+        room.feedback[value as usize] = room.feedback[value as usize].saturating_add(1);
+        state.broadcast_feedback(room_id);
+    }
+
+    /// Returns the response to given request
+    ///
+    /// Unknown endpoints and rooms result in a response with status `404 Not Found`.
+    fn http_response(&self, request: &Request) -> Response {
+        let path = request.url().path();
+        let path = path
+            .split_once("/api/")
+            .map_or(path.trim_start_matches('/'), |(_, path)| path);
+        let segments = path.split('/').collect::<Vec<_>>();
+        let mut state = self.state();
+
+        if let ("GET", ["room", room_id, "content", content_id, "attachment", attachment_id]) =
+            (request.method().as_str(), segments.as_slice())
+        {
+            if !attachment_id.is_empty() {
+                let attachment = state
+                    .rooms
+                    .iter()
+                    .find(|room| room.info.id.as_str() == *room_id)
+                    .and_then(|room| {
+                        room.attachments.iter().find(|(attachment, _)| {
+                            attachment.content_id.as_str() == *content_id
+                                && attachment.id.as_str() == *attachment_id
+                        })
+                    });
+                let response = match attachment {
+                    Some((attachment, data)) => {
+                        let mut response = http::Response::new(data.clone());
+                        if let Ok(media_type) = HeaderValue::from_str(&attachment.media_type) {
+                            response.headers_mut().insert(CONTENT_TYPE, media_type);
+                        }
+                        response
+                    }
+                    None => {
+                        let mut response = http::Response::new(vec![]);
+                        *response.status_mut() = StatusCode::NOT_FOUND;
+                        response
+                    }
+                };
+                return Response::from(response);
+            }
+        }
+
+        let body = match (request.method().as_str(), segments.as_slice()) {
+            ("GET", [""]) => Some(json!({
+                "productName": "arsnova-client-mock",
+                "version": env!("CARGO_PKG_VERSION")
+            })),
+            ("GET", ["configuration", ""]) => {
+                let features = state
+                    .features
+                    .iter()
+                    .map(|(feature, enabled)| (feature.clone(), json!({ "enabled": enabled })))
+                    .collect::<serde_json::Map<_, _>>();
+                Some(json!({
+                    "features": features,
+                    "authenticationProviders": [{ "id": "guest", "type": "ANONYMOUS" }]
+                }))
+            }
+            ("POST", ["auth", "login", "guest"]) => {
+                state.guests += 1;
+                let claim = json!({ "sub": format!("guest-{}", state.guests) }).to_string();
+                Some(json!({
+                    "token": format!(
+                        "{}.{}.mock",
+                        STANDARD_NO_PAD.encode(r#"{"alg":"none"}"#),
+                        STANDARD_NO_PAD.encode(claim)
+                    )
+                }))
+            }
+            ("POST", ["room", short_id, "request-membership"]) => {
+                let user_id = request
+                    .headers()
+                    .get(AUTHORIZATION)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.strip_prefix("Bearer "))
+                    .and_then(|token| decode_user_id(token).ok());
+                short_id
+                    .strip_prefix('~')
+                    .and_then(|short_id| state.room_mut(&ShortId::from(short_id)))
+                    .map(|room| {
+                        if let Some(user_id) = user_id {
+                            if !room.members.iter().any(|member| member.user_id == user_id) {
+                                room.members.push(RoomMember {
+                                    user_id,
+                                    roles: vec![RoomRole::Participant],
+                                    ..RoomMember::default()
+                                });
+                            }
+                        }
+                        json!({})
+                    })
+            }
+            ("GET", ["_view", "membership", "by-user", user_id]) => {
+                let user_id = UserId::from(*user_id);
+                let memberships = state
+                    .rooms
+                    .iter()
+                    .filter_map(|room| {
+                        let member = room
+                            .members
+                            .iter()
+                            .find(|member| member.user_id == user_id)?;
+                        Some(Membership {
+                            room_id: room.info.id.clone(),
+                            room_short_id: room.info.short_id.clone(),
+                            roles: member.roles.clone(),
+                            last_visit: member.last_visit.clone(),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                Some(json!(memberships))
+            }
+            ("GET", ["room", room_id, "member"]) => state
+                .rooms
+                .iter()
+                .find(|room| room.info.id.as_str() == *room_id)
+                .map(|room| json!(room.members)),
+            ("GET", ["room", short_id]) => short_id
+                .strip_prefix('~')
+                .and_then(|short_id| state.room_mut(&ShortId::from(short_id)))
+                .map(|room| json!(room.info)),
+            ("GET", ["room", room_id, "survey"]) => state
+                .rooms
+                .iter()
+                .find(|room| room.info.id.as_str() == *room_id)
+                .map(|room| json!(room.feedback)),
+            ("POST", ["room", room_id, "comment", "find"]) => state
+                .rooms
+                .iter()
+                .find(|room| room.info.id.as_str() == *room_id)
+                .map(|room| json!(room.comments)),
+            ("GET", ["room", room_id, "content", content_id, "attachment", ""]) => state
+                .rooms
+                .iter()
+                .find(|room| room.info.id.as_str() == *room_id)
+                .filter(|room| {
+                    room.contents
+                        .iter()
+                        .any(|content| content.id.as_str() == *content_id)
+                })
+                .map(|room| {
+                    let attachments = room
+                        .attachments
+                        .iter()
+                        .filter(|(attachment, _)| attachment.content_id.as_str() == *content_id)
+                        .map(|(attachment, _)| attachment)
+                        .collect::<Vec<_>>();
+                    json!(attachments)
+                }),
+            ("POST", ["room", room_id, "content", content_id, "attachment", ""]) => {
+                let name = request
+                    .url()
+                    .query_pairs()
+                    .find(|(name, _)| name == "name")
+                    .map(|(_, value)| value.to_string())
+                    .unwrap_or_default();
+                let media_type = request
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let data = request
+                    .body()
+                    .and_then(|body| body.as_bytes())
+                    .unwrap_or_default()
+                    .to_vec();
+                state
+                    .rooms
+                    .iter_mut()
+                    .find(|room| room.info.id.as_str() == *room_id)
+                    .filter(|room| {
+                        room.contents
+                            .iter()
+                            .any(|content| content.id.as_str() == *content_id)
+                    })
+                    .map(|room| {
+                        let attachment = Attachment {
+                            id: AttachmentId::new(format!("{:032x}", room.attachments.len() + 1)),
+                            content_id: ContentId::from(*content_id),
+                            name,
+                            media_type,
+                            size: data.len() as u64,
+                        };
+                        room.attachments.push((attachment.clone(), data));
+                        json!(attachment)
+                    })
+            }
+            ("GET", ["room", room_id, "content", content_id]) => state
+                .rooms
+                .iter()
+                .find(|room| room.info.id.as_str() == *room_id)
+                .and_then(|room| {
+                    room.contents
+                        .iter()
+                        .find(|content| content.id.as_str() == *content_id)
+                })
+                .map(|content| json!(content)),
+            ("PATCH", ["room", room_id, "content", content_id]) => request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .and_then(|body| serde_json::from_slice::<Value>(body).ok())
+                .and_then(|body| serde_json::from_value::<ContentState>(body["state"].clone()).ok())
+                .and_then(|content_state| {
+                    state.update_content_state(
+                        &RoomId::new(room_id.to_string()),
+                        &ContentId::new(content_id.to_string()),
+                        content_state,
+                    )
+                })
+                .map(|content| json!(content)),
+            ("POST", ["room", room_id, "focus-event"]) => {
+                let focus = request
+                    .body()
+                    .and_then(|body| body.as_bytes())
+                    .and_then(|body| serde_json::from_slice::<RoomFocus>(body).ok());
+                let room = state
+                    .rooms
+                    .iter_mut()
+                    .find(|room| room.info.id.as_str() == *room_id);
+                match (room, focus) {
+                    (Some(room), Some(focus)) => {
+                        room.focus = Some(focus.clone());
+                        let destination = format!("/topic/{}.focus.state.stream", room_id);
+                        state.broadcast(&destination, &json!(focus).to_string());
+                        Some(json!({}))
+                    }
+                    _ => None,
+                }
+            }
+            ("GET", ["room", room_id, "announcement", ""]) => state
+                .rooms
+                .iter()
+                .find(|room| room.info.id.as_str() == *room_id)
+                .map(|room| json!(room.announcements)),
+            ("POST", ["room", room_id, "announcement", ""]) => {
+                let announcement = request
+                    .body()
+                    .and_then(|body| body.as_bytes())
+                    .and_then(|body| serde_json::from_slice::<Announcement>(body).ok());
+                let count = state
+                    .rooms
+                    .iter()
+                    .map(|room| room.announcements.len())
+                    .sum::<usize>();
+                let room = state
+                    .rooms
+                    .iter_mut()
+                    .find(|room| room.info.id.as_str() == *room_id);
+                match (room, announcement) {
+                    (Some(room), Some(announcement)) => {
+                        let announcement = Announcement {
+                            id: AnnouncementId::new(format!("{:032x}", count + 1)),
+                            ..announcement
+                        };
+                        room.announcements.push(announcement.clone());
+                        let body = json!({
+                            "type": "AnnouncementCreated",
+                            "payload": announcement
+                        })
+                        .to_string();
+                        state.broadcast(&format!("/topic/{}.stream", room_id), &body);
+                        Some(json!(announcement))
+                    }
+                    _ => None,
+                }
+            }
+            ("GET", ["user", user_id]) => {
+                let user_id = UserId::from(*user_id);
+                let person = state.persons.get(&user_id).cloned().unwrap_or_default();
+                Some(json!(UserProfile {
+                    id: user_id,
+                    login_id: String::new(),
+                    person,
+                }))
+            }
+            ("GET", ["user", user_id, "export"]) => {
+                let user_id = UserId::from(*user_id);
+                let person = state.persons.get(&user_id).cloned().unwrap_or_default();
+                let rooms = state
+                    .rooms
+                    .iter()
+                    .filter(|room| room.members.iter().any(|member| member.user_id == user_id))
+                    .map(|room| &room.info.id)
+                    .collect::<Vec<_>>();
+                Some(json!({
+                    "profile": UserProfile {
+                        id: user_id.clone(),
+                        login_id: String::new(),
+                        person,
+                    },
+                    "rooms": rooms
+                }))
+            }
+            ("DELETE", ["user", user_id]) => request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .and_then(|body| serde_json::from_slice::<Value>(body).ok())
+                .filter(|body| {
+                    body["confirmationToken"]
+                        .as_str()
+                        .is_some_and(|token| !token.is_empty())
+                })
+                .map(|_| {
+                    let user_id = UserId::from(*user_id);
+                    state.persons.remove(&user_id);
+                    state
+                        .rooms
+                        .iter_mut()
+                        .for_each(|room| room.members.retain(|member| member.user_id != user_id));
+                    json!({})
+                }),
+            ("PATCH", ["user", user_id]) => request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .and_then(|body| serde_json::from_slice::<Value>(body).ok())
+                .and_then(|body| serde_json::from_value::<Person>(body["person"].clone()).ok())
+                .map(|person| {
+                    let user_id = UserId::from(*user_id);
+                    state.persons.insert(user_id.clone(), person.clone());
+                    json!(UserProfile {
+                        id: user_id,
+                        login_id: String::new(),
+                        person,
+                    })
+                }),
+            ("GET", ["user", _, "announcement-state"]) => {
+                let count = state
+                    .rooms
+                    .iter()
+                    .map(|room| room.announcements.len())
+                    .sum::<usize>();
+                Some(json!({ "total": count, "new": count }))
+            }
+            ("POST", ["room", ""]) => request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .and_then(|body| serde_json::from_slice::<RoomInfo>(body).ok())
+                .map(|room| {
+                    let short_id = ShortId::new(format!("{:08}", 90000000 + state.rooms.len() + 1));
+                    let info = state.add_room(&short_id, &room.name);
+                    let room_info = state.room_mut(&short_id).map(|mock_room| {
+                        mock_room.info = RoomInfo {
+                            id: info.id,
+                            short_id,
+                            ..room
+                        };
+                        mock_room.info.clone()
+                    });
+                    json!(room_info)
+                }),
+            ("POST", ["room", room_id, "content", ""]) => request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .and_then(|body| serde_json::from_slice::<Content>(body).ok())
+                .and_then(|content| state.add_content(&RoomId::from(*room_id), content))
+                .map(|content| json!(content)),
+            ("POST", ["room", room_id, "contentgroup", ""]) => {
+                let group = request
+                    .body()
+                    .and_then(|body| body.as_bytes())
+                    .and_then(|body| serde_json::from_slice::<ContentGroup>(body).ok());
+                state
+                    .rooms
+                    .iter_mut()
+                    .find(|room| room.info.id.as_str() == *room_id)
+                    .zip(group)
+                    .map(|(room, group)| {
+                        for content in room
+                            .contents
+                            .iter_mut()
+                            .filter(|content| group.content_ids.contains(&content.id))
+                        {
+                            if !content.groups.contains(&group.name) {
+                                content.groups.push(group.name.clone());
+                            }
+                        }
+                        room.content_groups()
+                            .into_iter()
+                            .find(|created| created.name == group.name)
+                            .unwrap_or(group)
+                    })
+                    .map(|group| json!(group))
+            }
+            ("POST", ["room", room_id, "comment", ""]) => request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .and_then(|body| serde_json::from_slice::<Comment>(body).ok())
+                .and_then(|comment| state.add_comment(&RoomId::from(*room_id), comment))
+                .map(|comment| json!(comment)),
+            ("GET", ["room", room_id, "contentgroup", ""]) => state
+                .rooms
+                .iter()
+                .find(|room| room.info.id.as_str() == *room_id)
+                .map(|room| json!(room.content_groups())),
+            ("GET", ["room", room_id, "contentgroup", "-", "names"]) => state
+                .rooms
+                .iter()
+                .find(|room| room.info.id.as_str() == *room_id)
+                .map(|room| {
+                    let groups = room.content_groups();
+                    json!(groups.iter().map(|group| &group.name).collect::<Vec<_>>())
+                }),
+            ("GET", ["room", room_id, "content", content_id, "stats"]) => state
+                .rooms
+                .iter()
+                .find(|room| room.info.id.as_str() == *room_id)
+                .filter(|room| {
+                    room.contents
+                        .iter()
+                        .any(|content| content.id.as_str() == *content_id)
+                })
+                .map(|room| json!(room.answer_stats(&ContentId::from(*content_id)))),
+            ("GET", ["room", room_id, "content", content_id, "myanswer"]) => {
+                let user_id = request
+                    .headers()
+                    .get(AUTHORIZATION)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.strip_prefix("Bearer "))
+                    .and_then(|token| decode_user_id(token).ok());
+                state
+                    .rooms
+                    .iter()
+                    .find(|room| room.info.id.as_str() == *room_id)
+                    .and_then(|room| {
+                        room.contents
+                            .iter()
+                            .find(|content| content.id.as_str() == *content_id)
+                            .map(|content| (room, content.state.round.max(1)))
+                    })
+                    .map(|(room, round)| {
+                        let answer = room.answers.iter().rev().find(|answer| {
+                            answer.content_id.as_str() == *content_id
+                                && answer.round == round
+                                && Some(&answer.creator_id) == user_id.as_ref()
+                        });
+                        json!(answer)
+                    })
+            }
+            (
+                method @ ("POST" | "DELETE"),
+                ["room", room_id, "content", content_id, "banned-keywords"],
+            ) => {
+                let keyword = request
+                    .body()
+                    .and_then(|body| body.as_bytes())
+                    .and_then(|body| serde_json::from_slice::<Value>(body).ok())
+                    .and_then(|body| body["keyword"].as_str().map(str::to_string));
+                state
+                    .rooms
+                    .iter_mut()
+                    .find(|room| room.info.id.as_str() == *room_id)
+                    .and_then(|room| {
+                        room.contents
+                            .iter_mut()
+                            .find(|content| content.id.as_str() == *content_id)
+                    })
+                    .zip(keyword)
+                    .and_then(|(content, keyword)| match &mut content.kind {
+                        ContentKind::Wordcloud {
+                            banned_keywords, ..
+                        } => {
+                            banned_keywords.retain(|banned| banned != &keyword);
+                            if method == "POST" {
+                                banned_keywords.push(keyword);
+                            }
+                            Some(json!({}))
+                        }
+                        _ => None,
+                    })
+            }
+            ("DELETE", ["room", room_id, "content", content_id, "answer"]) => state
+                .rooms
+                .iter_mut()
+                .find(|room| room.info.id.as_str() == *room_id)
+                .filter(|room| {
+                    room.contents
+                        .iter()
+                        .any(|content| content.id.as_str() == *content_id)
+                })
+                .map(|room| {
+                    room.answers
+                        .retain(|answer| answer.content_id.as_str() != *content_id);
+                    json!({})
+                }),
+            ("POST", ["room", room_id, "answer", ""]) => {
+                let answer = request
+                    .body()
+                    .and_then(|body| body.as_bytes())
+                    .and_then(|body| serde_json::from_slice::<Answer>(body).ok());
+                state
+                    .rooms
+                    .iter_mut()
+                    .find(|room| room.info.id.as_str() == *room_id)
+                    .zip(answer)
+                    .map(|(room, answer)| {
+                        let answer = Answer {
+                            id: AnswerId::new(format!("{:032x}", room.answers.len() + 1)),
+                            ..answer
+                        };
+                        room.answers.push(answer.clone());
+                        json!(answer)
+                    })
+            }
+            ("GET", ["_view", "room", "summary"]) => {
+                let ids = request
+                    .url()
+                    .query_pairs()
+                    .find(|(key, _)| key == "ids")
+                    .map(|(_, ids)| ids.to_string())
+                    .unwrap_or_default();
+                let summaries = ids
+                    .split(',')
+                    .filter_map(|id| state.rooms.iter().find(|room| room.info.id.as_str() == id))
+                    .map(|room| json!({ "id": room.info.id, "stats": room.stats }))
+                    .collect::<Vec<_>>();
+                Some(Value::Array(summaries))
+            }
+            _ => None,
+        };
+
+        let response = match body {
+            Some(body) if request.method() == Method::GET => {
+                let body = body.to_string();
+                let mut hasher = DefaultHasher::new();
+                body.hash(&mut hasher);
+                let etag = format!("\"{:016x}\"", hasher.finish());
+                let not_modified = request
+                    .headers()
+                    .get(IF_NONE_MATCH)
+                    .is_some_and(|value| value.as_bytes() == etag.as_bytes());
+                let mut response = match not_modified {
+                    true => {
+                        let mut response = http::Response::new(String::new());
+                        *response.status_mut() = StatusCode::NOT_MODIFIED;
+                        response
+                    }
+                    false => http::Response::new(body),
+                };
+                response
+                    .headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                if let Ok(etag) = HeaderValue::from_str(&etag) {
+                    response.headers_mut().insert(ETAG, etag);
+                }
+                response
+            }
+            Some(body) => {
+                let mut response = http::Response::new(body.to_string());
+                response
+                    .headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                response
+            }
+            None => {
+                let mut response = http::Response::new(String::new());
+                *response.status_mut() = StatusCode::NOT_FOUND;
+                response
+            }
+        };
+        Response::from(response)
+    }
+
+    /// Handles a STOMP frame received from a client connected using given sender
+    #[cfg(feature = "websocket")]
+    fn handle_frame(&self, raw: &str, sender: &UnboundedSender<String>) {
+        let Some(frame) = StompFrame::parse(raw) else {
+            return;
+        };
+        match frame.command {
+            "CONNECT" | "STOMP" => {
+                self.state().connections += 1;
+                let _ = sender.send("CONNECTED\nversion:1.2\nheart-beat:0,0\n\n\0".to_string());
+            }
+            "SUBSCRIBE" => {
+                let destination = frame
+                    .header("destination")
+                    .filter(|destination| destination.starts_with("/topic/"));
+                if let (Some(destination), Some(subscription_id)) =
+                    (destination, frame.header("id"))
+                {
+                    self.state().subscribers.push(MockSubscriber {
+                        destination: destination.to_string(),
+                        subscription_id: subscription_id.to_string(),
+                        sender: sender.clone(),
+                    });
+                }
+            }
+            "SEND"
+                if frame.header("destination").is_some_and(|destination| {
+                    destination.starts_with("/queue/feedback.command")
+                }) =>
+            {
+                match serde_json::from_str::<MockCommand>(frame.body) {
+                    Ok(MockCommand::CreateFeedback { payload }) => {
+                        self.vote(&payload.room_id, &payload.user_id, payload.value)
+                    }
+                    Ok(MockCommand::ResetFeedback { payload }) => {
+                        self.reset_votes(&payload.room_id)
+                    }
+                    Ok(MockCommand::StartFeedback { payload }) => {
+                        self.lock_feedback(&payload.room_id, false)
+                    }
+                    Ok(MockCommand::StopFeedback { payload }) => {
+                        self.lock_feedback(&payload.room_id, true)
+                    }
+                    Err(_) => {}
+                }
+            }
+            "DISCONNECT" => {
+                self.state()
+                    .subscribers
+                    .retain(|subscriber| !subscriber.sender.same_channel(sender));
+                if let Some(receipt) = frame.header("receipt") {
+                    let _ = sender.send(format!("RECEIPT\nreceipt-id:{}\n\n\0", receipt));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl HttpTransport for MockServer {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response, ClientError>> {
+        Box::pin(async move { Ok(self.http_response(&request)) })
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl WsTransport for MockServer {
+    fn connect(&self, _: WsRequest) -> BoxFuture<'_, Result<(WsWrite, WsRead), ClientError>> {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel::<String>();
+        let server = self.clone();
+        Box::pin(async move {
+            // An empty frame closes the connection
+            let read = futures_util::stream::unfold(receiver, |mut receiver| async move {
+                receiver
+                    .recv()
+                    .await
+                    .filter(|frame| !frame.is_empty())
+                    .map(|frame| (Ok(Message::Text(frame)), receiver))
+            })
+            .boxed();
+            let write = futures_util::sink::unfold(sender, move |sender, message: Message| {
+                if let Message::Text(text) = &message {
+                    server.handle_frame(text, &sender);
+                }
+                async move { Ok::<_, WsError>(sender) }
+            });
+            Ok((Box::pin(write) as WsWrite, read as WsRead))
+        })
+    }
+}
@@ -0,0 +1,984 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Typed models of objects returned by the ARSnova/Particify API
+//!
+//! All models are tolerant to schema changes: unknown fields are ignored, missing fields use
+//! their default value and unknown enum values are mapped to an `Unknown` variant. Fields known
+//! to be `null` in some server versions are deserialized as their default value.
+
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::ids::{
+    AnnouncementId, AnswerId, AttachmentId, CommentId, ContentGroupId, ContentId, RoomId, ShortId,
+    UserId,
+};
+
+/// Deserializes `null` as default value
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Deserializes the format specific part of a content or answer, using the default `Unknown`
+/// variant if the format is missing, unknown or its fields cannot be parsed
+fn kind_or_unknown<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + DeserializeOwned,
+{
+    Ok(T::deserialize(Value::deserialize(deserializer)?).unwrap_or_default())
+}
+
+/// The product name and version of an ARSnova/Particify server
+///
+/// Both are `None` if the server does not provide version information.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServerVersion {
+    pub product: Option<String>,
+    pub version: Option<String>,
+}
+
+impl ServerVersion {
+    /// Detects product name and version in the response of the API root
+    ///
+    /// Server versions differ in the field names used, e.g. `productName` or `name`, and
+    /// provide the version as string or within an object.
+    pub fn from_value(value: &Value) -> ServerVersion {
+        let string = |names: &[&str], value: &Value| {
+            names
+                .iter()
+                .find_map(|name| value.get(name).and_then(Value::as_str))
+                .map(str::to_string)
+        };
+        let version = match value.get("version") {
+            Some(Value::String(version)) => Some(version.to_string()),
+            Some(version @ Value::Object(_)) => {
+                string(&["string", "version", "buildVersion"], version)
+            }
+            _ => string(&["apiVersion", "productVersion"], value),
+        };
+        ServerVersion {
+            product: string(&["productName", "name", "serviceName"], value),
+            version,
+        }
+    }
+
+    /// Returns major, minor and patch version if the version can be parsed
+    ///
+    /// Missing minor or patch versions are returned as `0`, suffixes like `-SNAPSHOT` are ignored.
+    pub fn numbers(&self) -> Option<(u32, u32, u32)> {
+        let version = self.version.as_deref()?.trim_start_matches('v');
+        let version = version.split(['-', '+']).next()?;
+        let mut numbers = version.split('.').map(|number| number.parse::<u32>());
+        let major = numbers.next()?.ok()?;
+        let minor = numbers.next().unwrap_or(Ok(0)).ok()?;
+        let patch = numbers.next().unwrap_or(Ok(0)).ok()?;
+        Some((major, minor, patch))
+    }
+
+    /// Indicates if the server version is known and at least given major and minor version
+    pub fn is_at_least(&self, major: u32, minor: u32) -> bool {
+        self.numbers()
+            .is_some_and(|(actual_major, actual_minor, _)| {
+                (actual_major, actual_minor) >= (major, minor)
+            })
+    }
+}
+
+/// The features and public configuration of an ARSnova/Particify server
+///
+/// Features and login methods not reported by the server are assumed to be available,
+/// so servers without configuration are not restricted.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    pub features: BTreeMap<String, bool>,
+    pub authentication_providers: Vec<AuthenticationProvider>,
+    /// Settings of the web UI like limits, as provided by the server
+    pub ui: BTreeMap<String, Value>,
+}
+
+/// A login method offered by the server
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AuthenticationProvider {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: AuthenticationProviderKind,
+    pub title: Option<String>,
+}
+
+/// The kind of a login method
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuthenticationProviderKind {
+    /// Guest login
+    Anonymous,
+    /// Login using username and password of a registered user
+    UsernamePassword,
+    Ldap,
+    Oidc,
+    Saml,
+    Cas,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+impl Capabilities {
+    /// Detects the enabled features in the configuration of the server
+    ///
+    /// Features are reported either as boolean or as object with an `enabled` field.
+    pub fn from_value(value: &Value) -> Capabilities {
+        let features = value
+            .get("features")
+            .and_then(Value::as_object)
+            .map(|features| {
+                features
+                    .iter()
+                    .filter_map(|(name, feature)| {
+                        let enabled = match feature {
+                            Value::Bool(enabled) => *enabled,
+                            Value::Object(feature) => feature
+                                .get("enabled")
+                                .and_then(Value::as_bool)
+                                .unwrap_or(true),
+                            _ => return None,
+                        };
+                        Some((name.to_string(), enabled))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let authentication_providers = value
+            .get("authenticationProviders")
+            .and_then(|providers| serde_json::from_value(providers.clone()).ok())
+            .unwrap_or_default();
+        let ui = value
+            .get("ui")
+            .and_then(Value::as_object)
+            .map(|ui| ui.clone().into_iter().collect())
+            .unwrap_or_default();
+        Capabilities {
+            features,
+            authentication_providers,
+            ui,
+        }
+    }
+
+    /// Indicates if given feature is available, unknown features are assumed to be available
+    pub fn is_enabled(&self, feature: &str) -> bool {
+        self.features.get(feature).copied().unwrap_or(true)
+    }
+
+    /// Indicates if contents like quizzes and surveys are available
+    pub fn contents(&self) -> bool {
+        self.is_enabled("contents")
+    }
+
+    /// Indicates if comments are available
+    pub fn comments(&self) -> bool {
+        self.is_enabled("comments")
+    }
+
+    /// Indicates if live feedback is available
+    pub fn live_feedback(&self) -> bool {
+        self.is_enabled("liveFeedback")
+    }
+
+    /// Indicates if a login method of given kind is offered, all kinds are assumed to be
+    /// available if the server does not report its login methods
+    pub fn login_available(&self, kind: &AuthenticationProviderKind) -> bool {
+        self.authentication_providers.is_empty()
+            || self
+                .authentication_providers
+                .iter()
+                .any(|provider| provider.kind == *kind)
+    }
+
+    /// Indicates if guests can login using `Client::guest_login()`
+    pub fn guest_login(&self) -> bool {
+        self.login_available(&AuthenticationProviderKind::Anonymous)
+    }
+
+    /// Indicates if registered users can login with username and password using `Client::login()`
+    pub fn password_login(&self) -> bool {
+        self.login_available(&AuthenticationProviderKind::UsernamePassword)
+    }
+}
+
+/// The feedback values as returned by the survey endpoint
+///
+/// Older server versions wrap the values into an object.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum SurveyResponse {
+    Values([u16; 4]),
+    Wrapped { values: [u16; 4] },
+}
+
+impl SurveyResponse {
+    /// Returns the feedback values ordered from very good to very bad
+    pub fn values(&self) -> [u16; 4] {
+        match self {
+            SurveyResponse::Values(values) | SurveyResponse::Wrapped { values } => *values,
+        }
+    }
+}
+
+/// The response to a login request
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoginResponse {
+    pub token: String,
+}
+
+/// Information about a room as shown to participants
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RoomInfo {
+    #[serde(deserialize_with = "null_as_default")]
+    pub id: RoomId,
+    #[serde(deserialize_with = "null_as_default")]
+    pub short_id: ShortId,
+    #[serde(deserialize_with = "null_as_default")]
+    pub name: String,
+    #[serde(deserialize_with = "null_as_default")]
+    pub description: String,
+    pub closed: bool,
+    #[serde(deserialize_with = "null_as_default")]
+    pub settings: RoomInfoSettings,
+    #[serde(deserialize_with = "null_as_default")]
+    pub extensions: RoomExtensions,
+}
+
+impl RoomInfo {
+    /// Indicates if room is closed
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Indicates if room has locked feedback
+    pub fn is_feedback_locked(&self) -> bool {
+        self.settings.feedback_locked
+    }
+
+    /// Returns the mode of the live feedback of the room
+    pub fn feedback_mode(&self) -> FeedbackMode {
+        self.extensions.feedback.mode.clone()
+    }
+}
+
+/// Optional features of a room
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RoomExtensions {
+    #[serde(deserialize_with = "null_as_default")]
+    pub feedback: FeedbackExtension,
+}
+
+/// The configuration of the live feedback of a room
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FeedbackExtension {
+    #[serde(rename = "type", deserialize_with = "null_as_default")]
+    pub mode: FeedbackMode,
+}
+
+/// The mode of the live feedback of a room
+///
+/// In survey mode, the four feedback values are used as answers A, B, C and D.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FeedbackMode {
+    Survey,
+    #[default]
+    #[serde(other)]
+    Feedback,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RoomInfoSettings {
+    pub feedback_locked: bool,
+}
+
+/// A room including the data available to its owner and editors
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Room {
+    pub id: RoomId,
+    pub short_id: ShortId,
+    pub owner_id: UserId,
+    pub name: String,
+    #[serde(deserialize_with = "null_as_default")]
+    pub description: String,
+    pub language: Option<String>,
+    pub closed: bool,
+    pub password_protected: bool,
+    pub focus_mode_enabled: bool,
+    #[serde(deserialize_with = "null_as_default")]
+    pub settings: RoomInfoSettings,
+    pub creation_timestamp: Option<String>,
+    pub update_timestamp: Option<String>,
+}
+
+/// The summary of a room as returned by the room summary view
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct SummaryResponse {
+    pub id: RoomId,
+    pub name: String,
+    #[serde(rename = "shortId")]
+    pub short_id: ShortId,
+    #[serde(deserialize_with = "null_as_default")]
+    pub stats: RoomStats,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RoomStats {
+    pub content_count: usize,
+    pub ack_comment_count: usize,
+    pub room_user_count: usize,
+}
+
+/// The profile of a user
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UserProfile {
+    pub id: UserId,
+    pub login_id: String,
+    pub person: Person,
+}
+
+/// Personal information of a user profile
+///
+/// The display name is shown instead of the user ID, e.g. as alias in quiz leaderboards
+/// and as author of comments.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Person {
+    pub display_name: Option<String>,
+}
+
+/// The role of a user within a room
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RoomRole {
+    Owner,
+    Editor,
+    Moderator,
+    #[default]
+    Participant,
+    #[serde(other)]
+    Unknown,
+}
+
+impl RoomRole {
+    /// Returns the most privileged of given roles, `RoomRole::Participant` if none is given
+    pub fn highest(roles: &[RoomRole]) -> RoomRole {
+        let rank = |role: &RoomRole| match role {
+            RoomRole::Owner => 4,
+            RoomRole::Editor => 3,
+            RoomRole::Moderator => 2,
+            RoomRole::Participant => 1,
+            RoomRole::Unknown => 0,
+        };
+        roles
+            .iter()
+            .filter(|role| **role != RoomRole::Unknown)
+            .max_by_key(|role| rank(role))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// The membership of a user in a room
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Membership {
+    pub room_id: RoomId,
+    pub room_short_id: ShortId,
+    #[serde(deserialize_with = "null_as_default")]
+    pub roles: Vec<RoomRole>,
+    pub last_visit: Option<String>,
+}
+
+/// A member of a room as listed for owners and moderators
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RoomMember {
+    pub user_id: UserId,
+    #[serde(deserialize_with = "null_as_default")]
+    pub roles: Vec<RoomRole>,
+    pub creation_timestamp: Option<String>,
+    pub last_visit: Option<String>,
+}
+
+/// A comment (question of a participant) within a room
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Comment {
+    pub id: CommentId,
+    pub room_id: RoomId,
+    pub creator_id: UserId,
+    #[serde(deserialize_with = "null_as_default")]
+    pub body: String,
+    pub timestamp: Option<String>,
+    pub read: bool,
+    pub favorite: bool,
+    pub correct: u8,
+    pub ack: bool,
+    pub score: i32,
+    pub tag: Option<String>,
+    pub answer: Option<String>,
+    pub highlighted: bool,
+}
+
+impl Comment {
+    /// Applies given changes, e.g. received with a `CommentEvent::Patched`
+    pub fn apply(&mut self, changes: &CommentChanges) {
+        if let Some(score) = changes.score {
+            self.score = score;
+        }
+        if let Some(ack) = changes.ack {
+            self.ack = ack;
+        }
+        if let Some(favorite) = changes.favorite {
+            self.favorite = favorite;
+        }
+        if let Some(correct) = changes.correct {
+            self.correct = correct;
+        }
+        if let Some(read) = changes.read {
+            self.read = read;
+        }
+        if let Some(answer) = &changes.answer {
+            self.answer = Some(answer.clone());
+        }
+        if let Some(tag) = &changes.tag {
+            self.tag = Some(tag.clone());
+        }
+    }
+
+    /// Returns the Markdown body rendered as plain text
+    #[cfg(feature = "markdown")]
+    pub fn body_text(&self) -> String {
+        crate::markdown::to_plain_text(&self.body)
+    }
+
+    /// Returns the Markdown body rendered using ANSI escape sequences
+    #[cfg(feature = "markdown")]
+    pub fn body_ansi(&self) -> String {
+        crate::markdown::to_ansi(&self.body)
+    }
+}
+
+/// Changed properties of a comment, unchanged properties are `None`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CommentChanges {
+    pub score: Option<i32>,
+    pub ack: Option<bool>,
+    pub favorite: Option<bool>,
+    pub correct: Option<u8>,
+    pub read: Option<bool>,
+    pub answer: Option<String>,
+    pub tag: Option<String>,
+}
+
+/// The state of a content within its round
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ContentState {
+    pub round: u32,
+    pub visible: bool,
+    pub additional_text_visible: bool,
+    pub answers_published: bool,
+    pub answering_end_time: Option<String>,
+}
+
+/// The room feature a presenter currently focuses on
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FocusFeature {
+    Contents,
+    Comments,
+    Feedback,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+/// The state of a focused feature, e.g. the presented content
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FocusState {
+    pub content_id: Option<ContentId>,
+    pub content_index: Option<usize>,
+    pub content_group_name: Option<String>,
+}
+
+/// What a presenter currently shows in a room
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RoomFocus {
+    pub feature: FocusFeature,
+    #[serde(deserialize_with = "null_as_default")]
+    pub focus_state: FocusState,
+}
+
+/// An option of a choice content
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnswerOption {
+    pub label: String,
+    pub rendered_label: Option<String>,
+}
+
+/// A content (question) within a room
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Content {
+    #[serde(deserialize_with = "null_as_default")]
+    pub id: ContentId,
+    #[serde(deserialize_with = "null_as_default")]
+    pub room_id: RoomId,
+    #[serde(deserialize_with = "null_as_default")]
+    pub subject: String,
+    #[serde(deserialize_with = "null_as_default")]
+    pub body: String,
+    pub rendered_body: Option<String>,
+    #[serde(deserialize_with = "null_as_default")]
+    pub groups: Vec<String>,
+    #[serde(deserialize_with = "null_as_default")]
+    pub state: ContentState,
+    pub timestamp: Option<String>,
+    pub additional_text: Option<String>,
+    #[serde(deserialize_with = "null_as_default")]
+    pub abstention: bool,
+    #[serde(flatten, deserialize_with = "kind_or_unknown")]
+    pub kind: ContentKind,
+}
+
+#[cfg(feature = "markdown")]
+impl Content {
+    /// Returns the Markdown body rendered as plain text
+    pub fn body_text(&self) -> String {
+        crate::markdown::to_plain_text(&self.body)
+    }
+
+    /// Returns the Markdown body rendered using ANSI escape sequences
+    pub fn body_ansi(&self) -> String {
+        crate::markdown::to_ansi(&self.body)
+    }
+}
+
+/// The format specific part of a `Content`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(tag = "format", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContentKind {
+    #[serde(rename_all = "camelCase")]
+    Choice {
+        #[serde(default, deserialize_with = "null_as_default")]
+        options: Vec<AnswerOption>,
+        #[serde(default, deserialize_with = "null_as_default")]
+        correct_option_indexes: Vec<usize>,
+        #[serde(default, deserialize_with = "null_as_default")]
+        multiple: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Binary {
+        #[serde(default, deserialize_with = "null_as_default")]
+        options: Vec<AnswerOption>,
+        #[serde(default, deserialize_with = "null_as_default")]
+        correct_option_indexes: Vec<usize>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Scale {
+        #[serde(default, deserialize_with = "null_as_default")]
+        option_count: usize,
+    },
+    #[serde(rename_all = "camelCase")]
+    Sort {
+        #[serde(default, deserialize_with = "null_as_default")]
+        options: Vec<AnswerOption>,
+        #[serde(default, deserialize_with = "null_as_default")]
+        correct_option_indexes: Vec<usize>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Prioritization {
+        #[serde(default, deserialize_with = "null_as_default")]
+        options: Vec<AnswerOption>,
+        #[serde(default, deserialize_with = "null_as_default")]
+        assignable_points: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    Wordcloud {
+        #[serde(default, deserialize_with = "null_as_default")]
+        max_answers: usize,
+        #[serde(default, deserialize_with = "null_as_default")]
+        banned_keywords: Vec<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Numeric {
+        #[serde(default, deserialize_with = "null_as_default")]
+        min_number: f64,
+        #[serde(default, deserialize_with = "null_as_default")]
+        max_number: f64,
+        #[serde(default)]
+        correct_number: Option<f64>,
+        #[serde(default)]
+        tolerance: Option<f64>,
+    },
+    Text,
+    Slide,
+    Flashcard,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+/// A file attached to a content, e.g. an image illustrating the question
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Attachment {
+    pub id: AttachmentId,
+    pub content_id: ContentId,
+    pub name: String,
+    pub media_type: String,
+    pub size: u64,
+}
+
+/// An answer to a content
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Answer {
+    pub id: AnswerId,
+    pub content_id: ContentId,
+    pub room_id: RoomId,
+    pub creator_id: UserId,
+    pub round: u32,
+    pub creation_timestamp: Option<String>,
+    #[serde(flatten, deserialize_with = "kind_or_unknown")]
+    pub kind: AnswerKind,
+}
+
+/// The format specific part of an `Answer`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(tag = "format", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AnswerKind {
+    #[serde(rename_all = "camelCase")]
+    Choice {
+        #[serde(default, deserialize_with = "null_as_default")]
+        selected_choice_indexes: Vec<usize>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Prioritization {
+        #[serde(default, deserialize_with = "null_as_default")]
+        assigned_points: Vec<u32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Text {
+        #[serde(default, deserialize_with = "null_as_default")]
+        subject: String,
+        #[serde(default, deserialize_with = "null_as_default")]
+        body: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Multiple {
+        #[serde(default, deserialize_with = "null_as_default")]
+        texts: Vec<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Numeric {
+        #[serde(default)]
+        selected_number: Option<f64>,
+    },
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+/// A named group of contents within a room
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ContentGroup {
+    pub id: ContentGroupId,
+    pub room_id: RoomId,
+    pub name: String,
+    #[serde(deserialize_with = "null_as_default")]
+    pub content_ids: Vec<ContentId>,
+    pub published: bool,
+}
+
+/// A portable bundle of a room with its contents and comments
+///
+/// The bundle can be serialized, e.g. to JSON, to recreate the room on another instance.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RoomBundle {
+    pub room: RoomInfo,
+    pub content_groups: Vec<ContentGroup>,
+    pub contents: Vec<Content>,
+    pub comments: Vec<Comment>,
+}
+
+/// The answer statistics of a content for each round
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnswerStatistics {
+    pub content_id: ContentId,
+    #[serde(deserialize_with = "null_as_default")]
+    pub round_statistics: Vec<RoundStatistics>,
+}
+
+/// The answer statistics of a single round of a content
+///
+/// For choice contents, the independent counts contain the number of selections of each option.
+/// For wordcloud contents, the texts contain all terms not banned by a moderator and the counts
+/// contain the number of answers for each term.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RoundStatistics {
+    pub round: u32,
+    #[serde(deserialize_with = "null_as_default")]
+    pub independent_counts: Vec<u32>,
+    #[serde(deserialize_with = "null_as_default")]
+    pub texts: Vec<String>,
+    #[serde(deserialize_with = "null_as_default")]
+    pub counts: Vec<u32>,
+    #[serde(deserialize_with = "null_as_default")]
+    pub combinated_counts: Vec<CombinatedCount>,
+    pub abstention_count: u32,
+    pub answer_count: u32,
+}
+
+/// The number of choice answers with exactly the same selected options
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CombinatedCount {
+    #[serde(deserialize_with = "null_as_default")]
+    pub selected_choice_indexes: Vec<usize>,
+    pub count: u32,
+}
+
+/// The answer count and correctness of a content in its latest round
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ContentStats {
+    pub content_id: ContentId,
+    pub subject: String,
+    pub round: u32,
+    pub answer_count: u32,
+    /// The ratio of correct answers, `None` for contents without correct options or answers
+    pub correct_ratio: Option<f32>,
+}
+
+impl ContentStats {
+    /// Calculates the stats of given content using the latest round of given answer statistics
+    ///
+    /// An answer is correct if exactly the correct options of a choice or binary content were selected.
+    pub fn new(content: &Content, statistics: &AnswerStatistics) -> ContentStats {
+        let round = statistics
+            .round_statistics
+            .iter()
+            .max_by_key(|round| round.round);
+        let answer_count = round.map_or(0, |round| round.answer_count);
+        let correct_ratio = match (&content.kind, round) {
+            (
+                ContentKind::Choice {
+                    correct_option_indexes,
+                    ..
+                }
+                | ContentKind::Binary {
+                    correct_option_indexes,
+                    ..
+                },
+                Some(round),
+            ) if !correct_option_indexes.is_empty() && answer_count > 0 => {
+                let mut correct = correct_option_indexes.clone();
+                correct.sort_unstable();
+                let correct_count = round
+                    .combinated_counts
+                    .iter()
+                    .filter(|combination| {
+                        let mut selected = combination.selected_choice_indexes.clone();
+                        selected.sort_unstable();
+                        selected == correct
+                    })
+                    .map(|combination| combination.count)
+                    .sum::<u32>();
+                Some(correct_count as f32 / answer_count as f32)
+            }
+            _ => None,
+        };
+
+        ContentStats {
+            content_id: content.id.clone(),
+            subject: content.subject.clone(),
+            round: round.map_or(0, |round| round.round),
+            answer_count,
+            correct_ratio,
+        }
+    }
+}
+
+/// A term of a wordcloud with the number of answers containing it
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WordcloudTerm {
+    pub term: String,
+    pub count: u32,
+}
+
+/// An announcement of the room owner to all participants
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Announcement {
+    pub id: AnnouncementId,
+    pub room_id: RoomId,
+    pub creator_id: UserId,
+    pub title: String,
+    #[serde(deserialize_with = "null_as_default")]
+    pub body: String,
+    pub rendered_body: Option<String>,
+    pub creation_timestamp: Option<String>,
+    pub update_timestamp: Option<String>,
+}
+
+/// The number of announcements of all rooms of a user and how many of them are unread
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnnouncementState {
+    pub total: usize,
+    #[serde(rename = "new")]
+    pub unread: usize,
+    pub read_timestamp: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_deserialize_content_without_format() {
+        let content = serde_json::from_str::<Content>(r#"{"id":"x","subject":"s"}"#).unwrap();
+
+        assert_eq!(content.id, ContentId::from("x"));
+        assert_eq!(content.subject, "s");
+        assert_eq!(content.kind, ContentKind::Unknown);
+    }
+
+    #[test]
+    fn should_deserialize_content_with_unknown_format() {
+        let content =
+            serde_json::from_str::<Content>(r#"{"id":"x","format":"HOLOGRAM","extra":1}"#).unwrap();
+
+        assert_eq!(content.kind, ContentKind::Unknown);
+    }
+
+    #[test]
+    fn should_deserialize_choice_content_with_null_options() {
+        let content = serde_json::from_str::<Content>(
+            r#"{"id":"x","format":"CHOICE","options":null,"correctOptionIndexes":null,"multiple":true}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            content.kind,
+            ContentKind::Choice {
+                options: vec![],
+                correct_option_indexes: vec![],
+                multiple: true
+            }
+        );
+    }
+
+    #[test]
+    fn should_serialize_content_kind_flattened() {
+        let content = Content {
+            id: ContentId::from("x"),
+            kind: ContentKind::Scale { option_count: 5 },
+            ..Content::default()
+        };
+
+        let json = serde_json::to_value(&content).unwrap();
+
+        assert_eq!(json["format"], "SCALE");
+        assert_eq!(serde_json::from_value::<Content>(json).unwrap(), content);
+    }
+
+    #[test]
+    fn should_deserialize_answer_without_format() {
+        let answer = serde_json::from_str::<Answer>(r#"{"id":"a","round":2}"#).unwrap();
+
+        assert_eq!(answer.id, AnswerId::from("a"));
+        assert_eq!(answer.round, 2);
+        assert_eq!(answer.kind, AnswerKind::Unknown);
+    }
+
+    #[test]
+    fn should_deserialize_choice_answer() {
+        let answer = serde_json::from_str::<Answer>(
+            r#"{"id":"a","format":"CHOICE","selectedChoiceIndexes":[1]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            answer.kind,
+            AnswerKind::Choice {
+                selected_choice_indexes: vec![1]
+            }
+        );
+    }
+
+    #[test]
+    fn should_deserialize_room_info_with_null_values() {
+        let room_info = serde_json::from_str::<RoomInfo>(
+            r#"{"id":"r","shortId":"12345678","name":null,"description":null,"settings":null,"unknown":1}"#,
+        )
+        .unwrap();
+
+        assert_eq!(room_info.short_id, ShortId::from("12345678"));
+        assert_eq!(room_info.name, "");
+        assert_eq!(room_info.description, "");
+        assert!(!room_info.is_feedback_locked());
+    }
+
+    #[test]
+    fn should_map_unknown_feedback_mode() {
+        let room_info =
+            serde_json::from_str::<RoomInfo>(r#"{"extensions":{"feedback":{"type":"UNKNOWN"}}}"#)
+                .unwrap();
+
+        assert_eq!(room_info.feedback_mode(), FeedbackMode::Feedback);
+    }
+
+    #[test]
+    fn should_require_login_token() {
+        assert!(serde_json::from_str::<LoginResponse>("{}").is_err());
+    }
+}
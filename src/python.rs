@@ -0,0 +1,255 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::sync::Arc;
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyStopAsyncIteration};
+use pyo3::prelude::*;
+use pyo3_async_runtimes::tokio::future_into_py;
+use tokio::sync::mpsc::{channel, Receiver};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use crate::client::{ClientError, Feedback, FeedbackHandler, LoggedIn, RoomInfo, RoomStats};
+use crate::ids::ShortId;
+
+create_exception!(arsnova_client, ArsnovaError, PyException);
+
+impl From<ClientError> for PyErr {
+    fn from(err: ClientError) -> Self {
+        ArsnovaError::new_err(err.to_string())
+    }
+}
+
+/// Room information as returned by `Client.get_room_info()`
+#[pyclass(name = "RoomInfo", frozen, get_all)]
+struct PyRoomInfo {
+    id: String,
+    short_id: String,
+    name: String,
+    description: String,
+    closed: bool,
+    feedback_locked: bool,
+}
+
+impl From<RoomInfo> for PyRoomInfo {
+    fn from(room_info: RoomInfo) -> Self {
+        PyRoomInfo {
+            feedback_locked: room_info.is_feedback_locked(),
+            id: room_info.id.to_string(),
+            short_id: room_info.short_id.to_string(),
+            name: room_info.name,
+            description: room_info.description,
+            closed: room_info.closed,
+        }
+    }
+}
+
+/// Room statistics as returned by `Client.get_room_stats()`
+#[pyclass(name = "RoomStats", frozen, get_all)]
+struct PyRoomStats {
+    content_count: usize,
+    ack_comment_count: usize,
+    room_user_count: usize,
+}
+
+impl From<RoomStats> for PyRoomStats {
+    fn from(stats: RoomStats) -> Self {
+        PyRoomStats {
+            content_count: stats.content_count,
+            ack_comment_count: stats.ack_comment_count,
+            room_user_count: stats.room_user_count,
+        }
+    }
+}
+
+/// The feedback of a room
+#[pyclass(name = "Feedback", frozen, get_all)]
+struct PyFeedback {
+    very_good: u16,
+    good: u16,
+    bad: u16,
+    very_bad: u16,
+}
+
+#[pymethods]
+impl PyFeedback {
+    /// Returns the number of votes
+    fn count_votes(&self) -> u32 {
+        Feedback::from(self).count_votes()
+    }
+
+    /// Returns the average feedback score or `None` without votes
+    fn score(&self) -> Option<f32> {
+        Feedback::from(self).score()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Feedback(very_good={}, good={}, bad={}, very_bad={})",
+            self.very_good, self.good, self.bad, self.very_bad
+        )
+    }
+}
+
+impl From<Feedback> for PyFeedback {
+    fn from(feedback: Feedback) -> Self {
+        PyFeedback {
+            very_good: feedback.very_good,
+            good: feedback.good,
+            bad: feedback.bad,
+            very_bad: feedback.very_bad,
+        }
+    }
+}
+
+impl From<&PyFeedback> for Feedback {
+    fn from(feedback: &PyFeedback) -> Self {
+        Feedback::from_values([
+            feedback.very_good,
+            feedback.good,
+            feedback.bad,
+            feedback.very_bad,
+        ])
+    }
+}
+
+/// A logged in ARSnova client as returned by `guest_login()`
+#[pyclass(name = "Client", frozen)]
+struct PyClient {
+    inner: Arc<crate::Client<LoggedIn>>,
+}
+
+#[pymethods]
+impl PyClient {
+    /// The user ID of the logged in guest
+    #[getter]
+    fn user_id(&self) -> PyResult<String> {
+        Ok(self.inner.get_user_id()?.to_string())
+    }
+
+    /// Requests room information for given 8-digit room ID
+    fn get_room_info<'py>(&self, py: Python<'py>, short_id: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        future_into_py(py, async move {
+            Ok(PyRoomInfo::from(
+                client.get_room_info(&ShortId::from(short_id)).await?,
+            ))
+        })
+    }
+
+    /// Requests room statistics for given 8-digit room ID
+    fn get_room_stats<'py>(
+        &self,
+        py: Python<'py>,
+        short_id: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        future_into_py(py, async move {
+            Ok(PyRoomStats::from(
+                client.get_room_stats(&ShortId::from(short_id)).await?,
+            ))
+        })
+    }
+
+    /// Requests the current feedback for given 8-digit room ID
+    fn get_feedback<'py>(&self, py: Python<'py>, short_id: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        future_into_py(py, async move {
+            Ok(PyFeedback::from(
+                client.get_feedback(&ShortId::from(short_id)).await?,
+            ))
+        })
+    }
+
+    /// Returns an async iterator of feedback changes for given 8-digit room ID
+    fn feedback_stream(&self, short_id: String) -> PyFeedbackStream {
+        let client = self.inner.clone();
+        let (tx, rx) = channel::<Feedback>(10);
+        let task = pyo3_async_runtimes::tokio::get_runtime().spawn(async move {
+            client
+                .on_feedback_changed(&ShortId::from(short_id), FeedbackHandler::Sender(tx))
+                .await
+        });
+        PyFeedbackStream {
+            receiver: Arc::new(Mutex::new(rx)),
+            task,
+        }
+    }
+}
+
+/// An async iterator of feedback changes, ending if the subscription ends
+#[pyclass(name = "FeedbackStream", frozen)]
+struct PyFeedbackStream {
+    receiver: Arc<Mutex<Receiver<Feedback>>>,
+    task: JoinHandle<Result<(), ClientError>>,
+}
+
+#[pymethods]
+impl PyFeedbackStream {
+    fn __aiter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let receiver = self.receiver.clone();
+        future_into_py(py, async move {
+            match receiver.lock().await.recv().await {
+                Some(feedback) => Ok(PyFeedback::from(feedback)),
+                None => Err(PyStopAsyncIteration::new_err(())),
+            }
+        })
+    }
+
+    /// Ends the subscription
+    fn close(&self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for PyFeedbackStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Requests a guest login using given API URL and returns a logged in `Client`
+#[pyfunction]
+fn guest_login(py: Python<'_>, api_url: String) -> PyResult<Bound<'_, PyAny>> {
+    future_into_py(py, async move {
+        let client = crate::Client::new(api_url)?.guest_login().await?;
+        Ok(PyClient {
+            inner: Arc::new(client),
+        })
+    })
+}
+
+/// The Python module `arsnova_client`
+#[pymodule]
+fn arsnova_client(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("ArsnovaError", m.py().get_type::<ArsnovaError>())?;
+    m.add_class::<PyClient>()?;
+    m.add_class::<PyFeedback>()?;
+    m.add_class::<PyFeedbackStream>()?;
+    m.add_class::<PyRoomInfo>()?;
+    m.add_class::<PyRoomStats>()?;
+    m.add_function(wrap_pyfunction!(guest_login, m)?)?;
+    Ok(())
+}
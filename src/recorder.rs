@@ -0,0 +1,434 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use reqwest::{Response, ResponseBuilderExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
+
+const REDACTED: &str = "<redacted>";
+
+const OMITTED: &str = "<omitted>";
+
+/// Maximum size of recorded response bodies, larger bodies are omitted
+const MAX_BODY_SIZE: u64 = 1024 * 1024;
+
+/// Names of headers and JSON properties containing credentials
+const SECRETS: [&str; 7] = [
+    "authorization",
+    "cookie",
+    "set-cookie",
+    "token",
+    "passcode",
+    "password",
+    "confirmationtoken",
+];
+
+/// Path of login requests, whose bodies consist of credentials only
+const LOGIN_PATH: &str = "/auth/login";
+
+/// A recorded HTTP request or STOMP frame
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecordedEvent {
+    HttpRequest {
+        method: String,
+        url: String,
+        headers: BTreeMap<String, String>,
+        body: Option<String>,
+    },
+    HttpResponse {
+        url: String,
+        status: u16,
+        headers: BTreeMap<String, String>,
+        body: String,
+    },
+    StompSent {
+        frame: String,
+    },
+    StompReceived {
+        frame: String,
+    },
+}
+
+/// A recorded event and the time it was recorded at
+///
+/// Records are written as one JSON object per line, e.g.
+/// `{"timestamp":1700000000000,"type":"stomp_received","frame":"MESSAGE\n..."}`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// Milliseconds since UNIX epoch
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub event: RecordedEvent,
+}
+
+/// Records all HTTP requests, responses and STOMP frames of a client
+///
+/// Credentials like tokens, passwords, authorization headers and cookies are redacted before
+/// records are written. Bodies of login requests are redacted completely. Of tokens in response
+/// bodies only the signature is redacted, so recorded logins can be replayed.
+/// Failing writes are ignored to not affect the client.
+pub struct Recorder {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl Recorder {
+    /// Creates a recorder writing to given writer
+    pub fn new<W: Write + Send + 'static>(writer: W) -> Recorder {
+        Recorder {
+            writer: Mutex::new(Box::new(writer)),
+        }
+    }
+
+    /// Creates a recorder writing to a newly created file at given path
+    ///
+    /// This method fails if the file cannot be created.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Recorder> {
+        Ok(Recorder::new(File::create(path)?))
+    }
+
+    pub(crate) fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &HeaderMap,
+        body: Option<&[u8]>,
+    ) {
+        let is_login = Url::parse(url).is_ok_and(|url| url.path().contains(LOGIN_PATH));
+        self.record(RecordedEvent::HttpRequest {
+            method: method.to_string(),
+            url: redact_url(url),
+            headers: redact_headers(headers),
+            body: body.map(|body| match is_login {
+                true => redact_credentials(body),
+                false => redact_body(body),
+            }),
+        })
+    }
+
+    /// Records given response
+    ///
+    /// Textual response bodies are buffered, therefore a new response containing the buffered body
+    /// and the original URL is returned. Other bodies, like attachment downloads, and bodies larger
+    /// than 1 MiB are omitted from the record and the response is returned unchanged.
+    pub(crate) async fn http_response(
+        &self,
+        response: Response,
+    ) -> Result<Response, reqwest::Error> {
+        let url = response.url().clone();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let exceeds_size = response
+            .content_length()
+            .is_some_and(|size| size > MAX_BODY_SIZE);
+        if !is_textual(&headers) || exceeds_size {
+            self.record(RecordedEvent::HttpResponse {
+                url: redact_url(url.as_str()),
+                status: status.as_u16(),
+                headers: redact_headers(&headers),
+                body: OMITTED.to_string(),
+            });
+            return Ok(response);
+        }
+
+        let body = response.bytes().await?;
+        self.record(RecordedEvent::HttpResponse {
+            url: redact_url(url.as_str()),
+            status: status.as_u16(),
+            headers: redact_headers(&headers),
+            body: match body.len() as u64 > MAX_BODY_SIZE {
+                true => OMITTED.to_string(),
+                false => redact_body(&body),
+            },
+        });
+        let mut response = http::Response::builder()
+            .status(status)
+            .url(url)
+            .body(body)
+            .expect("valid response");
+        *response.headers_mut() = headers;
+        Ok(Response::from(response))
+    }
+
+    #[cfg_attr(not(feature = "websocket"), allow(dead_code))]
+    pub(crate) fn stomp_sent(&self, frame: &str) {
+        self.record(RecordedEvent::StompSent {
+            frame: redact_frame(frame),
+        })
+    }
+
+    #[cfg_attr(not(feature = "websocket"), allow(dead_code))]
+    pub(crate) fn stomp_received(&self, frame: &str) {
+        self.record(RecordedEvent::StompReceived {
+            frame: redact_frame(frame),
+        })
+    }
+
+    fn record(&self, event: RecordedEvent) {
+        let record = Record {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_millis() as u64)
+                .unwrap_or_default(),
+            event,
+        };
+        if let (Ok(line), Ok(mut writer)) = (serde_json::to_string(&record), self.writer.lock()) {
+            let _ = writeln!(writer, "{}", line).and_then(|_| writer.flush());
+        }
+    }
+}
+
+fn is_secret(name: &str) -> bool {
+    SECRETS.contains(&name.to_lowercase().as_str())
+}
+
+/// Checks whether the body of a response with given headers is JSON or text
+///
+/// Responses without content type are considered textual, as they usually have no body.
+fn is_textual(headers: &HeaderMap) -> bool {
+    match headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(content_type) => {
+            let content_type = content_type.to_lowercase();
+            content_type.starts_with("text/") || content_type.contains("json")
+        }
+        None => true,
+    }
+}
+
+/// Redacts the values of credential query parameters of given URL
+pub(crate) fn redact_url(url: &str) -> String {
+    let Ok(mut url) = Url::parse(url) else {
+        return url.to_string();
+    };
+    if !url.query_pairs().any(|(name, _)| is_secret(&name)) {
+        return url.to_string();
+    }
+    let pairs = url
+        .query_pairs()
+        .map(|(name, value)| match is_secret(&name) {
+            true => (name.to_string(), REDACTED.to_string()),
+            false => (name.to_string(), value.to_string()),
+        })
+        .collect::<Vec<_>>();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+    url.to_string()
+}
+
+fn redact_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if is_secret(name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).to_string()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// Redacts all credential properties if given body is JSON
+fn redact_body(body: &[u8]) -> String {
+    match serde_json::from_slice::<Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        }
+        Err(_) => String::from_utf8_lossy(body).to_string(),
+    }
+}
+
+/// Redacts all properties of given JSON body or the whole body if it is not a JSON object
+fn redact_credentials(body: &[u8]) -> String {
+    match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Object(mut map)) => {
+            map.values_mut()
+                .for_each(|value| *value = Value::String(REDACTED.to_string()));
+            Value::Object(map).to_string()
+        }
+        _ => REDACTED.to_string(),
+    }
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => map.iter_mut().for_each(|(key, value)| {
+            if key == "token" {
+                *value = Value::String(redact_token(value.as_str().unwrap_or_default()));
+            } else if is_secret(key) {
+                *value = Value::String(REDACTED.to_string());
+            } else {
+                redact_value(value);
+            }
+        }),
+        Value::Array(values) => values.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// Redacts the signature of given JSON Web Token or the whole token if it is no JWT
+///
+/// The claims are kept, so the user ID of a replayed login can still be decoded.
+fn redact_token(token: &str) -> String {
+    match token.split('.').collect::<Vec<_>>().as_slice() {
+        [header, claims, _] => format!("{}.{}.{}", header, claims, REDACTED),
+        _ => REDACTED.to_string(),
+    }
+}
+
+/// Redacts credential headers of a STOMP frame
+fn redact_frame(frame: &str) -> String {
+    let (head, body) = match frame.find("\n\n") {
+        Some(idx) => frame.split_at(idx),
+        None => (frame, ""),
+    };
+    let head = head
+        .split('\n')
+        .map(|line| match line.split_once(':') {
+            Some((name, _)) if is_secret(name) => format!("{}:{}", name, REDACTED),
+            _ => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("{}{}", head, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn records(&self) -> Vec<RecordedEvent> {
+            String::from_utf8_lossy(&self.0.lock().unwrap())
+                .lines()
+                .map(|line| serde_json::from_str::<Record>(line).unwrap().event)
+                .collect()
+        }
+    }
+
+    fn response(content_type: &str, body: Vec<u8>) -> Response {
+        let response = http::Response::builder()
+            .url(Url::parse("http://localhost/api/room/1").unwrap())
+            .header(CONTENT_TYPE, content_type)
+            .body(body)
+            .unwrap();
+        Response::from(response)
+    }
+
+    #[tokio::test]
+    async fn should_keep_url_of_recorded_response() {
+        let buffer = Buffer::default();
+        let recorder = Recorder::new(buffer.clone());
+
+        let response = recorder
+            .http_response(response("application/json", b"{\"id\":\"1\"}".to_vec()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.url().as_str(), "http://localhost/api/room/1");
+        assert_eq!(response.text().await.unwrap(), "{\"id\":\"1\"}");
+        assert!(matches!(
+            &buffer.records()[..],
+            [RecordedEvent::HttpResponse { body, .. }] if body == "{\"id\":\"1\"}"
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_omit_binary_and_large_bodies() {
+        let buffer = Buffer::default();
+        let recorder = Recorder::new(buffer.clone());
+        let large = vec![b' '; MAX_BODY_SIZE as usize + 1];
+
+        let binary = recorder
+            .http_response(response("image/png", vec![0x89, 0x50]))
+            .await
+            .unwrap();
+        let text = recorder
+            .http_response(response("text/plain", large.clone()))
+            .await
+            .unwrap();
+
+        assert_eq!(binary.bytes().await.unwrap().as_ref(), [0x89, 0x50]);
+        assert_eq!(text.bytes().await.unwrap().len(), large.len());
+        let records = buffer.records();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(
+            |record| matches!(record, RecordedEvent::HttpResponse { body, .. } if body == OMITTED)
+        ));
+    }
+
+    #[test]
+    fn should_redact_credential_query_parameters() {
+        assert_eq!(
+            redact_url("http://localhost/api/user/1?token=abc&page=2"),
+            "http://localhost/api/user/1?token=%3Credacted%3E&page=2"
+        );
+        assert_eq!(
+            redact_url("http://localhost/api/room?page=2"),
+            "http://localhost/api/room?page=2"
+        );
+    }
+
+    #[test]
+    fn should_redact_credentials() {
+        let body = redact_body(br#"{"user":{"password":"secret","name":"a"},"token":"h.c.s"}"#);
+
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            serde_json::json!({"user":{"password":REDACTED,"name":"a"},"token":"h.c.<redacted>"})
+        );
+        assert_eq!(
+            redact_credentials(br#"{"loginId":"a","password":"b"}"#),
+            r#"{"loginId":"<redacted>","password":"<redacted>"}"#
+        );
+        assert_eq!(redact_token("opaque"), REDACTED);
+        assert_eq!(
+            redact_frame("CONNECT\ntoken:abc\naccept-version:1.2\n\n\0"),
+            "CONNECT\ntoken:<redacted>\naccept-version:1.2\n\n\0"
+        );
+    }
+}
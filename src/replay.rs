@@ -0,0 +1,248 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+#[cfg(feature = "websocket")]
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Request, Response, StatusCode};
+
+#[cfg(feature = "websocket")]
+use futures_util::{SinkExt, StreamExt};
+#[cfg(feature = "websocket")]
+use tokio_tungstenite::tungstenite::handshake::client::Request as WsRequest;
+#[cfg(feature = "websocket")]
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+
+use crate::client::ClientError;
+use crate::recorder::{redact_url, Record, RecordedEvent};
+#[cfg(feature = "websocket")]
+use crate::runtime::{Runtime, TokioRuntime};
+use crate::transport::{BoxFuture, HttpTransport};
+#[cfg(feature = "websocket")]
+use crate::transport::{WsRead, WsTransport, WsWrite};
+
+struct RecordedResponse {
+    method: String,
+    url: String,
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+/// Replays a session recorded by a `Recorder` without a live ARSnova server
+///
+/// HTTP requests are answered using the recorded response of the first unused recorded request
+/// with same method and URL. Each websocket connection replays the STOMP frames received by the
+/// corresponding recorded connection and is closed afterwards. Frames sent are discarded.
+pub struct Replay {
+    responses: Mutex<Vec<RecordedResponse>>,
+    connections: Mutex<VecDeque<Vec<(Duration, String)>>>,
+    timing: bool,
+    #[cfg(feature = "websocket")]
+    runtime: Arc<dyn Runtime>,
+}
+
+impl Replay {
+    /// Creates a replay of given records
+    pub fn new(records: Vec<Record>) -> Replay {
+        let mut responses = vec![];
+        let mut connections = VecDeque::new();
+        let mut request = None;
+        let mut last_timestamp = 0;
+
+        for record in records {
+            match record.event {
+                RecordedEvent::HttpRequest { method, url, .. } => request = Some((method, url)),
+                RecordedEvent::HttpResponse {
+                    status,
+                    headers,
+                    body,
+                    ..
+                } => {
+                    if let Some((method, url)) = request.take() {
+                        responses.push(RecordedResponse {
+                            method,
+                            url,
+                            status,
+                            headers,
+                            body,
+                        });
+                    }
+                }
+                RecordedEvent::StompSent { frame } => {
+                    if frame.starts_with("CONNECT") {
+                        connections.push_back(vec![]);
+                        last_timestamp = record.timestamp;
+                    }
+                }
+                RecordedEvent::StompReceived { frame } => {
+                    if let Some(frames) = connections.back_mut() {
+                        let delay = record.timestamp.saturating_sub(last_timestamp);
+                        frames.push((Duration::from_millis(delay), frame));
+                        last_timestamp = record.timestamp;
+                    }
+                }
+            }
+        }
+
+        Replay {
+            responses: Mutex::new(responses),
+            connections: Mutex::new(connections),
+            timing: false,
+            #[cfg(feature = "websocket")]
+            runtime: Arc::new(TokioRuntime),
+        }
+    }
+
+    /// Reads records written by a `Recorder` from given reader
+    ///
+    /// This method fails if a line cannot be read or parsed.
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Replay> {
+        let mut records = vec![];
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            records.push(
+                serde_json::from_str::<Record>(&line)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+            );
+        }
+        Ok(Replay::new(records))
+    }
+
+    /// Reads records written by a `Recorder` from file at given path
+    ///
+    /// This method fails if the file cannot be read or parsed.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Replay> {
+        Replay::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Controls if STOMP frames are replayed with their recorded delays
+    ///
+    /// Defaults to `false`, replaying all frames immediately.
+    pub fn with_timing(mut self, timing: bool) -> Replay {
+        self.timing = timing;
+        self
+    }
+
+    /// Sets the `Runtime` used to delay STOMP frames if replayed with timing
+    ///
+    /// Defaults to `TokioRuntime`.
+    #[cfg(feature = "websocket")]
+    pub fn with_runtime<R: Runtime + 'static>(mut self, runtime: R) -> Replay {
+        self.runtime = Arc::new(runtime);
+        self
+    }
+
+    /// Returns the recorded response of given request
+    ///
+    /// If no matching response was recorded, a response with status `404 Not Found` is returned.
+    fn http_response(&self, request: &Request) -> Response {
+        let recorded = self.responses.lock().ok().and_then(|mut responses| {
+            responses
+                .iter()
+                .position(|response| {
+                    response.method == request.method().as_str()
+                        && response.url == redact_url(request.url().as_str())
+                })
+                .map(|idx| responses.remove(idx))
+        });
+
+        let response = match recorded {
+            Some(recorded) => {
+                let mut response = http::Response::new(recorded.body);
+                *response.status_mut() =
+                    StatusCode::from_u16(recorded.status).unwrap_or(StatusCode::OK);
+                *response.headers_mut() = recorded
+                    .headers
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        Some((
+                            HeaderName::try_from(name).ok()?,
+                            HeaderValue::try_from(value).ok()?,
+                        ))
+                    })
+                    .collect::<HeaderMap>();
+                response
+            }
+            None => {
+                let mut response = http::Response::new(String::new());
+                *response.status_mut() = StatusCode::NOT_FOUND;
+                response
+            }
+        };
+        Response::from(response)
+    }
+
+    /// Returns the received STOMP frames of the next recorded websocket connection
+    /// and the delay of each frame
+    #[cfg_attr(not(feature = "websocket"), allow(dead_code))]
+    fn next_connection(&self) -> Vec<(Duration, String)> {
+        let frames = self
+            .connections
+            .lock()
+            .ok()
+            .and_then(|mut connections| connections.pop_front())
+            .unwrap_or_default();
+        if self.timing {
+            frames
+        } else {
+            frames
+                .into_iter()
+                .map(|(_, frame)| (Duration::ZERO, frame))
+                .collect()
+        }
+    }
+}
+
+impl HttpTransport for Replay {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response, ClientError>> {
+        Box::pin(async move { Ok(self.http_response(&request)) })
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl WsTransport for Replay {
+    fn connect(&self, _: WsRequest) -> BoxFuture<'_, Result<(WsWrite, WsRead), ClientError>> {
+        let frames = self.next_connection();
+        let runtime = self.runtime.clone();
+        Box::pin(async move {
+            let read = futures_util::stream::iter(frames).then(move |(delay, frame)| {
+                let sleep = runtime.sleep(delay);
+                async move {
+                    sleep.await;
+                    Ok(Message::Text(frame))
+                }
+            });
+            let write =
+                futures_util::sink::drain().sink_map_err(|never| -> WsError { match never {} });
+            Ok((Box::pin(write) as WsWrite, Box::pin(read) as WsRead))
+        })
+    }
+}
@@ -0,0 +1,104 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Aggregated reports of a room session, e.g. for archiving after a lecture
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::{Client, ClientError, Feedback, LoggedIn, RoomInfo, RoomStats};
+use crate::history::{FeedbackHistory, FeedbackSnapshot};
+use crate::ids::ShortId;
+use crate::models::{AnswerStatistics, Comment, Content};
+
+/// A content of a room with its answer statistics
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContentReport {
+    pub content: Content,
+    pub statistics: AnswerStatistics,
+}
+
+/// A structured report of a room session
+///
+/// The report can be serialized, e.g. to JSON, to archive a session.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionReport {
+    /// The time the report was generated at
+    pub generated_at: SystemTime,
+    pub room: RoomInfo,
+    pub stats: RoomStats,
+    /// The feedback at the time the report was generated at
+    pub feedback: Feedback,
+    /// All feedback snapshots of the given history, oldest first
+    pub feedback_history: Vec<FeedbackSnapshot>,
+    pub comments: Vec<Comment>,
+    /// All contents of the content groups of the room with their answer statistics
+    pub contents: Vec<ContentReport>,
+}
+
+impl SessionReport {
+    /// Generates a report of given 8-digit room ID using the feedback recorded in given history
+    ///
+    /// Contents contained in multiple content groups are reported once.
+    ///
+    /// This method fails on connection or response errors of any request and if
+    /// no room is available with given room ID.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(client, history))
+    )]
+    pub async fn generate(
+        client: &Client<LoggedIn>,
+        short_id: &ShortId,
+        history: &FeedbackHistory,
+    ) -> Result<SessionReport, ClientError> {
+        let room = client.get_room_info(short_id).await?;
+        let stats = client.get_room_stats_by_room_id(&room.id).await?;
+        let feedback = client.get_feedback_by_room_id(&room.id).await?;
+        let comments = client.get_comments(short_id).await?;
+
+        let mut content_ids = vec![];
+        for group in client.get_content_groups(short_id).await? {
+            for content_id in group.content_ids {
+                if !content_ids.contains(&content_id) {
+                    content_ids.push(content_id);
+                }
+            }
+        }
+
+        let mut contents = vec![];
+        for content_id in content_ids {
+            contents.push(ContentReport {
+                content: client.get_content(short_id, &content_id).await?,
+                statistics: client.get_answer_stats(short_id, &content_id).await?,
+            });
+        }
+
+        Ok(SessionReport {
+            generated_at: SystemTime::now(),
+            room,
+            stats,
+            feedback,
+            feedback_history: history.snapshots().cloned().collect(),
+            comments,
+            contents,
+        })
+    }
+}
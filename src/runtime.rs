@@ -0,0 +1,49 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+use crate::transport::BoxFuture;
+
+/// Spawns background tasks and provides timers used by websocket subscriptions
+///
+/// Implement this trait to run subscriptions on other async runtimes than tokio, e.g. async-std
+/// or smol. Channels used by the client are runtime independent. The default transports still
+/// require tokio, use custom transports on other runtimes.
+pub trait Runtime: Send + Sync {
+    /// Spawns given future as background task
+    fn spawn(&self, future: BoxFuture<'static, ()>);
+
+    /// Returns a future completing after given duration
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// The default `Runtime` using tokio
+#[derive(Default)]
+pub struct TokioRuntime;
+
+impl Runtime for TokioRuntime {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        tokio::spawn(future);
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
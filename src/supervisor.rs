@@ -0,0 +1,212 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::FutureExt;
+use tokio::select;
+use tokio::sync::watch;
+
+use crate::client::{Client, ClientError, LoggedIn};
+
+/// The policy to restart failed tasks of a `Supervisor`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Failed tasks are not restarted
+    Never,
+    /// Failed tasks are restarted after given delay, at most given number of times
+    OnFailure { delay: Duration, max_restarts: u32 },
+}
+
+/// The health of a task of a `Supervisor`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskHealth {
+    /// The task is running after given number of restarts
+    Running { restarts: u32 },
+    /// The task failed with given error and will be restarted after the configured delay
+    Restarting { restarts: u32, error: String },
+    /// The task has finished, e.g. because its channel was closed
+    Finished,
+    /// The task failed with given error and will not be restarted
+    Failed(String),
+    /// The task was stopped
+    Stopped,
+}
+
+impl TaskHealth {
+    /// Indicates if the task has terminated and will not run again
+    pub fn is_terminated(&self) -> bool {
+        matches!(
+            self,
+            TaskHealth::Finished | TaskHealth::Failed(_) | TaskHealth::Stopped
+        )
+    }
+}
+
+/// A handle to a task of a `Supervisor`
+#[derive(Clone)]
+pub struct TaskHandle {
+    name: String,
+    health: watch::Receiver<TaskHealth>,
+    stop: Arc<watch::Sender<bool>>,
+}
+
+impl TaskHandle {
+    /// Returns the name of the task
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the current health of the task
+    pub fn health(&self) -> TaskHealth {
+        self.health.borrow().clone()
+    }
+
+    /// Stops the task, use `join()` to wait until it has stopped
+    pub fn stop(&self) {
+        self.stop.send_replace(true);
+    }
+
+    /// Waits until the task has terminated and returns its final health
+    pub async fn join(&self) -> TaskHealth {
+        let mut health = self.health.clone();
+        if let Ok(terminated) = health.wait_for(TaskHealth::is_terminated).await {
+            return terminated.clone();
+        }
+        // The task was dropped without terminating, e.g. on runtime shutdown
+        let last = health.borrow().clone();
+        last
+    }
+}
+
+/// Owns background subscription tasks of a client
+///
+/// Tasks are spawned using the `Runtime` of the client and restarted according to their
+/// `RestartPolicy` if they fail or panic. All tasks are stopped on `shutdown()` or if the
+/// supervisor and all handles of a task are dropped.
+pub struct Supervisor {
+    client: Arc<Client<LoggedIn>>,
+    tasks: Mutex<Vec<TaskHandle>>,
+}
+
+impl Supervisor {
+    /// Creates a supervisor for tasks using given client
+    pub fn new(client: Client<LoggedIn>) -> Supervisor {
+        Supervisor {
+            client: Arc::new(client),
+            tasks: Mutex::new(vec![]),
+        }
+    }
+
+    /// Returns the client shared by all tasks, e.g. to send requests
+    pub fn client(&self) -> &Arc<Client<LoggedIn>> {
+        &self.client
+    }
+
+    /// Spawns a task created by given function, which is called again on every restart
+    ///
+    /// The task finishes if the returned future completes successfully, e.g. because
+    /// the channel of a subscription was closed.
+    pub fn spawn<F, Fut>(&self, name: &str, policy: RestartPolicy, task: F) -> TaskHandle
+    where
+        F: Fn(Arc<Client<LoggedIn>>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), ClientError>> + Send + 'static,
+    {
+        let (health_tx, health_rx) = watch::channel(TaskHealth::Running { restarts: 0 });
+        let (stop_tx, mut stop_rx) = watch::channel(false);
+        let client = self.client.clone();
+        let runtime = self.client.runtime();
+
+        let run = async move {
+            let mut restarts = 0;
+            loop {
+                health_tx.send_replace(TaskHealth::Running { restarts });
+                let result = select! {
+                    result = AssertUnwindSafe(task(client.clone())).catch_unwind() => result,
+                    _ = stop_rx.wait_for(|stop| *stop) => break,
+                };
+                let error = match result {
+                    Ok(Ok(())) => {
+                        health_tx.send_replace(TaskHealth::Finished);
+                        return;
+                    }
+                    Ok(Err(err)) => err.to_string(),
+                    Err(_) => "task panicked".to_string(),
+                };
+                match policy {
+                    RestartPolicy::OnFailure {
+                        delay,
+                        max_restarts,
+                    } if restarts < max_restarts => {
+                        warn!(%error, "restarting failed task");
+                        restarts += 1;
+                        health_tx.send_replace(TaskHealth::Restarting { restarts, error });
+                        select! {
+                            _ = runtime.sleep(delay) => {}
+                            _ = stop_rx.wait_for(|stop| *stop) => break,
+                        }
+                    }
+                    _ => {
+                        warn!(%error, "task failed");
+                        health_tx.send_replace(TaskHealth::Failed(error));
+                        return;
+                    }
+                }
+            }
+            health_tx.send_replace(TaskHealth::Stopped);
+        };
+        self.client.runtime().spawn(Box::pin(run));
+
+        let handle = TaskHandle {
+            name: name.to_string(),
+            health: health_rx,
+            stop: Arc::new(stop_tx),
+        };
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.push(handle.clone());
+        }
+        handle
+    }
+
+    /// Returns the name and health of all tasks
+    pub fn health(&self) -> Vec<(String, TaskHealth)> {
+        match self.tasks.lock() {
+            Ok(tasks) => tasks
+                .iter()
+                .map(|task| (task.name.clone(), task.health()))
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    /// Stops all tasks and waits until they have terminated
+    pub async fn shutdown(&self) {
+        let tasks = match self.tasks.lock() {
+            Ok(tasks) => tasks.clone(),
+            Err(_) => return,
+        };
+        tasks.iter().for_each(TaskHandle::stop);
+        for task in tasks {
+            task.join().await;
+        }
+    }
+}
@@ -0,0 +1,66 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::future::Future;
+use std::pin::Pin;
+
+use reqwest::{Request, Response};
+
+use crate::client::ClientError;
+use crate::client::ClientError::ConnectionError;
+
+#[cfg(feature = "websocket")]
+mod ws;
+
+#[cfg(feature = "websocket")]
+pub use ws::{TungsteniteTransport, WsRead, WsTransport, WsWrite};
+
+/// A boxed future returned by transports
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Executes HTTP requests of a client
+///
+/// Implement this trait to test applications against a scripted fake instead of an ARSnova server.
+/// Responses can be created using `reqwest::Response::from(http::Response)`.
+pub trait HttpTransport: Send + Sync {
+    /// Executes given request, failing on connection errors
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response, ClientError>>;
+}
+
+/// The default `HttpTransport` using a `reqwest::Client`
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> ReqwestTransport {
+        ReqwestTransport { client }
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response, ClientError>> {
+        Box::pin(async move {
+            self.client
+                .execute(request)
+                .await
+                .map_err(|_| ConnectionError)
+        })
+    }
+}
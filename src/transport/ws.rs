@@ -0,0 +1,180 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures_util::{Sink, Stream, StreamExt};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::{connect_async_tls_with_config, Connector};
+
+use crate::client::ClientError::{CertificateError, ConnectionError};
+use crate::client::{ClientError, TlsOptions};
+use crate::transport::BoxFuture;
+
+/// The sending half of a websocket connection
+pub type WsWrite = Pin<Box<dyn Sink<Message, Error = WsError> + Send>>;
+
+/// The receiving half of a websocket connection
+pub type WsRead = Pin<Box<dyn Stream<Item = Result<Message, WsError>> + Send>>;
+
+/// Connects websockets of a client
+///
+/// Implement this trait to test applications against a scripted fake instead of an ARSnova server.
+pub trait WsTransport: Send + Sync {
+    /// Connects the websocket using given handshake request, failing on connection errors
+    fn connect(&self, request: Request) -> BoxFuture<'_, Result<(WsWrite, WsRead), ClientError>>;
+}
+
+/// The default `WsTransport` using tungstenite
+#[derive(Default)]
+pub struct TungsteniteTransport {
+    tls_config: Option<Arc<ClientConfig>>,
+}
+
+impl TungsteniteTransport {
+    /// Creates a transport using given TLS options
+    pub(crate) fn with_tls_options(
+        options: &TlsOptions,
+    ) -> Result<TungsteniteTransport, ClientError> {
+        Ok(TungsteniteTransport {
+            tls_config: tls_config(options)?,
+        })
+    }
+}
+
+impl WsTransport for TungsteniteTransport {
+    fn connect(&self, request: Request) -> BoxFuture<'_, Result<(WsWrite, WsRead), ClientError>> {
+        Box::pin(async move {
+            let connector = self.tls_config.clone().map(Connector::Rustls);
+            let (socket, _) = connect_async_tls_with_config(request, None, false, connector)
+                .await
+                .map_err(|_| ConnectionError)?;
+            let (write, read) = socket.split();
+            Ok((Box::pin(write) as WsWrite, Box::pin(read) as WsRead))
+        })
+    }
+}
+
+/// Creates the TLS configuration of websocket connections from given TLS options
+///
+/// Returns `None` if no options are set and the default configuration can be used.
+fn tls_config(options: &TlsOptions) -> Result<Option<Arc<ClientConfig>>, ClientError> {
+    if options.root_certificates.is_empty()
+        && options.identity.is_none()
+        && !options.accept_invalid_certs
+    {
+        return Ok(None);
+    }
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|_| ConnectionError)?;
+
+    let builder = if options.accept_invalid_certs {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoCertificateVerification(provider)))
+    } else {
+        let mut roots = RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        for pem in &options.root_certificates {
+            for certificate in CertificateDer::pem_slice_iter(pem) {
+                roots
+                    .add(certificate.map_err(|_| CertificateError)?)
+                    .map_err(|_| CertificateError)?;
+            }
+        }
+        builder.with_root_certificates(roots)
+    };
+
+    let config = match &options.identity {
+        Some(pem) => {
+            let chain = CertificateDer::pem_slice_iter(pem)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| CertificateError)?;
+            let key = PrivateKeyDer::from_pem_slice(pem).map_err(|_| CertificateError)?;
+            builder
+                .with_client_auth_cert(chain, key)
+                .map_err(|_| CertificateError)?
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    Ok(Some(Arc::new(config)))
+}
+
+/// A certificate verifier accepting any server certificate
+///
+/// Signatures are still verified to complete the handshake.
+#[derive(Debug)]
+struct NoCertificateVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
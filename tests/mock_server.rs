@@ -0,0 +1,236 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#![cfg(all(feature = "test-util", feature = "websocket"))]
+
+use std::future::Future;
+use std::time::Duration;
+
+use arsnova_client::client::ClientError;
+use arsnova_client::models::Content;
+use arsnova_client::transport::{BoxFuture, HttpTransport};
+use arsnova_client::{
+    Client, Envelope, EventKinds, Feedback, FeedbackEvent, FeedbackValue, MockServer, RoomEvent,
+    ShortId,
+};
+use futures_util::{Stream, StreamExt};
+use reqwest::{Request, Response};
+
+/// Fails the test if given future does not complete within five seconds
+async fn timeout<F: Future>(future: F) -> F::Output {
+    tokio::time::timeout(Duration::from_secs(5), future)
+        .await
+        .expect("completed in time")
+}
+
+/// Waits for the next changed feedback of given stream matching given predicate
+async fn next_feedback<S: Stream<Item = (ShortId, Envelope<RoomEvent>)> + Unpin>(
+    events: &mut S,
+    predicate: impl Fn(&Feedback) -> bool,
+) -> Feedback {
+    timeout(async {
+        while let Some((_, envelope)) = events.next().await {
+            if let RoomEvent::Feedback(FeedbackEvent::Changed(feedback)) = envelope.event {
+                if predicate(&feedback) {
+                    return feedback;
+                }
+            }
+        }
+        panic!("stream ended")
+    })
+    .await
+}
+
+fn mock_client(server: &MockServer) -> Client {
+    Client::builder("http://localhost/api")
+        .mock_server(server)
+        .build()
+        .expect("client created")
+}
+
+#[tokio::test]
+async fn should_login_as_guest() {
+    let server = MockServer::new();
+
+    let client = mock_client(&server).guest_login().await.expect("logged in");
+
+    assert_eq!(server.guest_logins(), 1);
+    assert!(client.get_user_id().unwrap().as_str().starts_with("guest-"));
+}
+
+#[tokio::test]
+async fn should_propagate_votes_to_subscribers() {
+    let server = MockServer::new();
+    let short_id = ShortId::from("12345678");
+    server.add_room(&short_id, "Test Room");
+
+    let voter = mock_client(&server).guest_login().await.expect("logged in");
+    let listener = mock_client(&server).guest_login().await.expect("logged in");
+    let events = listener
+        .monitor_rooms(std::slice::from_ref(&short_id))
+        .await
+        .expect("monitoring");
+    let mut events = Box::pin(events);
+
+    voter
+        .send_feedback(&short_id, FeedbackValue::Bad)
+        .await
+        .expect("feedback sent");
+
+    let feedback = next_feedback(&mut events, |feedback| feedback.count_votes() > 0).await;
+    assert_eq!(feedback, Feedback::from_values([0, 0, 1, 0]));
+    assert_eq!(server.feedback(&short_id), Some(feedback));
+}
+
+#[tokio::test]
+async fn should_reconnect_closed_connections() {
+    let server = MockServer::new();
+    let short_id = ShortId::from("12345678");
+    server.add_room(&short_id, "Test Room");
+
+    let client = Client::builder("http://localhost/api")
+        .mock_server(&server)
+        .reconnect_delay(Duration::from_millis(10))
+        .build()
+        .expect("client created")
+        .guest_login()
+        .await
+        .expect("logged in");
+    let events = client
+        .subscribe_room_events(&short_id, EventKinds::FEEDBACK)
+        .await
+        .expect("subscribed")
+        .map(|envelope| (short_id.clone(), envelope));
+    let mut events = Box::pin(events);
+    assert_eq!(server.connections(), 1);
+
+    server.close_connections();
+    timeout(async {
+        while server.connections() < 2 {
+            let _ = tokio::time::timeout(Duration::from_millis(10), events.next()).await;
+        }
+    })
+    .await;
+
+    server.set_feedback(&short_id, Feedback::from_values([0, 0, 0, 4]));
+    let feedback = next_feedback(&mut events, |feedback| feedback.very_bad == 4).await;
+    assert_eq!(feedback, Feedback::from_values([0, 0, 0, 4]));
+}
+
+#[tokio::test]
+async fn should_report_unknown_content_group() {
+    let server = MockServer::new();
+    let short_id = ShortId::from("12345678");
+    server.add_room(&short_id, "Test Room");
+    server.add_content(
+        &short_id,
+        Content {
+            subject: "Question".to_string(),
+            groups: vec!["Lecture 1".to_string()],
+            ..Default::default()
+        },
+    );
+
+    let client = mock_client(&server).guest_login().await.expect("logged in");
+
+    let stats = client
+        .get_content_group_stats(&short_id, "Lecture 1")
+        .await
+        .expect("stats");
+    assert_eq!(stats.len(), 1);
+    assert_eq!(
+        client.get_content_group_stats(&short_id, "Lecture 2").await,
+        Err(ClientError::ContentGroupNotFoundError(
+            "Lecture 2".to_string()
+        ))
+    );
+}
+
+#[tokio::test]
+async fn should_report_suppressed_votes() {
+    let server = MockServer::new();
+    let short_id = ShortId::from("12345678");
+    server.add_room(&short_id, "Test Room");
+
+    let client = Client::builder("http://localhost/api")
+        .mock_server(&server)
+        .dedup_votes(Duration::from_secs(60))
+        .build()
+        .expect("client created")
+        .guest_login()
+        .await
+        .expect("logged in");
+
+    timeout(client.send_feedback(&short_id, FeedbackValue::Good))
+        .await
+        .expect("vote sent");
+    assert_eq!(
+        timeout(client.send_feedback(&short_id, FeedbackValue::Good)).await,
+        Err(ClientError::VoteSuppressedError)
+    );
+    timeout(client.send_feedback(&short_id, FeedbackValue::Bad))
+        .await
+        .expect("vote sent");
+    assert_eq!(
+        server.feedback(&short_id),
+        Some(Feedback::from_values([0, 0, 1, 0]))
+    );
+}
+
+/// Answers room summary requests with an additional summary without room ID,
+/// all other requests using a mock server
+struct SummaryWithoutId(MockServer);
+
+impl HttpTransport for SummaryWithoutId {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response, ClientError>> {
+        if !request.url().path().ends_with("/_view/room/summary") {
+            return self.0.execute(request);
+        }
+        let mut summaries = vec![serde_json::json!({ "stats": { "contentCount": 3 } })];
+        for (_, ids) in request.url().query_pairs().filter(|(key, _)| key == "ids") {
+            for id in ids.split(',') {
+                summaries.push(serde_json::json!({ "id": id, "stats": { "contentCount": 1 } }));
+            }
+        }
+        let body = serde_json::Value::Array(summaries).to_string();
+        Box::pin(async move { Ok(Response::from(http::Response::new(body))) })
+    }
+}
+
+#[tokio::test]
+async fn should_skip_room_stats_without_room_id() {
+    let server = MockServer::new();
+    let room_id = server.add_room(&ShortId::from("12345678"), "Test Room").id;
+
+    let client = Client::builder("http://localhost/api")
+        .http_transport(SummaryWithoutId(server))
+        .build()
+        .expect("client created")
+        .guest_login()
+        .await
+        .expect("logged in");
+
+    let stats = client
+        .get_rooms_stats(std::slice::from_ref(&room_id))
+        .await
+        .expect("stats");
+
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[&room_id].content_count, 1);
+}
@@ -0,0 +1,100 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#![cfg(feature = "test-util")]
+
+use std::io::{self, Cursor, Write};
+use std::sync::{Arc, Mutex};
+
+use arsnova_client::{Client, MockServer, Recorder, Replay, ShortId};
+
+/// Shared buffer to read records written by a `Recorder`
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn should_replay_recorded_login() {
+    let server = MockServer::new();
+    let short_id = ShortId::from("12345678");
+    server.add_room(&short_id, "Test Room");
+
+    let buffer = Buffer::default();
+    let client = Client::builder("http://localhost/api")
+        .mock_server(&server)
+        .recorder(Recorder::new(buffer.clone()))
+        .build()
+        .expect("client created")
+        .guest_login()
+        .await
+        .expect("logged in");
+    let recorded_profile = client.get_user_profile().await.expect("profile");
+    let recorded_room = client.get_room_info(&short_id).await.expect("room info");
+
+    let records = buffer.0.lock().unwrap().clone();
+    let trace = String::from_utf8(records.clone()).unwrap();
+    assert!(!trace.contains(".mock\""));
+
+    let client = Client::builder("http://localhost/api")
+        .replay(Replay::from_reader(Cursor::new(records)).expect("replay"))
+        .build()
+        .expect("client created")
+        .guest_login()
+        .await
+        .expect("logged in");
+
+    assert_eq!(client.get_user_id().unwrap(), recorded_profile.id);
+    assert_eq!(client.get_user_profile().await.unwrap(), recorded_profile);
+    assert_eq!(
+        client.get_room_info(&short_id).await.unwrap(),
+        recorded_room
+    );
+}
+
+#[tokio::test]
+async fn should_not_record_confirmation_token() {
+    let server = MockServer::new();
+    let buffer = Buffer::default();
+    let client = Client::builder("http://localhost/api")
+        .mock_server(&server)
+        .recorder(Recorder::new(buffer.clone()))
+        .build()
+        .expect("client created")
+        .guest_login()
+        .await
+        .expect("logged in");
+
+    client
+        .delete_account("secret-confirmation")
+        .await
+        .expect("account deleted");
+
+    let trace = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    assert!(trace.contains("confirmationToken"));
+    assert!(!trace.contains("secret-confirmation"));
+}
@@ -0,0 +1,260 @@
+/*
+ * This file is part of arsnova-client
+ *
+ * Copyright (C) 2023  Paul-Christian Volkmer
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Lesser General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#![cfg(all(feature = "test-util", feature = "websocket"))]
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use arsnova_client::client::{ClientError, LoggedIn};
+use arsnova_client::transport::{BoxFuture, WsRead, WsWrite};
+use arsnova_client::{
+    Client, Counter, EventKinds, Feedback, FeedbackEvent, MetricsSink, MockServer, RoomEvent,
+    ShortId, WsTransport,
+};
+use futures_util::{stream, SinkExt, Stream, StreamExt};
+use http::Request;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+
+const CONNECTED: &str = "CONNECTED\nversion:1.2\nheart-beat:0,0\n\n\0";
+
+/// A websocket transport receiving scripted messages on each connection, ignoring sent messages
+struct ScriptedTransport {
+    messages: Vec<&'static str>,
+}
+
+impl WsTransport for ScriptedTransport {
+    fn connect(&self, _: Request<()>) -> BoxFuture<'_, Result<(WsWrite, WsRead), ClientError>> {
+        let messages = self.messages.clone();
+        Box::pin(async move {
+            let read = stream::iter(messages)
+                .map(|message| Message::Text(message.to_string()))
+                .map(Ok)
+                .chain(stream::pending());
+            let write =
+                futures_util::sink::drain().sink_map_err(|never| -> WsError { match never {} });
+            Ok((Box::pin(write) as WsWrite, Box::pin(read) as WsRead))
+        })
+    }
+}
+
+/// Counts rejected STOMP frames
+#[derive(Clone, Default)]
+struct RejectedFrames(Arc<AtomicU64>);
+
+impl MetricsSink for RejectedFrames {
+    fn increment(&self, counter: Counter, value: u64) {
+        if counter == Counter::RejectedFrames {
+            self.0.fetch_add(value, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Creates a logged in client receiving given scripted messages on websocket connections
+async fn scripted_client(
+    messages: Vec<&'static str>,
+    rejected: &RejectedFrames,
+    max_frame_size: usize,
+) -> Client<LoggedIn> {
+    let server = MockServer::new();
+    server.add_room(&ShortId::from("12345678"), "Test Room");
+
+    Client::builder("http://localhost/api")
+        .mock_server(&server)
+        .ws_transport(ScriptedTransport { messages })
+        .metrics_sink(rejected.clone())
+        .max_frame_size(max_frame_size)
+        .build()
+        .expect("client created")
+        .guest_login()
+        .await
+        .expect("logged in")
+}
+
+/// Subscribes to the feedback of the mock room, returning changed feedback only
+async fn subscribe(
+    client: &Client<LoggedIn>,
+) -> Result<impl Stream<Item = Feedback> + '_, ClientError> {
+    let events = client
+        .subscribe_room_events(&ShortId::from("12345678"), EventKinds::FEEDBACK)
+        .await?;
+    Ok(events.filter_map(|envelope| async move {
+        match envelope.event {
+            RoomEvent::Feedback(FeedbackEvent::Changed(feedback)) => Some(feedback),
+            _ => None,
+        }
+    }))
+}
+
+/// Collects all feedback received within a second
+async fn collect<S: Stream<Item = Feedback>>(events: S) -> Vec<Feedback> {
+    events
+        .take_until(tokio::time::sleep(Duration::from_secs(1)))
+        .collect()
+        .await
+}
+
+#[tokio::test]
+async fn should_reassemble_split_and_batched_frames() {
+    let rejected = RejectedFrames::default();
+    let client = scripted_client(
+        vec![
+            CONNECTED,
+            "\n",
+            "MESSAGE\nsubscription:sub-0\n\n{\"type\":\"FeedbackChanged\",\"payload\":{\"values\":[1,",
+            "0,0,0]}}\0\nMESSAGE\nsubscription:sub-0\n\n{\"type\":\"FeedbackChanged\",\"payload\":{\"values\":[2,0,0,0]}}\0",
+        ],
+        &rejected,
+        1024,
+    )
+    .await;
+    let events = subscribe(&client).await.expect("subscribed");
+
+    assert_eq!(
+        collect(events).await,
+        vec![
+            Feedback::from_values([1, 0, 0, 0]),
+            Feedback::from_values([2, 0, 0, 0])
+        ]
+    );
+    assert_eq!(rejected.0.load(Ordering::Relaxed), 0);
+}
+
+#[tokio::test]
+async fn should_reject_unexpected_frames() {
+    let rejected = RejectedFrames::default();
+    let client = scripted_client(
+        vec![
+            CONNECTED,
+            "MESSAGE\nsubscription:sub-9\n\n{\"type\":\"FeedbackChanged\",\"payload\":{\"values\":[9,0,0,0]}}\0",
+            CONNECTED,
+            "RECEIPT\nreceipt-id:1\n\n\0",
+            "MESSAGE\nsubscription:sub-0\n\n{\"type\":\"FeedbackChanged\",\"payload\":{\"values\":[1,0,0,0]}}\0",
+        ],
+        &rejected,
+        1024,
+    )
+    .await;
+    let events = subscribe(&client).await.expect("subscribed");
+
+    assert_eq!(
+        collect(events).await,
+        vec![Feedback::from_values([1, 0, 0, 0])]
+    );
+    assert_eq!(rejected.0.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn should_require_connected_frame() {
+    let rejected = RejectedFrames::default();
+    let client = scripted_client(
+        vec!["MESSAGE\nsubscription:sub-0\n\n{}\0", CONNECTED],
+        &rejected,
+        1024,
+    )
+    .await;
+    let result = subscribe(&client).await;
+
+    assert_eq!(result.err(), Some(ClientError::ConnectionError));
+    assert_eq!(rejected.0.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn should_reject_unsupported_version() {
+    let rejected = RejectedFrames::default();
+    let client = scripted_client(vec!["CONNECTED\nversion:2.0\n\n\0"], &rejected, 1024).await;
+    let result = subscribe(&client).await;
+
+    assert_eq!(result.err(), Some(ClientError::ConnectionError));
+}
+
+#[tokio::test]
+async fn should_close_on_error_frame() {
+    let rejected = RejectedFrames::default();
+    let client = scripted_client(
+        vec![
+            CONNECTED,
+            "ERROR\nmessage:failure\n\n\0",
+            "MESSAGE\nsubscription:sub-0\n\n{\"type\":\"FeedbackChanged\",\"payload\":{\"values\":[1,0,0,0]}}\0",
+        ],
+        &rejected,
+        1024,
+    )
+    .await;
+    let events = subscribe(&client).await.expect("subscribed");
+    let mut events = Box::pin(events);
+
+    let next = tokio::time::timeout(Duration::from_secs(1), events.next()).await;
+    assert_eq!(next, Ok(None));
+}
+
+#[tokio::test]
+async fn should_close_on_frames_exceeding_max_frame_size() {
+    let rejected = RejectedFrames::default();
+    let client = scripted_client(
+        vec![
+            CONNECTED,
+            "MESSAGE\nsubscription:sub-0\n\n{\"type\":\"FeedbackChanged\",\"payload\":{\"values\":[1,0,0,0]}}\0",
+        ],
+        &rejected,
+        64,
+    )
+    .await;
+    let events = subscribe(&client).await.expect("subscribed");
+    let mut events = Box::pin(events);
+
+    let next = tokio::time::timeout(Duration::from_secs(1), events.next()).await;
+    assert_eq!(next, Ok(None));
+    assert_eq!(rejected.0.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn should_accept_receipt_batched_with_connected_frame() {
+    let rejected = RejectedFrames::default();
+    let client = scripted_client(
+        vec!["CONNECTED\nversion:1.2\nheart-beat:0,0\n\n\0RECEIPT\nreceipt-id:feedback\n\n\0"],
+        &rejected,
+        1024,
+    )
+    .await;
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(1),
+        client.reset_feedback(&ShortId::from("12345678")),
+    )
+    .await;
+
+    assert_eq!(result, Ok(Ok(())));
+}
+
+#[tokio::test]
+async fn should_fail_on_error_frame_while_waiting_for_receipt() {
+    let rejected = RejectedFrames::default();
+    let client = scripted_client(
+        vec![CONNECTED, "ERROR\nmessage:forbidden\n\n\0"],
+        &rejected,
+        1024,
+    )
+    .await;
+
+    let result = client.reset_feedback(&ShortId::from("12345678")).await;
+
+    assert_eq!(result, Err(ClientError::ConnectionError));
+}